@@ -1,6 +1,6 @@
 use eyre::Result;
 
-use cargo_oh_my_dependencies::metadata::workspace_info::WorkspaceInfo;
+use cargo_oh_my_dependencies::{config::Filter, metadata::workspace_info::WorkspaceInfo};
 
 fn main() -> Result<()> {
     color_eyre::install().expect("color_eyre init");
@@ -8,7 +8,7 @@ fn main() -> Result<()> {
     // let manifest_path = "/home/robert/temp/test-crate-workpspace/Cargo.toml";
     let manifest_path = "/home/robert/projects/biz/podwriter/Cargo.toml";
     let info = WorkspaceInfo::load(manifest_path).expect("load workspace info");
-    let tree = info.tree();
+    let tree = info.tree(&Filter::default());
 
     tree.visit(&mut |node, parent| {
         println!("{:?}: {:?}", node, parent);
@@ -1,12 +1,16 @@
-use cargo_metadata::{DependencyKind, PackageId};
+use cargo_metadata::{semver::Version, DependencyKind, PackageId};
 use eyre::Result;
 
+use crate::cargo::FeatureEdit;
 use crate::metadata::dep_tree::FeatureStatus;
+use crate::metadata::workspace_info::FeatureToggle;
 
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub enum Action {
     Render,
+    /// Sent when no event arrived for a while, to check on background work.
+    Tick,
     Resize(u16, u16),
     Quit,
     Help,
@@ -16,6 +20,102 @@ pub enum Action {
         dep_name: String,
     },
 
+    ShowDuplicates,
+
+    ShowCompileTimes,
+
+    ShowDependencyMap {
+        package: PackageId,
+    },
+
+    ShowInverseTree {
+        package: PackageId,
+    },
+
+    ShowAddDependency {
+        package: PackageId,
+    },
+
+    CloseDialog,
+
+    AddDependency {
+        package: PackageId,
+        name: String,
+        version: Version,
+        kind: DependencyKind,
+        features: Vec<String>,
+        default_features: bool,
+    },
+
+    ShowRemoveDependency {
+        package: PackageId,
+        dep_name: String,
+        dep_kind: DependencyKind,
+    },
+
+    RemoveDependency {
+        package: PackageId,
+        dep_name: String,
+        dep_kind: DependencyKind,
+    },
+
+    /// Ask for the name of a new feature of a workspace package, or for an
+    /// entry to add to `feature`.
+    ShowFeatureInput {
+        package: PackageId,
+        feature: Option<String>,
+    },
+
+    ShowRemoveFeature {
+        package: PackageId,
+        feature: String,
+    },
+
+    /// Ask for the feature a dependency becomes optional behind, or confirm
+    /// making an optional dependency required.
+    ShowToggleOptional {
+        package: PackageId,
+        dep_name: String,
+        dep_kind: DependencyKind,
+    },
+
+    /// Change the `[features]` table of a workspace package.
+    EditFeatures {
+        package: PackageId,
+        edit: FeatureEdit,
+    },
+
+    /// Set the version requirement of a dependency to `version`.
+    BumpDependency {
+        package: PackageId,
+        dep_name: String,
+        dep_kind: DependencyKind,
+        version: Version,
+    },
+
+    /// Ask which features of `default` to keep before turning off the
+    /// default features of a dependency.
+    ShowDisableDefaultFeatures {
+        package: PackageId,
+        dep_name: String,
+        dep_kind: DependencyKind,
+    },
+
+    /// Set `default-features` of a dependency and add `features`.
+    SetDefaultFeatures {
+        package: PackageId,
+        dep_name: String,
+        dep_kind: DependencyKind,
+        enabled: bool,
+        features: Vec<String>,
+    },
+
+    /// Load the dependencies of the transitive dependency at index `node` of
+    /// the current dependency tree.
+    ExpandDependency {
+        node: usize,
+    },
+
     ToggleFeature {
         parent_package: PackageId,
         dep_name: String,
@@ -23,6 +123,25 @@ pub enum Action {
         feature_name: String,
         feature_status: FeatureStatus,
     },
+
+    /// Confirm enabling or disabling a feature of a dependency, depending on
+    /// its state in `package`, in all members that declare the dependency.
+    ShowApplyEverywhere {
+        package: PackageId,
+        dep_name: String,
+        dep_kind: DependencyKind,
+        feature: String,
+    },
+
+    /// Mark the selected feature to toggle it together with other marked
+    /// ones, or unmark it.
+    MarkFeature,
+
+    /// Toggle the marked features, possibly of several members and
+    /// dependencies, and refresh once.
+    ToggleFeatures {
+        features: Vec<FeatureToggle>,
+    },
 }
 
 impl Action {
@@ -1,7 +1,10 @@
 use std::path::PathBuf;
 
+use cargo_metadata::DependencyKind;
 use clap::Parser;
 
+use crate::config::{parse_kind, GraphFormat, IconSet, ThemeName};
+
 #[derive(Debug, Parser)]
 #[command(name = "cargo")]
 #[command(bin_name = "cargo")]
@@ -17,4 +20,91 @@ pub enum Args {
 pub struct Opt {
     #[clap(default_value = "Cargo.toml", help = "Path to Cargo.toml file")]
     pub manifest: PathBuf,
+
+    #[clap(
+        long,
+        help = "Compile times from `cargo build --timings`: the cargo-timing.html report or the JSON messages of `--timings=json`"
+    )]
+    pub timings: Option<PathBuf>,
+
+    #[clap(long, help = "Color theme, monochrome by default if NO_COLOR is set")]
+    pub theme: Option<ThemeName>,
+
+    #[clap(
+        long,
+        help = "Icons of the tree, ascii for terminals without Unicode fonts"
+    )]
+    pub icons: Option<IconSet>,
+
+    #[clap(
+        long,
+        help = "Don't try dependency edits in a temporary crate before writing the manifest"
+    )]
+    pub no_verify: bool,
+
+    #[clap(
+        long,
+        value_delimiter = ',',
+        value_parser = parse_kind,
+        help = "Dependency kinds to show in the tree: normal, dev, build"
+    )]
+    pub kinds: Vec<DependencyKind>,
+
+    #[clap(long, help = "Format of the feature graph")]
+    pub graph_format: Option<GraphFormat>,
+
+    #[clap(long, help = "Program to open the feature graph with")]
+    pub open: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Clone, clap::Subcommand)]
+pub enum Command {
+    /// Show every path from a workspace member to a package, like `cargo tree -i`
+    Invert {
+        #[clap(help = "Package to invert, `name` or `name@version`")]
+        package: String,
+
+        #[command(flatten)]
+        manifest: ManifestOpt,
+    },
+
+    /// Find the smallest feature set of a dependency that still compiles
+    Minimize {
+        #[clap(help = "Workspace package that declares the dependency")]
+        package: String,
+
+        #[clap(help = "Name of the dependency")]
+        dependency: String,
+
+        #[clap(long, conflicts_with = "build", help = "Minimize a dev-dependency")]
+        dev: bool,
+
+        #[clap(long, help = "Minimize a build-dependency")]
+        build: bool,
+
+        #[clap(long, help = "Write the minimal feature set to the manifest")]
+        apply: bool,
+
+        #[command(flatten)]
+        manifest: ManifestOpt,
+    },
+
+    /// List enabled dependency features that the workspace possibly doesn't use
+    Unused {
+        #[command(flatten)]
+        manifest: ManifestOpt,
+    },
+}
+
+#[derive(Debug, Clone, clap::Args)]
+pub struct ManifestOpt {
+    #[clap(
+        long = "manifest-path",
+        default_value = "Cargo.toml",
+        help = "Path to Cargo.toml file"
+    )]
+    pub path: PathBuf,
 }
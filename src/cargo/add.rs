@@ -0,0 +1,129 @@
+use cargo_metadata::{semver::Version, DependencyKind};
+use eyre::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use toml_edit::{Array, DocumentMut, InlineTable, Item, Table, Value};
+
+use crate::cargo::backup::ManifestBackup;
+use crate::metadata::toml::table_name;
+
+/// Adds a registry dependency to a manifest by editing it with `toml_edit`, so
+/// formatting and comments are kept. Like `cargo add`, the version requirement
+/// is the picked version.
+#[derive(Debug, Clone)]
+pub struct AddDependency {
+    manifest_path: PathBuf,
+    name: String,
+    version: Version,
+    kind: DependencyKind,
+    features: Vec<String>,
+    default_features: bool,
+}
+
+impl AddDependency {
+    pub fn new(
+        manifest_path: impl Into<PathBuf>,
+        name: impl Into<String>,
+        version: Version,
+    ) -> Self {
+        Self {
+            manifest_path: manifest_path.into(),
+            name: name.into(),
+            version,
+            kind: DependencyKind::Normal,
+            features: Vec::new(),
+            default_features: true,
+        }
+    }
+
+    #[must_use]
+    pub fn kind(mut self, kind: DependencyKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    #[must_use]
+    pub fn features(mut self, features: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.features = features.into_iter().map(Into::into).collect();
+        self
+    }
+
+    #[must_use]
+    pub fn default_features(mut self, default_features: bool) -> Self {
+        self.default_features = default_features;
+        self
+    }
+
+    /// The value of the dependency entry: a plain version string or an inline
+    /// table if features are involved.
+    fn entry(&self) -> Value {
+        let version = self.version.to_string();
+        if self.features.is_empty() && self.default_features {
+            return Value::from(version);
+        }
+
+        let mut table = InlineTable::new();
+        table.insert("version", Value::from(version));
+        if !self.default_features {
+            table.insert("default-features", Value::from(false));
+        }
+        if !self.features.is_empty() {
+            table.insert(
+                "features",
+                Value::Array(self.features.iter().collect::<Array>()),
+            );
+        }
+        Value::InlineTable(table)
+    }
+
+    fn edit(&self, doc: &mut DocumentMut) -> Result<()> {
+        let table_name = table_name(self.kind);
+        let table = doc
+            .entry(table_name)
+            .or_insert_with(|| Item::Table(Table::new()))
+            .as_table_like_mut()
+            .ok_or_else(|| eyre::eyre!("[{table_name}] is not a table"))?;
+        if table.contains_key(&self.name) {
+            eyre::bail!("{} is already in [{table_name}]", self.name);
+        }
+        table.insert(&self.name, Item::Value(self.entry()));
+        Ok(())
+    }
+
+    /// Writes the dependency and checks that cargo can resolve the manifest.
+    /// If not, the manifest is restored.
+    pub fn apply(self) -> Result<()> {
+        let content = std::fs::read_to_string(&self.manifest_path)
+            .with_context(|| format!("reading {:?}", self.manifest_path))?;
+        let mut doc = content.parse::<DocumentMut>()?;
+        self.edit(&mut doc)?;
+
+        // auto restores when dropped unless disposed
+        let backup =
+            ManifestBackup::create(&self.manifest_path).context("creating manifest backup")?;
+        std::fs::write(&self.manifest_path, doc.to_string())?;
+
+        debug!(manifest = ?self.manifest_path, "verifying added dependency {}", self.name);
+        check_manifest(&self.manifest_path)
+            .with_context(|| format!("failed to add {}@{}", self.name, self.version))?;
+
+        backup.dispose();
+        Ok(())
+    }
+}
+
+/// Runs `cargo metadata` to check that the manifest is valid and its
+/// dependencies resolve. This also updates the lock file.
+pub(super) fn check_manifest(manifest_path: &Path) -> Result<()> {
+    let output = Command::new("cargo")
+        .arg("metadata")
+        .arg("--format-version=1")
+        .arg("--manifest-path")
+        .arg(manifest_path)
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        eyre::bail!("{}", stderr.trim());
+    }
+    Ok(())
+}
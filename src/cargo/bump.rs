@@ -0,0 +1,187 @@
+use cargo_metadata::{semver::Version, DependencyKind, Package};
+use eyre::{Context, Result};
+use std::path::PathBuf;
+use toml_edit::{DocumentMut, Item, TableLike, Value};
+
+use crate::cargo::add::check_manifest;
+use crate::cargo::backup::ManifestBackup;
+use crate::metadata::toml::table_name;
+
+/// Sets the version requirement of a dependency. Dependencies inherited with
+/// `workspace = true` are bumped in `[workspace.dependencies]` of the
+/// workspace manifest.
+#[derive(Debug, Clone)]
+pub struct BumpDependency<'a> {
+    package: &'a Package,
+    dep_name: &'a str,
+    dep_kind: DependencyKind,
+    version: Version,
+    workspace_manifest: Option<PathBuf>,
+}
+
+impl<'a> BumpDependency<'a> {
+    pub fn new(
+        package: &'a Package,
+        dep_name: &'a str,
+        dep_kind: DependencyKind,
+        version: Version,
+    ) -> Self {
+        Self {
+            package,
+            dep_name,
+            dep_kind,
+            version,
+            workspace_manifest: None,
+        }
+    }
+
+    /// Where inherited dependencies are defined.
+    #[must_use]
+    pub fn workspace_manifest(mut self, path: impl Into<PathBuf>) -> Self {
+        self.workspace_manifest = Some(path.into());
+        self
+    }
+
+    fn key(&self) -> &str {
+        self.package
+            .dependencies
+            .iter()
+            .find(|dep| dep.name == self.dep_name && dep.kind == self.dep_kind)
+            .and_then(|dep| dep.rename.as_deref())
+            .unwrap_or(self.dep_name)
+    }
+
+    /// Bumps every entry of the dependency in the member manifest, in the
+    /// plain and the `[target.*]` tables. Returns whether an entry is
+    /// inherited from the workspace.
+    fn edit_member(&self, doc: &mut DocumentMut) -> Result<bool> {
+        let table_name = table_name(self.dep_kind);
+        let key = self.key();
+
+        let mut entries = Vec::new();
+        if let Some(table) = doc.get_mut(table_name).and_then(Item::as_table_like_mut) {
+            entries.extend(bump_entry(table, key, &self.version).transpose());
+        }
+        if let Some(targets) = doc.get_mut("target").and_then(Item::as_table_like_mut) {
+            for (_, target) in targets.iter_mut() {
+                if let Some(table) = target.get_mut(table_name).and_then(Item::as_table_like_mut) {
+                    entries.extend(bump_entry(table, key, &self.version).transpose());
+                }
+            }
+        }
+
+        if entries.is_empty() {
+            eyre::bail!("{key} not found in [{table_name}] of {}", self.package.name);
+        }
+        let mut inherited = false;
+        for entry in entries {
+            inherited |= entry.with_context(|| format!("bumping {key} in [{table_name}]"))?;
+        }
+        Ok(inherited)
+    }
+
+    fn edit_workspace(&self, doc: &mut DocumentMut) -> Result<()> {
+        let key = self.key();
+        let Some(item) = doc
+            .get_mut("workspace")
+            .and_then(|w| w.get_mut("dependencies"))
+            .and_then(|deps| deps.get_mut(key))
+        else {
+            eyre::bail!("{key} not found in [workspace.dependencies]");
+        };
+        set_requirement(item, &self.version)
+            .with_context(|| format!("bumping {key} in [workspace.dependencies]"))
+    }
+
+    pub fn apply(self) -> Result<()> {
+        let member_manifest = self.package.manifest_path.as_std_path();
+        let mut doc = read(member_manifest)?;
+
+        // auto restores when dropped unless disposed
+        let member_backup =
+            ManifestBackup::create(member_manifest).context("creating manifest backup")?;
+        let inherited = self.edit_member(&mut doc)?;
+        let workspace_backup = match &self.workspace_manifest {
+            _ if !inherited => None,
+            None => eyre::bail!(
+                "{} is inherited but the workspace is unknown",
+                self.dep_name
+            ),
+            // the root package of the workspace
+            Some(workspace_manifest) if workspace_manifest == member_manifest => {
+                self.edit_workspace(&mut doc)?;
+                None
+            }
+            Some(workspace_manifest) => {
+                let mut workspace_doc = read(workspace_manifest)?;
+                self.edit_workspace(&mut workspace_doc)?;
+                let backup = ManifestBackup::create(workspace_manifest)
+                    .context("creating manifest backup")?;
+                std::fs::write(workspace_manifest, workspace_doc.to_string())?;
+                Some(backup)
+            }
+        };
+        std::fs::write(member_manifest, doc.to_string())?;
+
+        debug!(manifest = ?member_manifest, "verifying bumped dependency {}", self.dep_name);
+        check_manifest(member_manifest)
+            .with_context(|| format!("failed to bump {} to {}", self.dep_name, self.version))?;
+
+        member_backup.dispose();
+        if let Some(backup) = workspace_backup {
+            backup.dispose();
+        }
+        Ok(())
+    }
+}
+
+/// Bumps `key` in a dependency table. Returns `None` if the table doesn't
+/// contain it and whether it is inherited from the workspace otherwise.
+fn bump_entry(table: &mut dyn TableLike, key: &str, version: &Version) -> Result<Option<bool>> {
+    let Some(item) = table.get_mut(key) else {
+        return Ok(None);
+    };
+    let inherited = item
+        .as_table_like()
+        .and_then(|t| t.get("workspace"))
+        .and_then(Item::as_bool)
+        .unwrap_or(false);
+    if !inherited {
+        set_requirement(item, version)?;
+    }
+    Ok(Some(inherited))
+}
+
+fn read(manifest_path: &std::path::Path) -> Result<DocumentMut> {
+    let content = std::fs::read_to_string(manifest_path)
+        .with_context(|| format!("reading {manifest_path:?}"))?;
+    Ok(content.parse::<DocumentMut>()?)
+}
+
+/// Replaces the version of a `dep = "1.0"` or `dep = { version = "1.0" }`
+/// entry. `=` and `~` operators are kept, anything else becomes a caret
+/// requirement like `cargo add` writes it.
+fn set_requirement(item: &mut Item, version: &Version) -> Result<()> {
+    let version_value = match item {
+        Item::Value(Value::String(_)) => item.as_value_mut(),
+        _ => item
+            .as_table_like_mut()
+            .and_then(|table| table.get_mut("version"))
+            .and_then(Item::as_value_mut),
+    };
+    let Some(value) = version_value else {
+        eyre::bail!("no version requirement");
+    };
+    let Some(old) = value.as_str() else {
+        eyre::bail!("version requirement is not a string");
+    };
+
+    let operator = match old.trim_start().chars().next() {
+        Some(op @ ('=' | '~')) => op.to_string(),
+        _ => String::new(),
+    };
+    let decor = value.decor().clone();
+    *value = Value::from(format!("{operator}{version}"));
+    *value.decor_mut() = decor;
+    Ok(())
+}
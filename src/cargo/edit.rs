@@ -3,8 +3,10 @@ use eyre::{Context, Result};
 use std::collections::HashSet;
 use std::path::PathBuf;
 use std::process::Command;
+use toml_edit::{DocumentMut, Item};
 
 use crate::cargo::backup::ManifestBackup;
+use crate::metadata::toml::table_name;
 
 #[derive(Debug, Clone)]
 pub struct EditDependency<'a> {
@@ -14,8 +16,11 @@ pub struct EditDependency<'a> {
     dep_kind: DependencyKind,
     features_to_add: HashSet<String>,
     features_to_remove: HashSet<String>,
+    /// `None` keeps the current `default-features` setting.
+    default_features: Option<bool>,
     is_workspace_dependency_at: Option<PathBuf>,
     dry_run: bool,
+    verify: bool,
     is_test_install: bool,
 }
 
@@ -28,8 +33,10 @@ impl<'a> EditDependency<'a> {
             dep_kind,
             features_to_add: Default::default(),
             features_to_remove: Default::default(),
+            default_features: None,
             is_workspace_dependency_at: None,
             dry_run: false,
+            verify: true,
             is_test_install: false,
         }
     }
@@ -40,18 +47,27 @@ impl<'a> EditDependency<'a> {
         self
     }
 
+    /// Whether to try the edit in a temporary crate before applying it.
+    #[must_use]
+    pub fn verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    /// Edit the manifest at `path` instead of the one of the package, e.g. a
+    /// copy of the workspace.
+    #[must_use]
+    pub fn manifest_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.manifest_path = path.into();
+        self
+    }
+
     #[must_use]
     pub fn toggle_feature(mut self, feature: impl Into<String>) -> Self {
         let feature = feature.into();
         let remove = self
-            .package
-            .dependencies
-            .iter()
-            .find(|dep| dep.name == self.dep_name)
-            .map(|dep| {
-                (feature == "default" && dep.uses_default_features)
-                    || dep.features.contains(&feature)
-            })
+            .existing()
+            .map(|dep| dep.features.contains(&feature))
             .unwrap_or(false);
 
         if remove {
@@ -63,6 +79,12 @@ impl<'a> EditDependency<'a> {
         self
     }
 
+    #[must_use]
+    pub fn default_features(mut self, enabled: bool) -> Self {
+        self.default_features = Some(enabled);
+        self
+    }
+
     #[must_use]
     pub fn set_workspace_dependency_at(mut self, path: Option<impl Into<PathBuf>>) -> Self {
         self.is_workspace_dependency_at = path.map(Into::into);
@@ -88,29 +110,123 @@ impl<'a> EditDependency<'a> {
         clone
     }
 
-    fn cmd(&self) -> String {
-        let mut cmds = Vec::new();
-        let mut features = HashSet::new();
+    /// The entry of the dependency with `dep_kind`, preferring the one that
+    /// is not specific to a target.
+    fn existing(&self) -> Option<&cargo_metadata::Dependency> {
+        self.package
+            .dependencies
+            .iter()
+            .filter(|dep| dep.name == self.dep_name && dep.kind == self.dep_kind)
+            .min_by_key(|dep| dep.target.is_some())
+    }
+
+    /// The `--dev`/`--build` and `--target` arguments that select the table
+    /// of the existing entry.
+    fn table_args(&self) -> Vec<String> {
+        let mut args = self
+            .kind_arg()
+            .map(String::from)
+            .into_iter()
+            .collect::<Vec<_>>();
+        if let Some(target) = self.existing().and_then(|dep| dep.target.as_ref()) {
+            args.push("--target".to_string());
+            args.push(target.to_string());
+        }
+        args
+    }
 
-        let kind_param = match self.dep_kind {
-            DependencyKind::Development => "--dev",
-            DependencyKind::Build => "--build",
-            _ => "",
+    fn kind_arg(&self) -> Option<&'static str> {
+        match self.dep_kind {
+            DependencyKind::Development => Some("--dev"),
+            DependencyKind::Build => Some("--build"),
+            _ => None,
+        }
+    }
+
+    /// The arguments of the `cargo rm` that drops the existing entry, if any.
+    /// `cargo add` writes it again with the edited features.
+    ///
+    /// `None` for an inherited dependency, `cargo rm` would also drop its
+    /// `[workspace.dependencies]` entry when no other member uses it.
+    fn rm_args(&self) -> Option<Vec<String>> {
+        if self.is_workspace_dependency_at.is_some() {
+            return None;
+        }
+        let existing = self.existing()?;
+        let mut args = vec!["rm".to_string()];
+        args.push(
+            existing
+                .rename
+                .as_deref()
+                .unwrap_or(self.dep_name)
+                .to_string(),
+        );
+        args.extend(self.table_args());
+        args.push("--package".to_string());
+        args.push(self.package.name.clone());
+        Some(args)
+    }
+
+    /// Drops the existing entry of an inherited dependency from the manifest
+    /// of the member, keeping the `[workspace.dependencies]` entry.
+    fn remove_inherited(&self) -> Result<()> {
+        let Some(existing) = self.existing() else {
+            return Ok(());
+        };
+        let key = existing.rename.as_deref().unwrap_or(self.dep_name);
+        let mut doc = std::fs::read_to_string(&self.manifest_path)
+            .with_context(|| format!("reading {:?}", self.manifest_path))?
+            .parse::<DocumentMut>()?;
+        let table = match &existing.target {
+            Some(target) => doc
+                .get_mut("target")
+                .and_then(|targets| targets.get_mut(target.to_string())),
+            None => Some(doc.as_item_mut()),
         };
+        if let Some(deps) = table
+            .and_then(|table| table.get_mut(table_name(self.dep_kind)))
+            .and_then(Item::as_table_like_mut)
+        {
+            deps.remove(key);
+        }
+        std::fs::write(&self.manifest_path, doc.to_string())
+            .with_context(|| format!("writing {:?}", self.manifest_path))?;
+        Ok(())
+    }
 
+    /// The arguments of the `cargo add` that writes the edited dependency.
+    ///
+    /// An inherited dependency takes its source and `default-features` from
+    /// `[workspace.dependencies]`, `cargo add` writes `workspace = true` again.
+    fn add_args(&self) -> Result<Vec<String>> {
+        let inherited = self.is_workspace_dependency_at.is_some();
+        let mut features = HashSet::new();
         let mut default_enabled = true;
-        if let Some(existing) = self
-            .package
-            .dependencies
-            .iter()
-            .find(|dep| dep.name == self.dep_name)
-        {
+        // keep the version requirement, path and rename of the existing dependency
+        let mut source_args = Vec::new();
+        if let Some(existing) = self.existing() {
             features.extend(existing.features.iter().cloned());
             default_enabled = existing.uses_default_features;
-            cmds.push(format!(
-                "(cargo rm {} {} --package {} || true)",
-                self.dep_name, kind_param, self.package.name
-            ));
+            if let Some(path) = existing.path.as_ref().filter(|_| !inherited) {
+                source_args.push("--path".to_string());
+                source_args.push(path.to_string());
+            }
+            if let Some(rename) = &existing.rename {
+                source_args.push("--rename".to_string());
+                source_args.push(rename.clone());
+            }
+        }
+        let dep_spec = match self.existing() {
+            Some(existing)
+                if existing.path.is_none()
+                    && !inherited
+                    && existing.req != cargo_metadata::semver::VersionReq::STAR =>
+            {
+                // a bare requirement is a caret requirement
+                let req = existing.req.to_string();
+                format!("{}@{}", self.dep_name, req.trim_start_matches('^'))
+            }
+            _ => self.dep_name.to_string(),
         };
 
         let features = features
@@ -120,37 +236,49 @@ impl<'a> EditDependency<'a> {
         let mut features = features
             .union(&self.features_to_add)
             .cloned()
-            .collect::<HashSet<_>>();
-
-        let default_enabled = !self.features_to_remove.contains("default")
-            && (default_enabled || features.contains("default"));
-        features.remove("default");
-
-        let features_params = features
-            .iter()
-            .map(|f| format!("--features {}", f))
-            .collect::<Vec<_>>()
-            .join(" ");
-
-        let default_feature_param = if default_enabled {
-            "--default-features"
-        } else {
-            "--no-default-features"
-        };
-
-        cmds.push(format!(
-            "cargo add {} --package {}",
-            [
-                self.dep_name,
-                &features_params,
-                default_feature_param,
-                kind_param
-            ]
-            .join(" "),
-            self.package.name
-        ));
+            .collect::<Vec<_>>();
+        features.sort();
+
+        let mut args = vec!["add".to_string(), dep_spec];
+        args.extend(source_args);
+        for feature in features {
+            args.push("--features".to_string());
+            args.push(feature);
+        }
+        match self.default_features {
+            Some(enabled) if inherited && enabled != default_enabled => eyre::bail!(
+                "default-features of {} are inherited, change them in [workspace.dependencies]",
+                self.dep_name
+            ),
+            _ if inherited => {}
+            enabled => args.push(
+                if enabled.unwrap_or(default_enabled) {
+                    "--default-features"
+                } else {
+                    "--no-default-features"
+                }
+                .to_string(),
+            ),
+        }
+        args.extend(self.table_args());
+        args.push("--package".to_string());
+        args.push(self.package.name.clone());
+        Ok(args)
+    }
 
-        cmds.join(" && ").to_string()
+    /// The commands of the edit as they would be typed in a shell.
+    fn cmd(&self) -> Result<String> {
+        let mut cmds = Vec::new();
+        if let Some(args) = self.rm_args() {
+            cmds.push(format!(
+                "(cargo rm {} {} --package {} || true)",
+                args[1],
+                self.table_args().join(" "),
+                self.package.name
+            ));
+        }
+        cmds.push(format!("cargo {}", self.add_args()?.join(" ")));
+        Ok(cmds.join(" && "))
     }
 
     fn test_install(&self) -> Result<()> {
@@ -159,17 +287,20 @@ impl<'a> EditDependency<'a> {
             .tempdir()?;
         std::fs::create_dir_all(&dir)?;
 
-        dbg!(dir.path().display());
-
-        let cmd = format!("cargo init --name {:?} --bin .", self.package.name);
-        debug!("[test_install] {cmd} in {dir:?}");
-        let status = Command::new("sh")
-            .arg("-c")
-            .arg(&cmd)
+        let args = ["init", "--name", &self.package.name, "--bin", "."];
+        debug!("[test_install] cargo {} in {dir:?}", args.join(" "));
+        // captured, the terminal belongs to the UI
+        let output = Command::new("cargo")
+            .args(args)
             .current_dir(&dir)
-            .status()?;
-        if !status.success() {
-            eyre::bail!("failed to execute command: {cmd}");
+            .output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            eyre::bail!(
+                "failed to execute: cargo {}\n{}",
+                args.join(" "),
+                stderr.trim()
+            );
         }
 
         let mut cloned = self.clone();
@@ -194,9 +325,9 @@ impl<'a> EditDependency<'a> {
         cloned.manifest_path = manifest_path.clone();
         cloned.apply()?;
 
-        println!("{}", std::fs::read_to_string(&manifest_path)?);
-
-        let _doc: toml_edit::DocumentMut = std::fs::read_to_string(&manifest_path)?.parse()?;
+        let content = std::fs::read_to_string(&manifest_path)?;
+        debug!("[test_install] edited manifest:\n{content}");
+        let _doc: toml_edit::DocumentMut = content.parse()?;
 
         if let Err(err) = dir.close() {
             error!("failed to cleanup temp dir: {err}");
@@ -206,9 +337,16 @@ impl<'a> EditDependency<'a> {
     }
 
     pub fn apply(self) -> Result<()> {
+        let rm_args = self.rm_args();
+        let add_args = self.add_args()?;
+        let inherited = self.is_workspace_dependency_at.is_some();
+
         let mut backup = if !self.is_test_install {
-            debug!("running test install for edit dependency");
-            self.for_test_install().test_install()?;
+            // a temporary crate has no `[workspace.dependencies]` to inherit from
+            if self.verify && !inherited {
+                debug!("running test install for edit dependency");
+                self.for_test_install().test_install()?;
+            }
 
             Some(ManifestBackup::create(&self.manifest_path).context("creating manifest backup")?)
         } else {
@@ -224,30 +362,44 @@ impl<'a> EditDependency<'a> {
             .map(ToOwned::to_owned)
             .unwrap_or_else(|| PathBuf::from("."));
 
-        let cmd = self.cmd();
-
-        debug!(?dir, ?cmd, "running command");
-
         if self.dry_run {
             if let Some(backup) = backup.take() {
                 backup.dispose();
             }
-            println!("DRY RUN: {cmd:?}");
+            println!("DRY RUN: {}", self.cmd()?);
             return Ok(());
         }
 
-        let status = Command::new("sh")
-            .arg("-c")
-            .arg(&cmd)
-            .current_dir(dir)
+        if let Some(args) = rm_args {
+            debug!(?dir, ?args, "running cargo");
+            // a missing entry is fine, `cargo add` writes it anyway
+            let output = Command::new("cargo")
+                .args(&args)
+                .current_dir(&dir)
+                .output()?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                debug!("cargo {} failed: {}", args.join(" "), stderr.trim());
+            }
+        } else if inherited {
+            self.remove_inherited()?;
+        }
+
+        debug!(?dir, args = ?add_args, "running cargo");
+        let output = Command::new("cargo")
+            .args(&add_args)
+            .current_dir(&dir)
             .output()?;
 
-        if !status.status.success() {
-            let stderr = String::from_utf8_lossy(&status.stderr);
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
             let stderr = stderr.trim();
-            let stdout = String::from_utf8_lossy(&status.stdout);
+            let stdout = String::from_utf8_lossy(&output.stdout);
             let stdout = stdout.trim();
-            let msg = format!("failed to execute command: {cmd}\n {stdout}\n{stderr}");
+            let msg = format!(
+                "failed to execute: cargo {}\n {stdout}\n{stderr}",
+                add_args.join(" ")
+            );
             error!("{msg}");
             eyre::bail!("{msg}");
         }
@@ -277,9 +429,44 @@ mod tests {
         let cmd = EditDependency::new(p, dep_name, DependencyKind::Normal)
             .add_feature("derive")
             .remove_feature("rc")
-            .cmd();
+            .cmd()
+            .unwrap();
 
-        let expected = "(cargo rm serde --package cargo-oh-my-dependencies || true) && cargo add serde --features derive --no-default-features  --package cargo-oh-my-dependencies";
+        let expected = "(cargo rm serde  --package cargo-oh-my-dependencies || true) && cargo add serde@1.0.197 --features derive --default-features --package cargo-oh-my-dependencies";
         assert_eq!(expected, cmd);
     }
+
+    #[test]
+    fn args_gen() {
+        let metadata = cargo_metadata::MetadataCommand::new()
+            .manifest_path(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"))
+            .exec()
+            .expect("could not get metadata");
+
+        let p = metadata.workspace_packages()[0];
+        let dep_name = "serde";
+
+        let edit = EditDependency::new(p, dep_name, DependencyKind::Normal)
+            .add_feature("derive")
+            .remove_feature("rc");
+
+        assert_eq!(
+            edit.rm_args().unwrap(),
+            ["rm", "serde", "--package", "cargo-oh-my-dependencies"]
+        );
+        let add_args = edit.add_args().unwrap();
+        // the requirement of the manifest is kept, whatever it currently is
+        assert!(add_args[1].starts_with("serde@"), "{add_args:?}");
+        assert_eq!(add_args[0], "add");
+        assert_eq!(
+            add_args[2..],
+            [
+                "--features",
+                "derive",
+                "--default-features",
+                "--package",
+                "cargo-oh-my-dependencies"
+            ]
+        );
+    }
 }
@@ -0,0 +1,532 @@
+use cargo_metadata::Package;
+use eyre::{Context, Result};
+use toml_edit::{Array, DocumentMut, InlineTable, Item, Table, TableLike, Value};
+
+use crate::cargo::add::check_manifest;
+use crate::cargo::backup::ManifestBackup;
+
+/// A change to the `[features]` table of a workspace package.
+#[derive(Debug, Clone)]
+pub enum FeatureEdit {
+    Create(String),
+    Remove(String),
+    /// Adds another feature, `dep:name` or `name/feature` to a feature.
+    /// `dep:name` makes the dependency optional.
+    AddEntry {
+        feature: String,
+        entry: String,
+    },
+    RemoveEntry {
+        feature: String,
+        entry: String,
+    },
+    /// Makes the dependency `dep` optional and adds `dep:dep` to `feature`,
+    /// which is created if needed.
+    MakeOptional {
+        dep: String,
+        feature: String,
+    },
+    /// Makes the dependency `dep` required again and removes the entries
+    /// that are only valid for optional dependencies.
+    MakeRequired {
+        dep: String,
+    },
+}
+
+/// Edits the `[features]` table of a workspace package with `toml_edit`.
+#[derive(Debug, Clone)]
+pub struct EditFeatures<'a> {
+    package: &'a Package,
+    edits: Vec<FeatureEdit>,
+}
+
+impl<'a> EditFeatures<'a> {
+    pub fn new(package: &'a Package) -> Self {
+        Self {
+            package,
+            edits: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn edit(mut self, edit: FeatureEdit) -> Self {
+        self.edits.push(edit);
+        self
+    }
+
+    /// The entries of `feature`. Features that are not in the manifest yet,
+    /// i.e. implicit features of optional dependencies, are added with the
+    /// entries cargo reports for them.
+    fn entries<'d>(&self, features: &'d mut dyn TableLike, feature: &str) -> Result<&'d mut Array> {
+        if !features.contains_key(feature) {
+            let Some(implicit) = self.package.features.get(feature) else {
+                eyre::bail!("feature {feature} not found in {}", self.package.name);
+            };
+            features.insert(
+                feature,
+                Item::Value(Value::Array(implicit.iter().collect())),
+            );
+        }
+        features
+            .get_mut(feature)
+            .and_then(Item::as_array_mut)
+            .ok_or_else(|| eyre::eyre!("feature {feature} is not an array"))
+    }
+
+    fn apply_edit(&self, doc: &mut DocumentMut, edit: &FeatureEdit) -> Result<()> {
+        let features = doc
+            .entry("features")
+            .or_insert_with(|| Item::Table(Table::new()))
+            .as_table_like_mut()
+            .ok_or_else(|| eyre::eyre!("[features] is not a table"))?;
+
+        match edit {
+            FeatureEdit::Create(name) => {
+                if features.contains_key(name) || self.package.features.contains_key(name) {
+                    eyre::bail!("feature {name} already exists");
+                }
+                features.insert(name, Item::Value(Value::Array(Array::new())));
+            }
+            FeatureEdit::Remove(name) => {
+                if features.remove(name).is_none() {
+                    if self.package.features.contains_key(name) {
+                        eyre::bail!(
+                            "feature {name} is implicit, it exists as long as the dependency {name} is optional"
+                        );
+                    }
+                    eyre::bail!("feature {name} not found in [features]");
+                }
+            }
+            FeatureEdit::AddEntry { feature, entry } => {
+                let entries = self.entries(features, feature)?;
+                if entries.iter().any(|e| e.as_str() == Some(entry)) {
+                    eyre::bail!("{entry} is already part of {feature}");
+                }
+                entries.push(entry.as_str());
+                if let Some(dep) = entry.strip_prefix("dep:") {
+                    set_optional(doc, dep, true)?;
+                }
+            }
+            FeatureEdit::RemoveEntry { feature, entry } => {
+                let entries = self.entries(features, feature)?;
+                if !entries.iter().any(|e| e.as_str() == Some(entry)) {
+                    eyre::bail!("{entry} is not part of {feature}");
+                }
+                remove_entries(entries, |e| e == entry);
+            }
+            FeatureEdit::MakeOptional { dep, feature } => {
+                let entry = format!("dep:{dep}");
+                if !features.contains_key(feature) && !self.package.features.contains_key(feature) {
+                    features.insert(feature, Item::Value(Value::Array(Array::new())));
+                }
+                let entries = self.entries(features, feature)?;
+                if !entries.iter().any(|e| e.as_str() == Some(&entry)) {
+                    entries.push(entry);
+                }
+                set_optional(doc, dep, true)?;
+            }
+            FeatureEdit::MakeRequired { dep } => {
+                let entry = format!("dep:{dep}");
+                let weak = format!("{dep}?/");
+                // without `dep:` entries cargo creates an implicit feature
+                // named like the dependency, which goes away as well
+                let implicit = !features.contains_key(dep);
+                for (_, entries) in features.iter_mut() {
+                    let Some(entries) = entries.as_array_mut() else {
+                        continue;
+                    };
+                    remove_entries(entries, |e| e == entry || (implicit && e == dep));
+                    // `dep?/feature` becomes `dep/feature`
+                    for e in entries.iter_mut() {
+                        let Some(feature) = e.as_str().and_then(|e| e.strip_prefix(&weak)) else {
+                            continue;
+                        };
+                        let decor = e.decor().clone();
+                        *e = Value::from(format!("{dep}/{feature}"));
+                        *e.decor_mut() = decor;
+                    }
+                }
+                set_optional(doc, dep, false)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn apply(self) -> Result<()> {
+        let manifest_path = self.package.manifest_path.as_std_path();
+        let content = std::fs::read_to_string(manifest_path)
+            .with_context(|| format!("reading {manifest_path:?}"))?;
+        let mut doc = content.parse::<DocumentMut>()?;
+        for edit in &self.edits {
+            self.apply_edit(&mut doc, edit)?;
+        }
+
+        // auto restores when dropped unless disposed
+        let backup = ManifestBackup::create(manifest_path).context("creating manifest backup")?;
+        std::fs::write(manifest_path, doc.to_string())?;
+
+        debug!(manifest = ?manifest_path, edits = ?self.edits, "verifying feature edits");
+        check_manifest(manifest_path).context("failed to edit features")?;
+
+        backup.dispose();
+        Ok(())
+    }
+}
+
+/// Removes the entries of a feature that `remove` matches. The first entry
+/// that is left gets the decor of the first one, e.g. no space after `[`.
+fn remove_entries(entries: &mut Array, mut remove: impl FnMut(&str) -> bool) {
+    let prefix = entries.get(0).and_then(|e| e.decor().prefix().cloned());
+    entries.retain(|e| e.as_str().is_none_or(|e| !remove(e)));
+    if let (Some(prefix), Some(first)) = (prefix, entries.get_mut(0)) {
+        first.decor_mut().set_prefix(prefix);
+    }
+}
+
+/// Sets `optional` on every normal and build dependency entry `key`, in the
+/// plain and the `[target.*]` tables. Dev dependencies can't be optional.
+pub(super) fn set_optional(doc: &mut DocumentMut, key: &str, optional: bool) -> Result<()> {
+    const TABLES: [&str; 2] = ["dependencies", "build-dependencies"];
+
+    let mut found = false;
+    for table_name in TABLES {
+        if let Some(table) = doc.get_mut(table_name).and_then(Item::as_table_like_mut) {
+            found |= set_entry_optional(table, key, optional);
+        }
+    }
+    if let Some(targets) = doc.get_mut("target").and_then(Item::as_table_like_mut) {
+        for (_, target) in targets.iter_mut() {
+            for table_name in TABLES {
+                if let Some(table) = target.get_mut(table_name).and_then(Item::as_table_like_mut) {
+                    found |= set_entry_optional(table, key, optional);
+                }
+            }
+        }
+    }
+
+    if !found {
+        eyre::bail!("{key} is not a dependency that can be optional");
+    }
+    Ok(())
+}
+
+fn set_entry_optional(table: &mut dyn TableLike, key: &str, optional: bool) -> bool {
+    let Some(item) = table.get_mut(key) else {
+        return false;
+    };
+    // `dep = "1.0"` becomes `dep = { version = "1.0", optional = true }`
+    if let Some(version) = item.as_str() {
+        if !optional {
+            return true;
+        }
+        let mut inline = InlineTable::new();
+        inline.insert("version", Value::from(version));
+        let decor = item.as_value().map(|v| v.decor().clone());
+        let mut value = Value::InlineTable(inline);
+        if let Some(decor) = decor {
+            *value.decor_mut() = decor;
+        }
+        *item = Item::Value(value);
+    }
+    if let Some(inline) = item.as_inline_table_mut() {
+        if !optional {
+            let last = inline.iter().last().is_some_and(|(k, _)| k == "optional");
+            let removed = inline.remove("optional");
+            // the space before the closing brace belongs to the last value
+            let suffix = removed
+                .filter(|_| last)
+                .and_then(|v| v.decor().suffix().cloned());
+            if let (Some(suffix), Some((_, last))) = (suffix, inline.iter_mut().last()) {
+                last.decor_mut().set_suffix(suffix);
+            }
+        } else if !inline.contains_key("optional") {
+            // keep the space before the closing brace after the new key
+            let suffix = inline
+                .iter_mut()
+                .last()
+                .and_then(|(_, v)| v.decor().suffix().cloned());
+            if let Some((_, last)) = inline.iter_mut().last() {
+                last.decor_mut().set_suffix("");
+            }
+            let mut value = Value::from(true);
+            value.decor_mut().set_prefix(" ");
+            if let Some(suffix) = suffix {
+                value.decor_mut().set_suffix(suffix);
+            }
+            inline.insert("optional", value);
+        }
+        return true;
+    }
+    let Some(entry) = item.as_table_like_mut() else {
+        return false;
+    };
+    if optional {
+        entry.insert("optional", toml_edit::value(true));
+    } else {
+        entry.remove("optional");
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Applies `edits` to `manifest` like [`EditFeatures::apply`], without
+    /// writing or checking the result.
+    fn edit(manifest: &str, edits: impl IntoIterator<Item = FeatureEdit>) -> Result<String> {
+        let dir = tempfile::Builder::new()
+            .prefix("cargo-oh-my-dependencies")
+            .tempdir()?;
+        std::fs::create_dir_all(dir.path().join("src"))?;
+        std::fs::write(dir.path().join("src/lib.rs"), "")?;
+        std::fs::write(dir.path().join("Cargo.toml"), manifest)?;
+        let metadata = cargo_metadata::MetadataCommand::new()
+            .current_dir(dir.path())
+            .no_deps()
+            .exec()?;
+
+        let editor = EditFeatures::new(&metadata.packages[0]);
+        let mut doc = manifest.parse::<DocumentMut>()?;
+        for edit in edits {
+            editor.apply_edit(&mut doc, &edit)?;
+        }
+        Ok(doc.to_string())
+    }
+
+    const PACKAGE: &str = "[package]\nname = \"a\"\nversion = \"0.1.0\"\nedition = \"2021\"\n";
+
+    fn manifest(rest: &str) -> String {
+        format!("{PACKAGE}\n{rest}")
+    }
+
+    #[test]
+    fn create_and_remove() {
+        let original = manifest("[dependencies]\nlog = \"0.4\"\n");
+        let created = edit(&original, [FeatureEdit::Create("fast".to_string())]).unwrap();
+        assert_eq!(
+            created,
+            manifest("[dependencies]\nlog = \"0.4\"\n\n[features]\nfast = []\n")
+        );
+
+        let removed = edit(&created, [FeatureEdit::Remove("fast".to_string())]).unwrap();
+        assert_eq!(
+            removed,
+            manifest("[dependencies]\nlog = \"0.4\"\n\n[features]\n")
+        );
+
+        assert!(edit(&created, [FeatureEdit::Create("fast".to_string())]).is_err());
+        assert!(edit(&original, [FeatureEdit::Remove("fast".to_string())]).is_err());
+    }
+
+    #[test]
+    fn implicit_feature() {
+        let original = manifest("[dependencies]\nlog = { version = \"0.4\", optional = true }\n");
+        // exists as long as `log` is optional, it can't be removed
+        assert!(edit(&original, [FeatureEdit::Remove("log".to_string())]).is_err());
+
+        // written with the entry cargo implies
+        let edited = edit(
+            &original,
+            [FeatureEdit::AddEntry {
+                feature: "log".to_string(),
+                entry: "log/std".to_string(),
+            }],
+        )
+        .unwrap();
+        assert_eq!(
+            edited,
+            manifest(
+                "[dependencies]\nlog = { version = \"0.4\", optional = true }\n\n[features]\nlog = [\"dep:log\", \"log/std\"]\n"
+            )
+        );
+    }
+
+    #[test]
+    fn add_and_remove_entries() {
+        let original = manifest(
+            "[dependencies]\nlog = \"0.4\" # logging\nserde = { version = \"1\" }\n\n[features]\nfast = []\n",
+        );
+        let edited = edit(
+            &original,
+            [
+                FeatureEdit::AddEntry {
+                    feature: "fast".to_string(),
+                    entry: "dep:log".to_string(),
+                },
+                FeatureEdit::AddEntry {
+                    feature: "fast".to_string(),
+                    entry: "serde/std".to_string(),
+                },
+            ],
+        )
+        .unwrap();
+        // `dep:log` makes `log` optional, the version becomes an inline table
+        assert_eq!(
+            edited,
+            manifest(
+                "[dependencies]\nlog = { version = \"0.4\", optional = true } # logging\nserde = { version = \"1\" }\n\n[features]\nfast = [\"dep:log\", \"serde/std\"]\n"
+            )
+        );
+
+        let removed = edit(
+            &edited,
+            [FeatureEdit::RemoveEntry {
+                feature: "fast".to_string(),
+                entry: "serde/std".to_string(),
+            }],
+        )
+        .unwrap();
+        assert!(removed.contains("fast = [\"dep:log\"]\n"), "{removed}");
+        // the first entry leaves no space after `[`
+        let without_first = edit(
+            &edited,
+            [FeatureEdit::RemoveEntry {
+                feature: "fast".to_string(),
+                entry: "dep:log".to_string(),
+            }],
+        )
+        .unwrap();
+        assert!(
+            without_first.contains("fast = [\"serde/std\"]\n"),
+            "{without_first}"
+        );
+        assert!(edit(
+            &removed,
+            [FeatureEdit::RemoveEntry {
+                feature: "fast".to_string(),
+                entry: "serde/std".to_string(),
+            }],
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn optional_tables() {
+        let make_optional = |manifest: &str| {
+            edit(
+                manifest,
+                [FeatureEdit::MakeOptional {
+                    dep: "log".to_string(),
+                    feature: "logging".to_string(),
+                }],
+            )
+            .unwrap()
+        };
+        let features = "\n[features]\nlogging = [\"dep:log\"]\n";
+
+        // dotted table
+        let dotted = manifest("[dependencies.log]\nversion = \"0.4\"\n");
+        assert_eq!(
+            make_optional(&dotted),
+            manifest(&format!(
+                "[dependencies.log]\nversion = \"0.4\"\noptional = true\n{features}"
+            ))
+        );
+
+        // inline table in a target table
+        let target = manifest("[target.'cfg(unix)'.dependencies]\nlog = { version = \"0.4\", default-features = false }\n");
+        assert_eq!(
+            make_optional(&target),
+            manifest(&format!(
+                "[target.'cfg(unix)'.dependencies]\nlog = {{ version = \"0.4\", default-features = false, optional = true }}\n{features}"
+            ))
+        );
+
+        // inherited from the workspace
+        let inherited = manifest(
+            "[workspace.dependencies]\nlog = \"0.4\"\n\n[dependencies]\nlog = { workspace = true }\n",
+        );
+        assert_eq!(
+            make_optional(&inherited),
+            manifest(&format!(
+                "[workspace.dependencies]\nlog = \"0.4\"\n\n[dependencies]\nlog = {{ workspace = true, optional = true }}\n{features}"
+            ))
+        );
+
+        // dev dependencies can't be optional
+        let dev = manifest("[dev-dependencies]\nlog = \"0.4\"\n");
+        assert!(edit(
+            &dev,
+            [FeatureEdit::MakeOptional {
+                dep: "log".to_string(),
+                feature: "logging".to_string(),
+            }],
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn make_required() {
+        let optional = manifest(
+            "[dependencies]\nserde = { version = \"1\", optional = true } # data\n\n[features]\njson = [\"dep:serde\", \"serde?/derive\"]\nstd = [\"serde?/std\"]\n",
+        );
+        let required = edit(
+            &optional,
+            [FeatureEdit::MakeRequired {
+                dep: "serde".to_string(),
+            }],
+        )
+        .unwrap();
+        assert_eq!(
+            required,
+            manifest(
+                "[dependencies]\nserde = { version = \"1\" } # data\n\n[features]\njson = [\"serde/derive\"]\nstd = [\"serde/std\"]\n"
+            )
+        );
+
+        // without `dep:` entries, the implicit feature is referred to by name
+        let implicit = manifest(
+            "[dependencies]\nserde = { version = \"1\", optional = true }\n\n[features]\nfull = [\"serde\", \"serde/std\"]\n",
+        );
+        let required = edit(
+            &implicit,
+            [FeatureEdit::MakeRequired {
+                dep: "serde".to_string(),
+            }],
+        )
+        .unwrap();
+        assert_eq!(
+            required,
+            manifest(
+                "[dependencies]\nserde = { version = \"1\" }\n\n[features]\nfull = [\"serde/std\"]\n"
+            )
+        );
+    }
+
+    #[test]
+    fn optional_round_trip() {
+        let original = manifest(
+            "[dependencies]\nserde = { version = \"1\", features = [\"derive\"] }\n\n[features]\nstd = [\"serde/std\"]\n",
+        );
+        let optional = edit(
+            &original,
+            [FeatureEdit::MakeOptional {
+                dep: "serde".to_string(),
+                feature: "serde".to_string(),
+            }],
+        )
+        .unwrap();
+        assert_eq!(
+            optional,
+            manifest(
+                "[dependencies]\nserde = { version = \"1\", features = [\"derive\"], optional = true }\n\n[features]\nstd = [\"serde/std\"]\nserde = [\"dep:serde\"]\n"
+            )
+        );
+
+        // the feature that made it optional stays, it may be used elsewhere
+        let required = edit(
+            &optional,
+            [FeatureEdit::MakeRequired {
+                dep: "serde".to_string(),
+            }],
+        )
+        .unwrap();
+        assert_eq!(
+            required,
+            manifest(
+                "[dependencies]\nserde = { version = \"1\", features = [\"derive\"] }\n\n[features]\nstd = [\"serde/std\"]\nserde = []\n"
+            )
+        );
+    }
+}
@@ -0,0 +1,308 @@
+use cargo_metadata::{Dependency, DependencyKind, Metadata, Package};
+use eyre::{Context, Result};
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use toml_edit::visit_mut::{visit_table_like_kv_mut, VisitMut};
+use toml_edit::{DocumentMut, Item, KeyMut};
+
+use crate::cargo::{backup::ManifestBackup, EditDependency};
+
+/// Finds the smallest set of features of a dependency that still compiles.
+///
+/// The workspace is copied into a scratch directory. There, default features
+/// are turned off and the requested features are removed one at a time, each
+/// time running `cargo check` on the package. Path dependencies outside of the
+/// workspace root are not copied, the copied manifests point at them instead. Edits that break the build are
+/// rolled back with a [`ManifestBackup`]. The original workspace is not
+/// touched.
+pub struct Minimize<'a> {
+    metadata: &'a Metadata,
+    package: &'a Package,
+    dep_name: &'a str,
+    dep_kind: DependencyKind,
+}
+
+#[derive(Debug, Clone)]
+pub struct MinimalFeatures {
+    pub default_features: bool,
+    pub features: Vec<String>,
+    /// Requested features that are not needed.
+    pub removed: Vec<String>,
+}
+
+impl<'a> Minimize<'a> {
+    pub fn new(
+        metadata: &'a Metadata,
+        package: &'a Package,
+        dep_name: &'a str,
+        dep_kind: DependencyKind,
+    ) -> Self {
+        Self {
+            metadata,
+            package,
+            dep_name,
+            dep_kind,
+        }
+    }
+
+    fn dependency(&self) -> Result<&'a Dependency> {
+        self.package
+            .dependencies
+            .iter()
+            .find(|d| d.name == self.dep_name && d.kind == self.dep_kind)
+            .ok_or_else(|| {
+                eyre::eyre!(
+                    "{} has no {} dependency {:?}",
+                    self.package.name,
+                    self.dep_kind,
+                    self.dep_name
+                )
+            })
+    }
+
+    /// Runs the search, reporting each step to `progress`.
+    pub fn run(&self, mut progress: impl FnMut(&str)) -> Result<MinimalFeatures> {
+        let dependency = self.dependency()?;
+
+        let scratch = tempfile::Builder::new()
+            .prefix("cargo-oh-my-dependencies")
+            .tempdir()?;
+        let workspace_root = self.metadata.workspace_root.as_std_path();
+        progress(&format!(
+            "copying workspace to {}",
+            scratch.path().display()
+        ));
+        copy_workspace(workspace_root, workspace_root, scratch.path())
+            .context("copying workspace")?;
+
+        let manifest_path = scratch.path().join(
+            self.package
+                .manifest_path
+                .as_std_path()
+                .strip_prefix(workspace_root)
+                .context("package is not inside the workspace root")?,
+        );
+        // share the build cache between runs, it makes the checks much faster
+        let target_dir = self.metadata.target_directory.join("omd-minimize");
+
+        progress("checking the current configuration");
+        if !self.check(scratch.path(), target_dir.as_std_path())? {
+            eyre::bail!(
+                "{} does not compile with its current features",
+                self.package.name
+            );
+        }
+
+        let mut default_features = dependency.uses_default_features;
+        let mut removed = Vec::new();
+
+        if default_features {
+            progress("trying without default features");
+            if self.try_features(&manifest_path, target_dir.as_std_path(), false, &removed)? {
+                default_features = false;
+                progress("  ok");
+            } else {
+                progress("  failed, keeping default features");
+            }
+        }
+
+        for feature in &dependency.features {
+            progress(&format!("trying without feature {feature:?}"));
+            let mut candidate = removed.clone();
+            candidate.push(feature.clone());
+            if self.try_features(
+                &manifest_path,
+                target_dir.as_std_path(),
+                default_features,
+                &candidate,
+            )? {
+                removed = candidate;
+                progress("  ok");
+            } else {
+                progress("  failed, keeping it");
+            }
+        }
+
+        let features = dependency
+            .features
+            .iter()
+            .filter(|f| !removed.contains(f))
+            .cloned()
+            .collect();
+
+        if let Err(err) = scratch.close() {
+            error!("failed to cleanup scratch workspace: {err}");
+        }
+
+        Ok(MinimalFeatures {
+            default_features,
+            features,
+            removed,
+        })
+    }
+
+    /// Applies the edit to the scratch manifest, checks the package and rolls
+    /// the manifest back.
+    fn try_features(
+        &self,
+        manifest_path: &Path,
+        target_dir: &Path,
+        default_features: bool,
+        removed: &[String],
+    ) -> Result<bool> {
+        // restores the manifest when dropped
+        let _backup = ManifestBackup::create(manifest_path)?;
+
+        let mut edit = EditDependency::new(self.package, self.dep_name, self.dep_kind)
+            .manifest_path(manifest_path)
+            .verify(false);
+        if !default_features {
+            edit = edit.default_features(false);
+        }
+        for feature in removed {
+            edit = edit.remove_feature(feature);
+        }
+        if let Err(err) = edit.apply() {
+            debug!("[minimize] edit failed: {err}");
+            return Ok(false);
+        }
+
+        let dir = manifest_path.parent().unwrap_or(Path::new("."));
+        self.check(dir, target_dir)
+    }
+
+    fn check(&self, dir: &Path, target_dir: &Path) -> Result<bool> {
+        debug!("[minimize] cargo check -p {} in {dir:?}", self.package.name);
+        let output = Command::new("cargo")
+            .arg("check")
+            .arg("--all-targets")
+            .arg("--package")
+            .arg(&self.package.name)
+            .arg("--target-dir")
+            .arg(target_dir)
+            .current_dir(dir)
+            .output()?;
+        if !output.status.success() {
+            debug!(
+                "[minimize] check failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+        Ok(output.status.success())
+    }
+}
+
+/// Copies the workspace sources, skipping build output and version control.
+fn copy_workspace(root: &Path, from: &Path, to: &Path) -> Result<()> {
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if name == "target" || name == ".git" {
+            continue;
+        }
+        let to: PathBuf = to.join(&name);
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            std::fs::create_dir_all(&to)?;
+            copy_workspace(root, &entry.path(), &to)?;
+        } else if file_type.is_file() && name == "Cargo.toml" {
+            let manifest = std::fs::read_to_string(entry.path())
+                .with_context(|| format!("reading {:?}", entry.path()))?;
+            std::fs::write(&to, outside_paths_absolute(root, from, &manifest)?)?;
+        } else if file_type.is_file() {
+            std::fs::copy(entry.path(), &to)?;
+        }
+    }
+    Ok(())
+}
+
+/// Makes the relative `path`s of the manifest in `dir` that point outside of
+/// the workspace `root` absolute, so they still resolve from the copy.
+fn outside_paths_absolute(root: &Path, dir: &Path, manifest: &str) -> Result<String> {
+    let mut doc = manifest.parse::<DocumentMut>()?;
+    OutsidePaths { root, dir }.visit_document_mut(&mut doc);
+    Ok(doc.to_string())
+}
+
+struct OutsidePaths<'a> {
+    root: &'a Path,
+    dir: &'a Path,
+}
+
+impl VisitMut for OutsidePaths<'_> {
+    fn visit_table_like_kv_mut(&mut self, key: KeyMut<'_>, node: &mut Item) {
+        if key.get() == "path" {
+            if let Some(value) = node.as_value_mut() {
+                let resolved = value
+                    .as_str()
+                    .map(Path::new)
+                    .filter(|path| path.is_relative())
+                    .map(|path| normalize(&self.dir.join(path)))
+                    .filter(|path| !path.starts_with(self.root));
+                if let Some(resolved) = resolved {
+                    let decor = value.decor().clone();
+                    *value = resolved.to_string_lossy().as_ref().into();
+                    *value.decor_mut() = decor;
+                }
+            }
+        }
+        visit_table_like_kv_mut(self, key, node);
+    }
+}
+
+/// Resolves `.` and `..` without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::CurDir => {}
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outside_paths() {
+        let manifest = r#"[package]
+name = "a"
+
+[lib]
+path = "src/lib.rs"
+
+[dependencies]
+inside = { path = "../inside" }
+outside = { path = "../../outside", features = ["x"] }
+absolute = { path = "/opt/absolute" }
+
+[target.'cfg(unix)'.dev-dependencies.sibling]
+path = "./../../sibling"
+"#;
+        let rewritten =
+            outside_paths_absolute(Path::new("/ws"), Path::new("/ws/a"), manifest).unwrap();
+        assert_eq!(
+            rewritten,
+            r#"[package]
+name = "a"
+
+[lib]
+path = "src/lib.rs"
+
+[dependencies]
+inside = { path = "../inside" }
+outside = { path = "/outside", features = ["x"] }
+absolute = { path = "/opt/absolute" }
+
+[target.'cfg(unix)'.dev-dependencies.sibling]
+path = "/sibling"
+"#
+        );
+    }
+}
@@ -1,4 +1,16 @@
+mod add;
 mod backup;
+mod bump;
 mod edit;
+mod features;
+mod minimize;
+mod registry_index;
+mod remove;
 
+pub use add::AddDependency;
+pub use bump::BumpDependency;
 pub use edit::EditDependency;
+pub use features::{EditFeatures, FeatureEdit};
+pub use minimize::{MinimalFeatures, Minimize};
+pub use registry_index::{IndexVersion, RegistryIndex};
+pub use remove::RemoveDependency;
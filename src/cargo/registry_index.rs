@@ -0,0 +1,224 @@
+use cargo_metadata::semver::Version;
+use eyre::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Read-only access to the index entries cargo caches locally for sparse
+/// registries (`$CARGO_HOME/registry/index/*/.cache`). Only crates that were
+/// resolved on this machine before are known, but no network is needed.
+#[derive(Debug, Clone)]
+pub struct RegistryIndex {
+    cache_dirs: Vec<PathBuf>,
+    /// Sorted and deduplicated crate names.
+    names: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct IndexVersion {
+    pub version: Version,
+    pub features: BTreeMap<String, Vec<String>>,
+    pub yanked: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct IndexEntry {
+    vers: String,
+    #[serde(default)]
+    features: BTreeMap<String, Vec<String>>,
+    /// Features using the `dep:` or `?` syntax are stored separately.
+    #[serde(default)]
+    features2: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    yanked: bool,
+    #[serde(default)]
+    deps: Vec<IndexDependency>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IndexDependency {
+    name: String,
+    #[serde(default)]
+    optional: bool,
+}
+
+impl RegistryIndex {
+    pub fn open() -> Result<Self> {
+        let index_dir = cargo_home()?.join("registry").join("index");
+        let mut cache_dirs = std::fs::read_dir(&index_dir)
+            .with_context(|| format!("reading registry index {index_dir:?}"))?
+            .flatten()
+            .map(|entry| entry.path().join(".cache"))
+            .filter(|dir| dir.is_dir())
+            .collect::<Vec<_>>();
+        // crates.io first
+        cache_dirs.sort_by_key(|dir| !dir.to_string_lossy().contains("crates.io"));
+
+        let mut names = Vec::new();
+        for dir in &cache_dirs {
+            collect_names(dir, &mut names);
+        }
+        names.sort();
+        names.dedup();
+
+        Ok(Self { cache_dirs, names })
+    }
+
+    /// Crate names matching `query`, best matches first: exact, prefix, then
+    /// substring matches. `-` and `_` are treated the same.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<&str> {
+        let normalize = |s: &str| s.to_lowercase().replace('_', "-");
+        let query = normalize(query.trim());
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matches = self
+            .names
+            .iter()
+            .filter_map(|name| {
+                let normalized = normalize(name);
+                let rank = if normalized == query {
+                    0
+                } else if normalized.starts_with(&query) {
+                    1
+                } else if normalized.contains(&query) {
+                    2
+                } else {
+                    return None;
+                };
+                Some((rank, name.len(), name.as_str()))
+            })
+            .collect::<Vec<_>>();
+        matches.sort();
+        matches
+            .into_iter()
+            .take(limit)
+            .map(|(_, _, name)| name)
+            .collect()
+    }
+
+    /// All versions of a crate, newest first.
+    pub fn versions(&self, name: &str) -> Result<Vec<IndexVersion>> {
+        let relative = cache_path(name);
+        let Some(path) = self
+            .cache_dirs
+            .iter()
+            .map(|dir| dir.join(&relative))
+            .find(|path| path.exists())
+        else {
+            eyre::bail!("{name} is not in the local registry index");
+        };
+
+        let data = std::fs::read(&path).with_context(|| format!("reading {path:?}"))?;
+        let mut versions = parse_cache_file(&data)
+            .with_context(|| format!("parsing index cache {path:?}"))?
+            .into_iter()
+            .filter_map(|entry| {
+                let version = entry.vers.parse::<Version>().ok()?;
+                let mut features = entry.features;
+                features.extend(entry.features2);
+                add_implicit_features(&mut features, &entry.deps);
+                Some(IndexVersion {
+                    version,
+                    features,
+                    yanked: entry.yanked,
+                })
+            })
+            .collect::<Vec<_>>();
+        versions.sort_by(|a, b| b.version.cmp(&a.version));
+        Ok(versions)
+    }
+
+    /// The newest version that is not yanked. Pre-releases are only
+    /// considered if `pre_release` is set.
+    pub fn newest(&self, name: &str, pre_release: bool) -> Option<Version> {
+        self.versions(name)
+            .ok()?
+            .into_iter()
+            .filter(|v| !v.yanked && (pre_release || v.version.pre.is_empty()))
+            .map(|v| v.version)
+            .next()
+    }
+}
+
+/// Optional dependencies that are not referenced with `dep:` get a feature of
+/// the same name.
+fn add_implicit_features(features: &mut BTreeMap<String, Vec<String>>, deps: &[IndexDependency]) {
+    for dep in deps.iter().filter(|dep| dep.optional) {
+        let dep_feature = format!("dep:{}", dep.name);
+        let explicit = features
+            .values()
+            .flatten()
+            .any(|value| *value == dep_feature);
+        if !explicit && !features.contains_key(&dep.name) {
+            features.insert(dep.name.clone(), vec![dep_feature]);
+        }
+    }
+}
+
+fn cargo_home() -> Result<PathBuf> {
+    if let Some(home) = std::env::var_os("CARGO_HOME") {
+        return Ok(PathBuf::from(home));
+    }
+    let Some(dirs) = directories::BaseDirs::new() else {
+        eyre::bail!("could not find home directory");
+    };
+    Ok(dirs.home_dir().join(".cargo"))
+}
+
+/// Index files are named after the lowercased crate name and sharded by its
+/// first characters: `1/a`, `2/ab`, `3/a/abc`, `ab/cd/abcd...`.
+fn cache_path(name: &str) -> PathBuf {
+    let name = name.to_lowercase();
+    match name.len() {
+        1 => Path::new("1").join(&name),
+        2 => Path::new("2").join(&name),
+        3 => Path::new("3").join(&name[..1]).join(&name),
+        _ => Path::new(&name[..2]).join(&name[2..4]).join(&name),
+    }
+}
+
+fn collect_names(dir: &Path, names: &mut Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            collect_names(&entry.path(), names);
+        } else if let Some(name) = entry.file_name().to_str() {
+            names.push(name.to_string());
+        }
+    }
+}
+
+/// A cache file starts with a cache version byte, a `u32` index format version
+/// and the index version (e.g. an etag) terminated by a null byte. It is
+/// followed by pairs of null terminated version strings and JSON entries.
+fn parse_cache_file(data: &[u8]) -> Result<Vec<IndexEntry>> {
+    const CACHE_VERSION: u8 = 3;
+    if data.len() < 5 {
+        eyre::bail!("file too short");
+    }
+    if data[0] != CACHE_VERSION {
+        eyre::bail!("unsupported cache version {}", data[0]);
+    }
+    let mut parts = data[5..].split(|b| *b == 0);
+    // index version
+    parts.next();
+
+    let mut entries = Vec::new();
+    while let (Some(_version), Some(json)) = (parts.next(), parts.next()) {
+        if json.is_empty() {
+            continue;
+        }
+        match serde_json::from_slice::<IndexEntry>(json) {
+            Ok(entry) => entries.push(entry),
+            Err(err) => debug!("skipping index entry: {err}"),
+        }
+    }
+    Ok(entries)
+}
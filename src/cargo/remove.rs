@@ -0,0 +1,91 @@
+use cargo_metadata::{DependencyKind, Package};
+use eyre::{Context, Result};
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::cargo::backup::ManifestBackup;
+
+/// Removes a dependency from a manifest with `cargo rm`. Every entry of the
+/// dependency with the given kind is removed, including the ones in
+/// `[target.'cfg(..)'.*]` tables.
+#[derive(Debug, Clone)]
+pub struct RemoveDependency<'a> {
+    manifest_path: PathBuf,
+    package: &'a Package,
+    dep_name: &'a str,
+    dep_kind: DependencyKind,
+}
+
+impl<'a> RemoveDependency<'a> {
+    pub fn new(package: &'a Package, dep_name: &'a str, dep_kind: DependencyKind) -> Self {
+        Self {
+            manifest_path: package.manifest_path.clone().into_std_path_buf(),
+            package,
+            dep_name,
+            dep_kind,
+        }
+    }
+
+    /// The arguments of the `cargo` invocations, one per target table.
+    fn cmds(&self) -> Vec<Vec<String>> {
+        let mut cmds = Vec::new();
+        for dep in self
+            .package
+            .dependencies
+            .iter()
+            .filter(|dep| dep.name == self.dep_name && dep.kind == self.dep_kind)
+        {
+            let mut args = vec!["rm".to_string()];
+            args.push(dep.rename.as_deref().unwrap_or(&dep.name).to_string());
+            match self.dep_kind {
+                DependencyKind::Development => args.push("--dev".to_string()),
+                DependencyKind::Build => args.push("--build".to_string()),
+                _ => {}
+            }
+            if let Some(target) = &dep.target {
+                args.push("--target".to_string());
+                args.push(target.to_string());
+            }
+            args.push("--manifest-path".to_string());
+            args.push(self.manifest_path.display().to_string());
+            if !cmds.contains(&args) {
+                cmds.push(args);
+            }
+        }
+        cmds
+    }
+
+    pub fn apply(self) -> Result<()> {
+        let cmds = self.cmds();
+        if cmds.is_empty() {
+            eyre::bail!(
+                "{} is not a {} dependency of {}",
+                self.dep_name,
+                self.dep_kind,
+                self.package.name
+            );
+        }
+
+        // auto restores when dropped unless disposed
+        let backup =
+            ManifestBackup::create(&self.manifest_path).context("creating manifest backup")?;
+
+        for args in cmds {
+            debug!(?args, "running cargo");
+            let output = Command::new("cargo").args(&args).output()?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let msg = format!(
+                    "failed to execute: cargo {}\n{}",
+                    args.join(" "),
+                    stderr.trim()
+                );
+                error!("{msg}");
+                eyre::bail!("{msg}");
+            }
+        }
+
+        backup.dispose();
+        Ok(())
+    }
+}
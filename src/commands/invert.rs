@@ -0,0 +1,25 @@
+use eyre::Result;
+use std::path::PathBuf;
+
+use crate::metadata::{inverse_tree, workspace_info::WorkspaceInfo};
+
+pub fn run(manifest_path: PathBuf, spec: &str) -> Result<()> {
+    let info = WorkspaceInfo::load(manifest_path)?;
+
+    let packages = inverse_tree::find_packages(&info.metadata, spec);
+    if packages.is_empty() {
+        eyre::bail!("package {spec:?} not found in the dependency graph");
+    }
+
+    for (i, package) in packages.iter().enumerate() {
+        let Some(tree) = inverse_tree::InverseTree::build(&info.metadata, &package.id) else {
+            eyre::bail!("could not resolve dependency graph");
+        };
+        if i > 0 {
+            println!();
+        }
+        print!("{}", tree.render_text());
+    }
+
+    Ok(())
+}
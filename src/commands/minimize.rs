@@ -0,0 +1,63 @@
+use cargo_metadata::DependencyKind;
+use eyre::Result;
+use std::path::PathBuf;
+
+use crate::cargo::Minimize;
+use crate::metadata::workspace_info::WorkspaceInfo;
+
+pub fn run(
+    manifest_path: PathBuf,
+    package_name: &str,
+    dep_name: &str,
+    dep_kind: DependencyKind,
+    apply: bool,
+) -> Result<()> {
+    let mut info = WorkspaceInfo::load(manifest_path)?;
+    let Some(package) = info
+        .workspace_packages()
+        .into_iter()
+        .find(|p| p.name == package_name)
+    else {
+        eyre::bail!("workspace package {package_name:?} not found");
+    };
+
+    let minimal =
+        Minimize::new(&info.metadata, package, dep_name, dep_kind).run(|msg| eprintln!("{msg}"))?;
+
+    println!("minimal feature set of {dep_name} in {package_name}:");
+    println!("  default-features = {}", minimal.default_features);
+    println!(
+        "  features = [{}]",
+        minimal
+            .features
+            .iter()
+            .map(|f| format!("{f:?}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    if !minimal.removed.is_empty() {
+        println!("not needed: {}", minimal.removed.join(", "));
+    }
+
+    let dependency = package
+        .dependencies
+        .iter()
+        .find(|d| d.name == dep_name && d.kind == dep_kind);
+    let default_removed =
+        dependency.is_some_and(|d| d.uses_default_features) && !minimal.default_features;
+
+    if apply && (default_removed || !minimal.removed.is_empty()) {
+        let package_id = package.id.clone();
+        let manifest_path = package.manifest_path.clone();
+        info.remove_features(
+            package_id,
+            dep_name.to_string(),
+            dep_kind,
+            !default_removed,
+            minimal.removed,
+        )?;
+        println!("updated {manifest_path}");
+    }
+
+    Ok(())
+}
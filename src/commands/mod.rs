@@ -0,0 +1,34 @@
+//! Non-interactive subcommands that print their result to stdout.
+
+mod invert;
+mod minimize;
+mod unused;
+
+use cargo_metadata::DependencyKind;
+use eyre::Result;
+
+use crate::args::Command;
+
+pub fn run(command: Command) -> Result<()> {
+    match command {
+        Command::Invert { package, manifest } => invert::run(manifest.path, &package),
+        Command::Minimize {
+            package,
+            dependency,
+            dev,
+            build,
+            apply,
+            manifest,
+        } => {
+            let kind = if dev {
+                DependencyKind::Development
+            } else if build {
+                DependencyKind::Build
+            } else {
+                DependencyKind::Normal
+            };
+            minimize::run(manifest.path, &package, &dependency, kind, apply)
+        }
+        Command::Unused { manifest } => unused::run(manifest.path),
+    }
+}
@@ -0,0 +1,38 @@
+use cargo_metadata::DependencyKind;
+use eyre::Result;
+use std::path::PathBuf;
+
+use crate::metadata::{unused_features, workspace_info::WorkspaceInfo};
+
+pub fn run(manifest_path: PathBuf) -> Result<()> {
+    let info = WorkspaceInfo::load(manifest_path)?;
+
+    let unused = unused_features::find_unused_features(&info.metadata);
+    if unused.is_empty() {
+        println!("no possibly unused features found");
+        return Ok(());
+    }
+
+    let mut member = None;
+    for feature in unused {
+        if member.as_ref() != Some(&feature.member) {
+            if member.is_some() {
+                println!();
+            }
+            println!("{}", feature.member_name);
+            member = Some(feature.member.clone());
+        }
+        let kind = match feature.dep_kind {
+            DependencyKind::Normal => String::new(),
+            kind => format!(" ({kind})"),
+        };
+        println!(
+            "  {}{kind}: feature {:?} gates {}",
+            feature.dep_name,
+            feature.feature,
+            feature.items.join(", ")
+        );
+    }
+
+    Ok(())
+}
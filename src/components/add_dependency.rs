@@ -0,0 +1,378 @@
+use cargo_metadata::{DependencyKind, PackageId};
+use crossterm::event;
+use eyre::Result;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+
+use crate::action::Action;
+use crate::cargo::{IndexVersion, RegistryIndex};
+use crate::component::Component;
+use crate::metadata::toml::table_name;
+
+const MAX_MATCHES: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Search,
+    Version,
+    Features,
+}
+
+/// Popup to add a dependency to a workspace member: search a crate in the
+/// local registry index, pick a version and its features.
+#[derive(Debug)]
+pub struct AddDependencyDialog {
+    package: PackageId,
+    package_name: String,
+    index: RegistryIndex,
+    step: Step,
+    kind: DependencyKind,
+    query: String,
+    matches: Vec<String>,
+    /// The crate picked in the search step.
+    name: String,
+    versions: Vec<IndexVersion>,
+    /// The version picked in the version step.
+    version: Option<IndexVersion>,
+    /// Features of the picked version and whether they are selected.
+    features: Vec<(String, bool)>,
+    default_features: bool,
+    list_state: ListState,
+    error: Option<String>,
+}
+
+impl AddDependencyDialog {
+    pub fn new(package: PackageId, package_name: impl Into<String>) -> Result<Self> {
+        Ok(Self {
+            package,
+            package_name: package_name.into(),
+            index: RegistryIndex::open()?,
+            step: Step::Search,
+            kind: DependencyKind::Normal,
+            query: String::new(),
+            matches: Vec::new(),
+            name: String::new(),
+            versions: Vec::new(),
+            version: None,
+            features: Vec::new(),
+            default_features: true,
+            list_state: ListState::default(),
+            error: None,
+        })
+    }
+
+    /// Shows an error, e.g. if writing the manifest failed.
+    pub fn set_error(&mut self, err: eyre::Report) {
+        self.error = Some(format!("{err:#}"));
+    }
+
+    fn len(&self) -> usize {
+        match self.step {
+            Step::Search => self.matches.len(),
+            Step::Version => self.versions.len(),
+            // the first row toggles the default features
+            Step::Features => self.features.len() + 1,
+        }
+    }
+
+    fn search(&mut self) {
+        self.matches = self
+            .index
+            .search(&self.query, MAX_MATCHES)
+            .into_iter()
+            .map(String::from)
+            .collect();
+        self.list_state
+            .select((!self.matches.is_empty()).then_some(0));
+    }
+
+    fn open_versions(&mut self) -> Result<()> {
+        let Some(name) = self.list_state.selected().and_then(|i| self.matches.get(i)) else {
+            return Ok(());
+        };
+        self.name = name.clone();
+        self.versions = self.index.versions(&self.name)?;
+        let newest = self
+            .versions
+            .iter()
+            .position(|v| !v.yanked && v.version.pre.is_empty())
+            .unwrap_or(0);
+        self.list_state.select(Some(newest));
+        self.step = Step::Version;
+        Ok(())
+    }
+
+    fn open_features(&mut self) -> Result<()> {
+        let Some(version) = self
+            .list_state
+            .selected()
+            .and_then(|i| self.versions.get(i))
+        else {
+            return Ok(());
+        };
+        if version.yanked {
+            eyre::bail!("{} {} is yanked", self.name, version.version);
+        }
+        self.features = version
+            .features
+            .keys()
+            .filter(|name| *name != "default")
+            .map(|name| (name.clone(), false))
+            .collect();
+        self.version = Some(version.clone());
+        self.default_features = true;
+        self.list_state.select(Some(0));
+        self.step = Step::Features;
+        Ok(())
+    }
+
+    fn toggle_feature(&mut self) {
+        match self.list_state.selected() {
+            Some(0) => self.default_features = !self.default_features,
+            Some(i) => {
+                if let Some((_, selected)) = self.features.get_mut(i - 1) {
+                    *selected = !*selected;
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn confirm(&self) -> Option<Action> {
+        let version = self.version.as_ref()?;
+        Some(Action::AddDependency {
+            package: self.package.clone(),
+            name: self.name.clone(),
+            version: version.version.clone(),
+            kind: self.kind,
+            features: self
+                .features
+                .iter()
+                .filter(|(_, selected)| *selected)
+                .map(|(name, _)| name.clone())
+                .collect(),
+            default_features: self.default_features,
+        })
+    }
+
+    fn back(&mut self) -> Option<Action> {
+        match self.step {
+            Step::Search => return Some(Action::CloseDialog),
+            Step::Version => {
+                self.step = Step::Search;
+                let i = self.matches.iter().position(|name| *name == self.name);
+                self.list_state.select(i.or(Some(0)));
+            }
+            Step::Features => {
+                self.step = Step::Version;
+                let i = self
+                    .versions
+                    .iter()
+                    .position(|v| Some(&v.version) == self.version.as_ref().map(|v| &v.version));
+                self.list_state.select(i.or(Some(0)));
+            }
+        }
+        None
+    }
+
+    fn next_kind(&mut self) {
+        self.kind = match self.kind {
+            DependencyKind::Normal => DependencyKind::Development,
+            DependencyKind::Development => DependencyKind::Build,
+            _ => DependencyKind::Normal,
+        };
+    }
+
+    fn items(&self) -> Vec<ListItem<'static>> {
+        match self.step {
+            Step::Search => self
+                .matches
+                .iter()
+                .map(|name| ListItem::new(name.clone()))
+                .collect(),
+            Step::Version => self
+                .versions
+                .iter()
+                .map(|v| {
+                    let mut spans = vec![Span::raw(v.version.to_string())];
+                    if v.yanked {
+                        spans.push(" yanked".red());
+                    } else if !v.version.pre.is_empty() {
+                        spans.push(" pre-release".dim());
+                    }
+                    ListItem::new(Line::from(spans))
+                })
+                .collect(),
+            Step::Features => {
+                let version = self.version.as_ref();
+                let check = |selected: bool| if selected { "[x] " } else { "[ ] " };
+                let default = version
+                    .and_then(|v| v.features.get("default"))
+                    .map(|deps| deps.join(", "))
+                    .unwrap_or_default();
+                let mut items = vec![ListItem::new(Line::from(vec![
+                    Span::raw(check(self.default_features)),
+                    "default features".bold(),
+                    Span::styled(format!(" {default}"), Style::default().dim()),
+                ]))];
+                items.extend(self.features.iter().map(|(name, selected)| {
+                    let deps = version
+                        .and_then(|v| v.features.get(name))
+                        .map(|deps| deps.join(", "))
+                        .unwrap_or_default();
+                    ListItem::new(Line::from(vec![
+                        Span::raw(check(*selected)),
+                        Span::raw(name.clone()),
+                        Span::styled(format!(" {deps}"), Style::default().dim()),
+                    ]))
+                }));
+                items
+            }
+        }
+    }
+
+    fn help(&self) -> Vec<Span<'static>> {
+        let mut help = vec![" ".dim()];
+        match self.step {
+            Step::Search | Step::Version => {
+                help.push("<enter>".blue());
+                help.push(" select".dim());
+            }
+            Step::Features => {
+                help.push("<space>".blue());
+                help.push(" toggle".dim());
+                help.push(" ".dim());
+                help.push("<enter>".blue());
+                help.push(" add".dim());
+            }
+        }
+        help.extend([
+            " ".dim(),
+            "<tab>".blue(),
+            " kind".dim(),
+            " ".dim(),
+            "<esc>".blue(),
+            " back".dim(),
+            " ".dim(),
+        ]);
+        help
+    }
+}
+
+impl Component for AddDependencyDialog {
+    fn handle_key_events(&mut self, key_event: event::KeyEvent) -> Result<Option<Action>> {
+        self.error = None;
+        let result = match key_event.code {
+            event::KeyCode::Esc => return Ok(self.back().or(Some(Action::Render))),
+            event::KeyCode::Tab => {
+                self.next_kind();
+                Ok(())
+            }
+            event::KeyCode::Up => {
+                let i = self.list_state.selected().unwrap_or(0);
+                self.list_state.select(Some(i.saturating_sub(1)));
+                Ok(())
+            }
+            event::KeyCode::Down => {
+                let i = self.list_state.selected().map_or(0, |i| i + 1);
+                self.list_state
+                    .select(Some(i.min(self.len().saturating_sub(1))));
+                Ok(())
+            }
+            event::KeyCode::Enter => match self.step {
+                Step::Search => self.open_versions(),
+                Step::Version => self.open_features(),
+                Step::Features => return Ok(self.confirm()),
+            },
+            event::KeyCode::Char(' ') if self.step == Step::Features => {
+                self.toggle_feature();
+                Ok(())
+            }
+            event::KeyCode::Char(c) if self.step == Step::Search => {
+                self.query.push(c);
+                self.search();
+                Ok(())
+            }
+            event::KeyCode::Backspace if self.step == Step::Search => {
+                self.query.pop();
+                self.search();
+                Ok(())
+            }
+            _ => return Action::none(),
+        };
+        if let Err(err) = result {
+            self.set_error(err);
+        }
+        Action::render()
+    }
+
+    fn render(&mut self, f: &mut Frame, rect: Rect) {
+        let [area] = Layout::vertical([Constraint::Percentage(70)])
+            .flex(layout::Flex::Center)
+            .areas(rect);
+        let [area] = Layout::horizontal([Constraint::Percentage(60)])
+            .flex(layout::Flex::Center)
+            .areas(area);
+
+        let block = Block::default()
+            .title(format!(" add dependency to {} ", self.package_name))
+            .title_bottom(self.help())
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Double);
+        let inner = block.inner(area);
+        f.render_widget(Clear, area);
+        f.render_widget(block, area);
+
+        let [header, list, footer] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(1),
+            Constraint::Length(if self.error.is_some() { 2 } else { 0 }),
+        ])
+        .areas(inner);
+
+        let kind = table_name(self.kind);
+        let crate_line = match self.step {
+            Step::Search => Line::from(vec![
+                "crate: ".dim(),
+                Span::raw(self.query.clone()),
+                "▏".blue(),
+            ]),
+            Step::Version => Line::from(vec!["crate: ".dim(), self.name.clone().bold()]),
+            Step::Features => Line::from(vec![
+                "crate: ".dim(),
+                self.name.clone().bold(),
+                Span::raw(" "),
+                Span::raw(
+                    self.version
+                        .as_ref()
+                        .map(|v| v.version.to_string())
+                        .unwrap_or_default(),
+                ),
+            ]),
+        };
+        let header_text = vec![
+            crate_line,
+            Line::from(vec!["table: ".dim(), Span::raw(format!("[{kind}]"))]),
+        ];
+        f.render_widget(Paragraph::new(header_text), header);
+
+        let empty = match self.step {
+            Step::Search if self.query.is_empty() => "type to search the local registry index",
+            Step::Search => "no crates found in the local registry index",
+            _ => "",
+        };
+        if self.len() == 0 && !empty.is_empty() {
+            f.render_widget(Paragraph::new(empty.dim()), list);
+        } else {
+            let items = List::new(self.items()).highlight_style(Style::default().on_dark_gray());
+            f.render_stateful_widget(items, list, &mut self.list_state);
+        }
+
+        if let Some(err) = &self.error {
+            f.render_widget(Paragraph::new(err.clone().red()), footer);
+        }
+    }
+}
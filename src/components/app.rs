@@ -6,6 +6,9 @@ use ratatui::{
     widgets::{block::Title, Block, BorderType, Borders, Paragraph},
 };
 
+use crate::config::{self, Config};
+use crate::keymap::{keymap, Command};
+use crate::theme::theme;
 use crate::{action::Action, Args};
 use crate::{args::Opt, component::Component};
 
@@ -19,9 +22,15 @@ pub struct App {
 
 impl App {
     pub fn new(args: Args) -> Result<Self> {
-        let Args::Omd(Opt { manifest }) = args;
+        let Args::Omd(opt) = args;
+        let mut config = Config::load(&opt.manifest)?;
+        config.apply_args(&opt);
+        config::init(config);
+        let Opt {
+            manifest, timings, ..
+        } = opt;
         Ok(Self {
-            tab: DependencyTab::new(manifest)?,
+            tab: DependencyTab::new(manifest, timings)?,
             error: None,
         })
     }
@@ -45,11 +54,16 @@ impl App {
             .title(Title::from("Error"))
             .borders(Borders::ALL)
             .border_type(BorderType::Double)
-            .border_style(Style::default().fg(Color::Red))
+            .border_style(theme().error)
             .title_alignment(Alignment::Center);
-        let text = format!("{:?}", err)
+        let mut text = format!("{:?}", err)
             .into_text()
             .unwrap_or_else(|_| Text::raw(err.to_string()));
+        if !theme().colored {
+            for span in text.lines.iter_mut().flat_map(|line| line.spans.iter_mut()) {
+                span.style = Style::default();
+            }
+        }
         let msg_box = Paragraph::new(text).block(block);
         f.render_widget(msg_box, rect);
     }
@@ -57,12 +71,26 @@ impl App {
 
 impl Component for App {
     fn handle_events(&mut self, event: Event) -> Result<Option<Action>> {
-        self.tab.handle_events(event)
+        match event {
+            Event::Key(key_event) => self.handle_key_events(key_event),
+            Event::Mouse(_) if self.error.is_some() => Action::none(),
+            event => self.tab.handle_events(event),
+        }
+    }
+
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match self.tab.update(action) {
+            Err(err) => {
+                self.error = Some(err);
+                Action::render()
+            }
+            action => action,
+        }
     }
 
     fn handle_key_events(&mut self, key_event: event::KeyEvent) -> Result<Option<Action>> {
         match key_event.code {
-            event::KeyCode::Char('q') => return Action::quit(),
+            _ if keymap().command(&key_event) == Some(Command::Quit) => return Action::quit(),
             event::KeyCode::Esc if self.error.is_some() => {
                 self.error = None;
                 return Action::render();
@@ -0,0 +1,188 @@
+use cargo_metadata::{DependencyKind, PackageId};
+use crossterm::event;
+use eyre::Result;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+};
+
+use crate::action::Action;
+use crate::component::Component;
+
+/// What the checked items of a [`ChecklistDialog`] are used for.
+#[derive(Debug, Clone)]
+pub enum ChecklistTarget {
+    /// Features to keep when turning off the default features of a
+    /// dependency.
+    DisableDefaultFeatures {
+        package: PackageId,
+        dep_name: String,
+        dep_kind: DependencyKind,
+    },
+}
+
+impl ChecklistTarget {
+    fn action(&self, checked: Vec<String>) -> Action {
+        match self {
+            ChecklistTarget::DisableDefaultFeatures {
+                package,
+                dep_name,
+                dep_kind,
+            } => Action::SetDefaultFeatures {
+                package: package.clone(),
+                dep_name: dep_name.clone(),
+                dep_kind: *dep_kind,
+                enabled: false,
+                features: checked,
+            },
+        }
+    }
+}
+
+/// Popup with a text and a list of items that can be checked with space.
+/// Enter submits the checked items.
+#[derive(Debug)]
+pub struct ChecklistDialog {
+    title: String,
+    text: Vec<Line<'static>>,
+    target: ChecklistTarget,
+    items: Vec<(String, bool)>,
+    list_state: ListState,
+    error: Option<String>,
+}
+
+impl ChecklistDialog {
+    pub fn new(
+        title: impl Into<String>,
+        text: Vec<Line<'static>>,
+        target: ChecklistTarget,
+        items: Vec<(String, bool)>,
+    ) -> Self {
+        let mut list_state = ListState::default();
+        list_state.select((!items.is_empty()).then_some(0));
+        Self {
+            title: title.into(),
+            text,
+            target,
+            items,
+            list_state,
+            error: None,
+        }
+    }
+
+    /// Shows an error, e.g. if applying the checked items failed.
+    pub fn set_error(&mut self, err: eyre::Report) {
+        self.error = Some(format!("{err:#}"));
+    }
+
+    fn submit(&self) -> Action {
+        let checked = self
+            .items
+            .iter()
+            .filter(|(_, checked)| *checked)
+            .map(|(item, _)| item.clone())
+            .collect();
+        self.target.action(checked)
+    }
+}
+
+impl Component for ChecklistDialog {
+    fn handle_key_events(&mut self, key_event: event::KeyEvent) -> Result<Option<Action>> {
+        self.error = None;
+        match key_event.code {
+            event::KeyCode::Esc => return Ok(Some(Action::CloseDialog)),
+            event::KeyCode::Enter => return Ok(Some(self.submit())),
+            event::KeyCode::Up => {
+                let i = self.list_state.selected().unwrap_or(0);
+                self.list_state.select(Some(i.saturating_sub(1)));
+            }
+            event::KeyCode::Down if !self.items.is_empty() => {
+                let i = self
+                    .list_state
+                    .selected()
+                    .map_or(0, |i| (i + 1).min(self.items.len() - 1));
+                self.list_state.select(Some(i));
+            }
+            event::KeyCode::Char(' ') => {
+                if let Some((_, checked)) = self
+                    .list_state
+                    .selected()
+                    .and_then(|i| self.items.get_mut(i))
+                {
+                    *checked = !*checked;
+                }
+            }
+            _ => return Action::none(),
+        }
+        Action::render()
+    }
+
+    fn render(&mut self, f: &mut Frame, rect: Rect) {
+        let text_height = self.text.len() as u16;
+        let list_height = self.items.len().min(15) as u16;
+        let error_height = if self.error.is_some() { 3 } else { 0 };
+        // borders and a blank line between text and list
+        let height = (text_height + 1 + list_height + error_height + 2).min(rect.height);
+        let [area] = Layout::vertical([Constraint::Length(height)])
+            .flex(layout::Flex::Center)
+            .areas(rect);
+        let [area] = Layout::horizontal([Constraint::Percentage(50)])
+            .flex(layout::Flex::Center)
+            .areas(area);
+
+        let help = vec![
+            " ".dim(),
+            "<space>".blue(),
+            " toggle".dim(),
+            " ".dim(),
+            "<enter>".blue(),
+            " ok".dim(),
+            " ".dim(),
+            "<esc>".blue(),
+            " cancel".dim(),
+            " ".dim(),
+        ];
+        let block = Block::default()
+            .title(format!(" {} ", self.title))
+            .title_bottom(help)
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Double);
+        let inner = block.inner(area);
+        f.render_widget(Clear, area);
+        f.render_widget(block, area);
+
+        let [text, _, list, error] = Layout::vertical([
+            Constraint::Length(text_height),
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(error_height),
+        ])
+        .areas(inner);
+
+        f.render_widget(Paragraph::new(self.text.clone()), text);
+
+        let items = self
+            .items
+            .iter()
+            .map(|(item, checked)| {
+                let check = if *checked { "[x] " } else { "[ ] " };
+                ListItem::new(format!("{check}{item}"))
+            })
+            .collect::<Vec<_>>();
+        f.render_stateful_widget(
+            List::new(items).highlight_style(Style::default().on_dark_gray()),
+            list,
+            &mut self.list_state,
+        );
+
+        if let Some(err) = &self.error {
+            let [_, error] =
+                Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(error);
+            f.render_widget(
+                Paragraph::new(err.clone().red()).wrap(Wrap { trim: false }),
+                error,
+            );
+        }
+    }
+}
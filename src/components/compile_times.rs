@@ -0,0 +1,180 @@
+use crossterm::event;
+use eyre::Result;
+use ratatui::prelude::*;
+use std::collections::HashSet;
+
+use crate::action::Action;
+use crate::component::Component;
+use crate::keymap::keymap;
+use crate::metadata::workspace_info::WorkspaceInfo;
+use crate::theme::theme;
+
+use super::treemap::{self, Treemap, TreemapItem};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Mode {
+    #[default]
+    Packages,
+    Features,
+}
+
+/// Treemap of compile times, either per package or per enabled feature (the
+/// compile time of the packages only built because of the feature).
+#[derive(Debug, Clone)]
+pub struct CompileTimesView {
+    mode: Mode,
+    packages: Vec<TreemapItem>,
+    features: Vec<TreemapItem>,
+    selected: usize,
+    /// Layout of the last render, used to move the selection.
+    rects: Vec<Rect>,
+}
+
+impl CompileTimesView {
+    /// `None` if no compile times were loaded. Resolves the features of the
+    /// workspace once per enabled feature, so it is built with the other
+    /// views of a task.
+    pub fn new(info: &WorkspaceInfo) -> Option<Self> {
+        let timings = info.timings.as_ref()?;
+        let metadata = &info.metadata;
+
+        let members = metadata.workspace_members.iter().collect::<HashSet<_>>();
+        let resolver = info.dependency_resolver();
+        let direct = members
+            .iter()
+            .flat_map(|id| resolver.dependencies(id))
+            .map(|(_, package)| &package.id)
+            .collect::<HashSet<_>>();
+
+        let mut packages = metadata
+            .packages
+            .iter()
+            .filter_map(|package| {
+                let seconds = timings.seconds(package)?;
+                let style = if members.contains(&package.id) {
+                    theme().map_member
+                } else if direct.contains(&package.id) {
+                    theme().map_dependency
+                } else {
+                    theme().map_transitive
+                };
+                Some(TreemapItem {
+                    label: format!("{} v{}", package.name, package.version),
+                    caption: format!("{seconds:.2}s"),
+                    value: seconds,
+                    style,
+                })
+            })
+            .collect::<Vec<_>>();
+        packages.sort_by(|a, b| b.value.total_cmp(&a.value));
+
+        let several_members = members.len() > 1;
+        let features = timings
+            .feature_costs(metadata)
+            .into_iter()
+            .filter(|cost| cost.seconds > 0.0)
+            .map(|cost| {
+                let label = if several_members {
+                    format!("{}: {}/{}", cost.member.name, cost.dep_name, cost.feature)
+                } else {
+                    format!("{}/{}", cost.dep_name, cost.feature)
+                };
+                TreemapItem {
+                    label,
+                    caption: format!(
+                        "{:.2}s, {} {}",
+                        cost.seconds,
+                        cost.packages.len(),
+                        if cost.packages.len() == 1 {
+                            "package"
+                        } else {
+                            "packages"
+                        }
+                    ),
+                    value: cost.seconds,
+                    style: theme().map_feature,
+                }
+            })
+            .collect();
+
+        Some(Self {
+            mode: Mode::default(),
+            packages,
+            features,
+            selected: 0,
+            rects: Vec::new(),
+        })
+    }
+
+    fn items(&self) -> &[TreemapItem] {
+        match self.mode {
+            Mode::Packages => &self.packages,
+            Mode::Features => &self.features,
+        }
+    }
+
+    pub fn breadcrumbs(&self) -> Vec<Span<'static>> {
+        let items = self.items();
+        let total = items.iter().map(|item| item.value).sum::<f64>();
+        let mut spans = vec![
+            Span::raw(" compile times > "),
+            Span::styled(
+                match self.mode {
+                    Mode::Packages => "packages",
+                    Mode::Features => "features",
+                },
+                theme().emphasis,
+            ),
+            Span::raw(format!(" ({total:.2}s)")),
+        ];
+        if let Some(item) = items.get(self.selected) {
+            spans.push(Span::raw(" > "));
+            spans.push(Span::styled(item.label.clone(), theme().emphasis));
+            spans.push(Span::raw(format!(" {}", item.caption)));
+        }
+        spans.push(Span::raw(" "));
+        spans
+    }
+
+    pub fn help(&self) -> Vec<Span<'static>> {
+        vec![
+            Span::styled(" ", theme().dim),
+            Span::styled("<tab>", theme().key),
+            match self.mode {
+                Mode::Packages => Span::styled(" features", theme().dim),
+                Mode::Features => Span::styled(" packages", theme().dim),
+            },
+        ]
+        .into_iter()
+        .chain(keymap().global_help())
+        .collect()
+    }
+}
+
+impl Component for CompileTimesView {
+    fn handle_key_events(&mut self, key_event: event::KeyEvent) -> Result<Option<Action>> {
+        let (direction, forward) = match key_event.code {
+            event::KeyCode::Tab => {
+                self.mode = match self.mode {
+                    Mode::Packages => Mode::Features,
+                    Mode::Features => Mode::Packages,
+                };
+                self.selected = 0;
+                return Action::render();
+            }
+            event::KeyCode::Left => (Direction::Horizontal, false),
+            event::KeyCode::Right => (Direction::Horizontal, true),
+            event::KeyCode::Up => (Direction::Vertical, false),
+            event::KeyCode::Down => (Direction::Vertical, true),
+            _ => return Action::none(),
+        };
+        self.selected = treemap::neighbor(&self.rects, self.selected, direction, forward);
+        Action::render()
+    }
+
+    fn render(&mut self, f: &mut Frame, rect: Rect) {
+        self.rects = Treemap::layout(self.items(), rect);
+        let treemap = Treemap::new(self.items()).select(Some(self.selected));
+        f.render_widget(treemap, rect);
+    }
+}
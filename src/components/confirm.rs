@@ -0,0 +1,84 @@
+use crossterm::event;
+use eyre::Result;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+};
+
+use crate::action::Action;
+use crate::component::Component;
+
+/// Popup asking to confirm an action.
+#[derive(Debug)]
+pub struct ConfirmDialog {
+    title: String,
+    text: Vec<Line<'static>>,
+    /// Sent when confirmed.
+    action: Action,
+    error: Option<String>,
+}
+
+impl ConfirmDialog {
+    pub fn new(title: impl Into<String>, text: Vec<Line<'static>>, action: Action) -> Self {
+        Self {
+            title: title.into(),
+            text,
+            action,
+            error: None,
+        }
+    }
+
+    /// Shows an error, e.g. if the confirmed action failed.
+    pub fn set_error(&mut self, err: eyre::Report) {
+        self.error = Some(format!("{err:#}"));
+    }
+}
+
+impl Component for ConfirmDialog {
+    fn handle_key_events(&mut self, key_event: event::KeyEvent) -> Result<Option<Action>> {
+        match key_event.code {
+            event::KeyCode::Enter | event::KeyCode::Char('y') => Ok(Some(self.action.clone())),
+            event::KeyCode::Esc | event::KeyCode::Char('n') => Ok(Some(Action::CloseDialog)),
+            _ => Action::none(),
+        }
+    }
+
+    fn render(&mut self, f: &mut Frame, rect: Rect) {
+        let mut text = self.text.clone();
+        if let Some(err) = &self.error {
+            text.push(Line::default());
+            text.push(Line::from(err.clone().red()));
+        }
+
+        let width = rect.width * 6 / 10;
+        // borders, and some room for wrapped lines
+        let height = (text.len() as u16 + 4).min(rect.height);
+        let [area] = Layout::vertical([Constraint::Length(height)])
+            .flex(layout::Flex::Center)
+            .areas(rect);
+        let [area] = Layout::horizontal([Constraint::Length(width)])
+            .flex(layout::Flex::Center)
+            .areas(area);
+
+        let help = vec![
+            " ".dim(),
+            "<enter>".blue(),
+            " confirm".dim(),
+            " ".dim(),
+            "<esc>".blue(),
+            " cancel".dim(),
+            " ".dim(),
+        ];
+        let block = Block::default()
+            .title(format!(" {} ", self.title))
+            .title_bottom(help)
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Double);
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(text).block(block).wrap(Wrap { trim: false }),
+            area,
+        );
+    }
+}
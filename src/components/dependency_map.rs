@@ -0,0 +1,212 @@
+use cargo_metadata::{DependencyKind, Metadata, PackageId};
+use crossterm::event;
+use eyre::Result;
+use ratatui::prelude::*;
+use std::sync::Arc;
+
+use crate::action::Action;
+use crate::component::Component;
+use crate::keymap::keymap;
+use crate::metadata::dependency_size::{dependency_sizes, DependencySize, SourceLines};
+use crate::metadata::workspace_info::WorkspaceInfo;
+use crate::theme::theme;
+
+use super::treemap::{self, Treemap, TreemapItem};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Measure {
+    #[default]
+    Packages,
+    Lines,
+}
+
+/// Treemap of the direct dependencies of a package, sized by the number of
+/// packages each one brings in or by their source lines. Selecting a
+/// dependency drills into its own dependencies.
+#[derive(Debug, Clone)]
+pub struct DependencyMapView {
+    metadata: Arc<Metadata>,
+    /// The packages drilled into, starting with the one the view was opened
+    /// for.
+    path: Vec<(PackageId, String)>,
+    measure: Measure,
+    source_lines: SourceLines,
+    /// Sorted like `items`.
+    sizes: Vec<DependencySize>,
+    items: Vec<TreemapItem>,
+    selected: usize,
+    rects: Vec<Rect>,
+}
+
+impl DependencyMapView {
+    pub fn new(info: &WorkspaceInfo, package: &PackageId) -> Result<Self> {
+        let Some(root) = info.dependency_resolver().package(package) else {
+            eyre::bail!("Could not resolve package {package}");
+        };
+        let mut view = Self {
+            metadata: info.metadata.clone(),
+            path: vec![(root.id.clone(), root.name.clone())],
+            measure: Measure::default(),
+            source_lines: SourceLines::default(),
+            sizes: Vec::new(),
+            items: Vec::new(),
+            selected: 0,
+            rects: Vec::new(),
+        };
+        view.load();
+        Ok(view)
+    }
+
+    /// Computes the rectangles for the last package of the path.
+    fn load(&mut self) {
+        let Some((id, _)) = self.path.last() else {
+            return;
+        };
+
+        let mut sizes = dependency_sizes(&self.metadata, id)
+            .into_iter()
+            .map(|size| {
+                let value = match self.measure {
+                    Measure::Packages => size.packages.len(),
+                    Measure::Lines => size.lines(&self.metadata, &mut self.source_lines),
+                };
+                (size, value)
+            })
+            .collect::<Vec<_>>();
+        sizes.sort_by(|(a, a_value), (b, b_value)| b_value.cmp(a_value).then(a.name.cmp(&b.name)));
+
+        self.items = sizes
+            .iter()
+            .map(|(size, value)| TreemapItem {
+                label: match size.kind {
+                    DependencyKind::Normal => size.name.clone(),
+                    kind => format!("{} ({kind})", size.name),
+                },
+                caption: match self.measure {
+                    Measure::Packages if *value == 1 => "1 package".to_string(),
+                    Measure::Packages => format!("{value} packages"),
+                    Measure::Lines => format!("{} lines", format_count(*value)),
+                },
+                value: *value as f64,
+                style: match size.kind {
+                    DependencyKind::Development => theme().map_dev_dependency,
+                    DependencyKind::Build => theme().map_build_dependency,
+                    _ => theme().map_dependency,
+                },
+            })
+            .collect();
+        self.sizes = sizes.into_iter().map(|(size, _)| size).collect();
+        self.selected = 0;
+    }
+
+    fn drill_down(&mut self) {
+        let Some(size) = self.sizes.get(self.selected) else {
+            return;
+        };
+        // nothing to show for packages without dependencies
+        if size.packages.len() <= 1 {
+            return;
+        }
+        self.path.push((size.id.clone(), size.name.clone()));
+        self.load();
+    }
+
+    fn go_up(&mut self) {
+        if self.path.len() <= 1 {
+            return;
+        }
+        let (left, _) = self.path.pop().expect("path is not empty");
+        self.load();
+        if let Some(i) = self.sizes.iter().position(|size| size.id == left) {
+            self.selected = i;
+        }
+    }
+
+    pub fn breadcrumbs(&self) -> Vec<Span<'static>> {
+        let mut spans = vec![Span::raw(" ")];
+        for (i, (_, name)) in self.path.iter().enumerate() {
+            if i > 0 {
+                spans.push(Span::raw(" > "));
+            }
+            spans.push(Span::styled(name.clone(), theme().emphasis));
+        }
+        if let Some(item) = self.items.get(self.selected) {
+            spans.push(Span::raw(" > "));
+            spans.push(Span::raw(format!("{} ({})", item.label, item.caption)));
+        }
+        spans.push(Span::raw(" "));
+        spans
+    }
+
+    pub fn help(&self) -> Vec<Span<'static>> {
+        vec![
+            Span::styled(" ", theme().dim),
+            Span::styled("<enter>", theme().key),
+            Span::styled(" open", theme().dim),
+            Span::styled(" ", theme().dim),
+            Span::styled("<backspace>", theme().key),
+            Span::styled(" up", theme().dim),
+            Span::styled(" ", theme().dim),
+            Span::styled("<tab>", theme().key),
+            match self.measure {
+                Measure::Packages => Span::styled(" lines", theme().dim),
+                Measure::Lines => Span::styled(" packages", theme().dim),
+            },
+        ]
+        .into_iter()
+        .chain(keymap().global_help())
+        .collect()
+    }
+}
+
+/// `12345` -> `12.3k`
+fn format_count(count: usize) -> String {
+    if count >= 1_000_000 {
+        format!("{:.1}M", count as f64 / 1_000_000.0)
+    } else if count >= 1_000 {
+        format!("{:.1}k", count as f64 / 1_000.0)
+    } else {
+        count.to_string()
+    }
+}
+
+impl Component for DependencyMapView {
+    fn handle_key_events(&mut self, key_event: event::KeyEvent) -> Result<Option<Action>> {
+        let (direction, forward) = match key_event.code {
+            event::KeyCode::Enter => {
+                self.drill_down();
+                return Action::render();
+            }
+            event::KeyCode::Backspace => {
+                self.go_up();
+                return Action::render();
+            }
+            event::KeyCode::Tab => {
+                self.measure = match self.measure {
+                    Measure::Packages => Measure::Lines,
+                    Measure::Lines => Measure::Packages,
+                };
+                let selected = self.sizes.get(self.selected).map(|size| size.id.clone());
+                self.load();
+                if let Some(i) = selected.and_then(|id| self.sizes.iter().position(|s| s.id == id))
+                {
+                    self.selected = i;
+                }
+                return Action::render();
+            }
+            event::KeyCode::Left => (Direction::Horizontal, false),
+            event::KeyCode::Right => (Direction::Horizontal, true),
+            event::KeyCode::Up => (Direction::Vertical, false),
+            event::KeyCode::Down => (Direction::Vertical, true),
+            _ => return Action::none(),
+        };
+        self.selected = treemap::neighbor(&self.rects, self.selected, direction, forward);
+        Action::render()
+    }
+
+    fn render(&mut self, f: &mut Frame, rect: Rect) {
+        self.rects = Treemap::layout(&self.items, rect);
+        let treemap = Treemap::new(&self.items).select(Some(self.selected));
+        f.render_widget(treemap, rect);
+    }
+}
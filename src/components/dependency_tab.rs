@@ -1,42 +1,237 @@
+use std::collections::VecDeque;
 use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::Instant;
 
+use cargo_metadata::{DependencyKind, Package, PackageId};
 use crossterm::event::{self, Event};
 use eyre::Result;
 use ratatui::{
     prelude::*,
     widgets::{
         block::{Position, Title},
-        Block, BorderType, Borders,
+        Block, BorderType, Borders, Tabs,
     },
 };
 
+use crate::cargo::{self, FeatureEdit};
 use crate::component::Component;
+use crate::config::config;
+use crate::keymap::{keymap, Command};
+use crate::metadata::crate_references::{crate_identifier, find_crate_references};
+use crate::metadata::dep_tree::DepTree;
+use crate::metadata::workspace_info::FeatureToggle;
+use crate::theme::theme;
 use crate::{action::Action, metadata::workspace_info::WorkspaceInfo};
 
-use super::dependency_tree::DependencyTree;
+use super::{
+    add_dependency::AddDependencyDialog,
+    checklist::{ChecklistDialog, ChecklistTarget},
+    compile_times::CompileTimesView,
+    confirm::ConfirmDialog,
+    dependency_map::DependencyMapView,
+    dependency_tree::DependencyTree,
+    details::Details,
+    duplicates::DuplicatesView,
+    help::HelpDialog,
+    input::{InputDialog, InputTarget},
+    inverse_tree::InverseTreeView,
+    policy::PolicyView,
+};
+
+/// The views of the tab bar, selected with the number keys.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Tab {
+    #[default]
+    Packages,
+    /// The dependency tree rooted at the dependencies.
+    Dependencies,
+    Duplicates,
+    Policy,
+}
+
+impl Tab {
+    const ALL: [Tab; 4] = [
+        Tab::Packages,
+        Tab::Dependencies,
+        Tab::Duplicates,
+        Tab::Policy,
+    ];
+
+    /// Whether the tab shows a dependency tree and the views opened from it.
+    fn has_tree(self) -> bool {
+        matches!(self, Tab::Packages | Tab::Dependencies)
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Tab::Packages => "packages",
+            Tab::Dependencies => "dependencies",
+            Tab::Duplicates => "duplicates",
+            Tab::Policy => "policy",
+        }
+    }
+}
 
+/// What the tree tabs show, the tree or a view opened from it.
 #[derive(Debug, Clone, Default)]
 enum View {
     #[default]
     DependencyTree,
+    InverseTree(InverseTreeView),
+    CompileTimes(CompileTimesView),
+    DependencyMap(DependencyMapView),
+}
+
+/// Popups shown above the current view.
+#[derive(Debug)]
+enum Dialog {
+    AddDependency(AddDependencyDialog),
+    Checklist(ChecklistDialog),
+    Confirm(ConfirmDialog),
+    Help(HelpDialog),
+    Input(InputDialog),
+}
+
+impl Dialog {
+    fn set_error(&mut self, err: eyre::Report) {
+        match self {
+            Dialog::AddDependency(dialog) => dialog.set_error(err),
+            Dialog::Checklist(dialog) => dialog.set_error(err),
+            Dialog::Confirm(dialog) => dialog.set_error(err),
+            Dialog::Help(_) => error!("{err:#}"),
+            Dialog::Input(dialog) => dialog.set_error(err),
+        }
+    }
+}
+
+impl Component for Dialog {
+    fn handle_key_events(&mut self, key_event: event::KeyEvent) -> Result<Option<Action>> {
+        match self {
+            Dialog::AddDependency(dialog) => dialog.handle_key_events(key_event),
+            Dialog::Checklist(dialog) => dialog.handle_key_events(key_event),
+            Dialog::Confirm(dialog) => dialog.handle_key_events(key_event),
+            Dialog::Help(dialog) => dialog.handle_key_events(key_event),
+            Dialog::Input(dialog) => dialog.handle_key_events(key_event),
+        }
+    }
+
+    fn render(&mut self, f: &mut Frame, rect: Rect) {
+        match self {
+            Dialog::AddDependency(dialog) => dialog.render(f, rect),
+            Dialog::Checklist(dialog) => dialog.render(f, rect),
+            Dialog::Confirm(dialog) => dialog.render(f, rect),
+            Dialog::Help(dialog) => dialog.render(f, rect),
+            Dialog::Input(dialog) => dialog.render(f, rect),
+        }
+    }
+}
+
+/// A manifest edit or refresh running on a background thread. It works on a
+/// copy of the workspace info that replaces the current one when it is done,
+/// the UI keeps using the current one until then.
+#[derive(Debug)]
+struct Task {
+    label: &'static str,
+    started: Instant,
+    /// Whether the task was started from a dialog, which is closed when it
+    /// succeeds and shows the error otherwise.
+    from_dialog: bool,
+    result: mpsc::Receiver<Result<Loaded>>,
+}
+
+type Work = Box<dyn FnOnce(&mut WorkspaceInfo) -> Result<()> + Send>;
+
+/// An edit or refresh requested while a task runs, started after it.
+struct Queued {
+    label: &'static str,
+    from_dialog: bool,
+    work: Work,
+}
+
+impl std::fmt::Debug for Queued {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Queued")
+            .field("label", &self.label)
+            .field("from_dialog", &self.from_dialog)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The reloaded workspace info of a task and what is shown of it, built on
+/// the background thread as well. Only the tree states are updated on the UI
+/// thread.
+#[derive(Debug)]
+struct Loaded {
+    info: WorkspaceInfo,
+    tree: DepTree,
+    tree_by_dependency: DepTree,
+    duplicates: DuplicatesView,
+    policy: PolicyView,
+    compile_times: Option<CompileTimesView>,
+}
+
+impl Loaded {
+    fn new(info: WorkspaceInfo) -> Self {
+        Self {
+            tree: info.tree(&config().filter),
+            tree_by_dependency: info.tree_by_dependency(&config().filter),
+            duplicates: DuplicatesView::new(&info),
+            policy: PolicyView::new(&info),
+            compile_times: CompileTimesView::new(&info),
+            info,
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct DependencyTab {
+    tab: Tab,
     view: View,
+    duplicates: DuplicatesView,
+    policy: PolicyView,
+    /// `None` without a timings report.
+    compile_times: Option<CompileTimesView>,
     workspace_info: WorkspaceInfo,
     dependency_tree: DependencyTree,
+    /// The tree of the dependencies tab.
+    by_dependency: DependencyTree,
+    details: Details,
+    /// Popup that receives all keys while open.
+    dialog: Option<Dialog>,
+    /// Only one task runs at a time, edits requested meanwhile wait in
+    /// `queue`.
+    task: Option<Task>,
+    queue: VecDeque<Queued>,
+    /// Where the tab was rendered last, to map clicks on the breadcrumbs.
+    area: Rect,
+    tab_bar: Rect,
 }
 
 impl DependencyTab {
-    pub fn new(config_toml: impl Into<PathBuf>) -> Result<Self> {
-        let workspace_info = WorkspaceInfo::load(config_toml)?;
+    pub fn new(config_toml: impl Into<PathBuf>, timings: Option<PathBuf>) -> Result<Self> {
+        let mut workspace_info = WorkspaceInfo::load(config_toml)?;
+        if let Some(timings) = timings {
+            workspace_info.load_timings(timings)?;
+        }
         let dependency_tree = DependencyTree::new(&workspace_info)?;
+        let by_dependency = DependencyTree::by_dependency(&workspace_info)?;
 
         Ok(Self {
+            tab: Tab::default(),
+            duplicates: DuplicatesView::new(&workspace_info),
+            policy: PolicyView::new(&workspace_info),
+            compile_times: CompileTimesView::new(&workspace_info),
             workspace_info,
             dependency_tree,
+            by_dependency,
+            details: Default::default(),
+            dialog: None,
+            task: None,
+            queue: VecDeque::new(),
             view: Default::default(),
+            area: Rect::default(),
+            tab_bar: Rect::default(),
             // view: View::FeatureGraph {
             //     parent_package: PackageId{repr:"doppelgaenger-server 0.1.0 (path+file:///Users/robert/projects/biz/podwriter/backend/doppelgaenger-server)".to_string()},
             //     dep_name:"async-openai".to_string(),
@@ -44,17 +239,196 @@ impl DependencyTab {
         })
     }
 
-    fn update(&mut self) -> Result<()> {
-        self.workspace_info.update()?;
-        self.dependency_tree.update(&self.workspace_info);
-        Ok(())
+    /// Shows what a task loaded. The tree states are kept, so the selection
+    /// and the open nodes stay.
+    fn load(&mut self, loaded: Loaded) {
+        self.workspace_info = loaded.info;
+        self.dependency_tree
+            .update(&self.workspace_info, loaded.tree);
+        self.by_dependency
+            .update(&self.workspace_info, loaded.tree_by_dependency);
+        self.duplicates = loaded.duplicates;
+        self.policy = loaded.policy;
+        self.compile_times = loaded.compile_times;
+        self.details.clear();
+    }
+
+    /// The tree of the current tab, or of the packages tab if it has none.
+    fn tree(&mut self) -> &mut DependencyTree {
+        match self.tab {
+            Tab::Dependencies => &mut self.by_dependency,
+            _ => &mut self.dependency_tree,
+        }
+    }
+
+    fn set_tab(&mut self, tab: Tab) {
+        // views opened from one tree don't belong to the other
+        if tab.has_tree() && self.tab.has_tree() && tab != self.tab {
+            self.view = View::DependencyTree;
+        }
+        self.tab = tab;
     }
 
     fn refresh(&mut self) -> Result<Option<Action>> {
-        self.update()?;
+        self.spawn("refreshing", |_| Ok(()))
+    }
+
+    /// Runs `work`, usually a manifest edit, and then reloads the workspace
+    /// info on a background thread, see [`Task`]. If a task runs already,
+    /// `work` is queued and runs on the info that task loads.
+    fn spawn(
+        &mut self,
+        label: &'static str,
+        work: impl FnOnce(&mut WorkspaceInfo) -> Result<()> + Send + 'static,
+    ) -> Result<Option<Action>> {
+        let queued = Queued {
+            label,
+            from_dialog: self.dialog.is_some(),
+            work: Box::new(work),
+        };
+        match &self.task {
+            Some(task) => {
+                debug!("{label} after {}", task.label);
+                self.queue.push_back(queued);
+            }
+            None => self.start(queued),
+        }
         Action::render()
     }
 
+    fn start(&mut self, queued: Queued) {
+        let Queued {
+            label,
+            from_dialog,
+            work,
+        } = queued;
+        let mut info = self.workspace_info.clone();
+        let (sender, result) = mpsc::channel();
+        std::thread::spawn(move || {
+            let result = work(&mut info)
+                .and_then(|()| info.update())
+                .map(|()| Loaded::new(info));
+            // the receiver is gone if the app quit meanwhile
+            let _ = sender.send(result);
+        });
+        self.task = Some(Task {
+            label,
+            started: Instant::now(),
+            from_dialog,
+            result,
+        });
+    }
+
+    /// Takes over the result of the task if it is done.
+    fn poll_task(&mut self) -> Result<Option<Action>> {
+        let Some(task) = &self.task else {
+            return Action::none();
+        };
+        let result = match task.result.try_recv() {
+            // redraw the spinner
+            Err(mpsc::TryRecvError::Empty) => return Action::render(),
+            Err(mpsc::TryRecvError::Disconnected) => Err(eyre::eyre!("{} failed", task.label)),
+            Ok(result) => result,
+        };
+        let from_dialog = task.from_dialog;
+        self.task = None;
+
+        let action = match result {
+            Ok(loaded) => {
+                self.load(loaded);
+                if from_dialog {
+                    self.dialog = None;
+                }
+                Action::render()
+            }
+            Err(err) => match &mut self.dialog {
+                Some(dialog) if from_dialog => {
+                    dialog.set_error(err);
+                    Action::render()
+                }
+                _ => Err(err),
+            },
+        };
+        // on top of what was just loaded
+        if let Some(next) = self.queue.pop_front() {
+            self.start(next);
+        }
+        action
+    }
+
+    /// The spinner and label of the running task.
+    fn status(&self) -> Option<Line<'static>> {
+        let task = self.task.as_ref()?;
+        let spinner = theme().icons.spinner;
+        let elapsed = task.started.elapsed();
+        let frame = spinner[(elapsed.as_millis() / 100) as usize % spinner.len()];
+        let mut spans = vec![
+            Span::styled(frame, theme().key),
+            Span::raw(format!(" {} ", task.label)),
+            Span::styled(format!("{}s ", elapsed.as_secs()), theme().dim),
+        ];
+        if !self.queue.is_empty() {
+            spans.push(Span::styled(
+                format!("+{} queued ", self.queue.len()),
+                theme().warning,
+            ));
+        }
+        Some(Line::from(spans))
+    }
+
+    fn tab_titles(&self) -> Vec<Line<'static>> {
+        Tab::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, tab)| {
+                let count = match tab {
+                    Tab::Packages | Tab::Dependencies => None,
+                    Tab::Duplicates => Some(self.duplicates.count()),
+                    Tab::Policy => Some(self.policy.count()),
+                };
+                let mut spans = vec![
+                    Span::styled(format!("{}", i + 1), theme().key),
+                    Span::raw(format!(" {}", tab.title())),
+                ];
+                if let Some(count) = count.filter(|count| *count > 0) {
+                    spans.push(Span::styled(format!(" ({count})"), theme().dim));
+                }
+                Line::from(spans)
+            })
+            .collect()
+    }
+
+    /// The tab whose title is at `column` of the tab bar.
+    fn tab_at(&self, column: u16) -> Option<Tab> {
+        let mut x = self.tab_bar.x;
+        for (title, tab) in self.tab_titles().iter().zip(Tab::ALL) {
+            // a space on both sides and the divider
+            let width = title.width() as u16 + 2;
+            if (x..x + width).contains(&column) {
+                return Some(tab);
+            }
+            x += width + 1;
+        }
+        None
+    }
+
+    fn render_tab_bar(&mut self, f: &mut Frame, rect: Rect) {
+        let status = self.status().unwrap_or_default();
+        let [rect, status_area] = Layout::horizontal([
+            Constraint::Min(0),
+            Constraint::Length(status.width() as u16),
+        ])
+        .areas(rect);
+        f.render_widget(status, status_area);
+
+        self.tab_bar = rect;
+        let selected = Tab::ALL.iter().position(|tab| *tab == self.tab);
+        let tabs = Tabs::new(self.tab_titles())
+            .select(selected.unwrap_or(0))
+            .highlight_style(theme().highlight);
+        f.render_widget(tabs, rect);
+    }
+
     fn apply_action(&mut self, action: Result<Option<Action>>) -> Result<Option<Action>> {
         match action {
             Ok(Some(Action::ShowFeatureTree {
@@ -63,26 +437,492 @@ impl DependencyTab {
             })) => {
                 crate::mermaid::FeatureGraph::new(&self.workspace_info, &parent_package, &dep_name)
                     .build()
-                    .render_and_open()?;
+                    .render_and_open(&config().graph)?;
                 Action::none()
             }
 
+            Ok(Some(Action::ShowDuplicates)) => {
+                self.set_tab(Tab::Duplicates);
+                Action::render()
+            }
+
+            Ok(Some(Action::ShowCompileTimes)) => {
+                let Some(compile_times) = self.compile_times.clone() else {
+                    eyre::bail!(
+                        "No compile times loaded. Run `cargo build --timings` and start with `--timings target/cargo-timings/cargo-timing.html`."
+                    );
+                };
+                self.view = View::CompileTimes(compile_times);
+                Action::render()
+            }
+
+            Ok(Some(Action::ShowDependencyMap { package })) => {
+                self.view =
+                    View::DependencyMap(DependencyMapView::new(&self.workspace_info, &package)?);
+                Action::render()
+            }
+
+            Ok(Some(Action::ShowInverseTree { package })) => {
+                self.view =
+                    View::InverseTree(InverseTreeView::new(&self.workspace_info, &package)?);
+                Action::render()
+            }
+
+            Ok(Some(Action::ShowAddDependency { package })) => {
+                let Some(package) = self.workspace_info.dependency_resolver().package(&package)
+                else {
+                    eyre::bail!("Could not resolve package {package}");
+                };
+                self.dialog = Some(Dialog::AddDependency(AddDependencyDialog::new(
+                    package.id.clone(),
+                    package.name.clone(),
+                )?));
+                Action::render()
+            }
+
+            Ok(Some(Action::Help)) => {
+                let tabs = format!("1-{}", Tab::ALL.len());
+                let tree_keys = [
+                    ("↑/↓", "move"),
+                    ("←/→", "collapse/expand"),
+                    (tabs.as_str(), "switch tab"),
+                ];
+                let global = Command::GLOBAL.map(|c| (c, c.label()));
+                let dialog = match (self.tab.has_tree(), &self.view) {
+                    (true, View::DependencyTree) => match self.tree().location() {
+                        Some(location) => HelpDialog::new("keys", &location.commands(), &tree_keys),
+                        None => HelpDialog::new("keys", &global, &tree_keys),
+                    },
+                    (true, _) => HelpDialog::new("keys", &global, &tree_keys[2..]),
+                    _ => HelpDialog::new("keys", &global, &tree_keys),
+                };
+                self.dialog = Some(Dialog::Help(dialog));
+                Action::render()
+            }
+
+            Ok(Some(Action::CloseDialog)) => {
+                self.dialog = None;
+                Action::render()
+            }
+
+            Ok(Some(Action::AddDependency {
+                package,
+                name,
+                version,
+                kind,
+                features,
+                default_features,
+            })) => self.spawn("adding dependency", move |info| {
+                cargo::AddDependency::new(&member(info, &package)?.manifest_path, name, version)
+                    .kind(kind)
+                    .features(features)
+                    .default_features(default_features)
+                    .apply()
+            }),
+
+            Ok(Some(Action::ShowRemoveDependency {
+                package,
+                dep_name,
+                dep_kind,
+            })) => {
+                let resolver = self.workspace_info.dependency_resolver();
+                let Some(member) = resolver.package(&package) else {
+                    eyre::bail!("Could not resolve package {package}");
+                };
+                let Some(dep) = member
+                    .dependencies
+                    .iter()
+                    .find(|dep| dep.name == dep_name && dep.kind == dep_kind)
+                else {
+                    eyre::bail!("{dep_name} is not a dependency of {}", member.name);
+                };
+                let ident = crate_identifier(dep, resolver.resolve_dependency(&package, &dep_name));
+                let references = find_crate_references(member, &ident);
+
+                let mut text = vec![Line::from(vec![
+                    Span::raw("Remove "),
+                    Span::styled(dep_name.clone(), theme().emphasis),
+                    Span::raw(format!(" from {}?", member.manifest_path)),
+                ])];
+                if references.is_empty() {
+                    text.push(Line::from(
+                        format!("`{ident}` is not referenced in the sources.")
+                            .set_style(theme().dim),
+                    ));
+                } else {
+                    text.push(Line::default());
+                    text.push(Line::from(
+                        format!("`{ident}` still appears to be used:").set_style(theme().warning),
+                    ));
+                    let root = member.manifest_path.parent();
+                    for reference in references.iter().take(10) {
+                        let path = root
+                            .and_then(|root| reference.path.strip_prefix(root).ok())
+                            .unwrap_or(&reference.path);
+                        text.push(Line::from(vec![
+                            Span::raw(format!("{}:{} ", path.display(), reference.line)),
+                            reference.text.clone().set_style(theme().dim),
+                        ]));
+                    }
+                    if references.len() > 10 {
+                        text.push(Line::from(
+                            format!("and {} more", references.len() - 10).set_style(theme().dim),
+                        ));
+                    }
+                }
+
+                self.dialog = Some(Dialog::Confirm(ConfirmDialog::new(
+                    format!("remove {dep_name}"),
+                    text,
+                    Action::RemoveDependency {
+                        package,
+                        dep_name,
+                        dep_kind,
+                    },
+                )));
+                Action::render()
+            }
+
+            Ok(Some(Action::RemoveDependency {
+                package,
+                dep_name,
+                dep_kind,
+            })) => self.spawn("removing dependency", move |info| {
+                cargo::RemoveDependency::new(member(info, &package)?, &dep_name, dep_kind).apply()
+            }),
+
+            Ok(Some(Action::ShowFeatureInput { package, feature })) => {
+                let resolver = self.workspace_info.dependency_resolver();
+                let Some(member) = resolver.package(&package) else {
+                    eyre::bail!("Could not resolve package {package}");
+                };
+                let dialog = match feature {
+                    None => InputDialog::new(
+                        format!("new feature of {}", member.name),
+                        InputTarget::NewFeature { package },
+                        Vec::new(),
+                    ),
+                    Some(feature) => {
+                        let existing = member.features.get(&feature).cloned().unwrap_or_default();
+                        let mut suggestions = member
+                            .features
+                            .keys()
+                            .filter(|name| **name != feature)
+                            .cloned()
+                            .collect::<Vec<_>>();
+                        for dep in &member.dependencies {
+                            let key = dep.rename.as_deref().unwrap_or(&dep.name);
+                            if dep.kind != DependencyKind::Development {
+                                suggestions.push(format!("dep:{key}"));
+                            }
+                            if let Some(dep_package) =
+                                resolver.resolve_dependency(&package, &dep.name)
+                            {
+                                suggestions.extend(
+                                    dep_package.features.keys().map(|f| format!("{key}/{f}")),
+                                );
+                            }
+                        }
+                        suggestions.retain(|s| !existing.contains(s));
+                        suggestions.dedup();
+                        InputDialog::new(
+                            format!("add to feature {feature}"),
+                            InputTarget::FeatureEntry { package, feature },
+                            suggestions,
+                        )
+                    }
+                };
+                self.dialog = Some(Dialog::Input(dialog));
+                Action::render()
+            }
+
+            Ok(Some(Action::ShowRemoveFeature { package, feature })) => {
+                let Some(member) = self.workspace_info.dependency_resolver().package(&package)
+                else {
+                    eyre::bail!("Could not resolve package {package}");
+                };
+                let mut text = vec![Line::from(vec![
+                    Span::raw("Remove feature "),
+                    Span::styled(feature.clone(), theme().emphasis),
+                    Span::raw(format!(" from {}?", member.manifest_path)),
+                ])];
+                let used_by = member
+                    .features
+                    .iter()
+                    .filter(|(_, entries)| entries.contains(&feature))
+                    .map(|(name, _)| name.as_str())
+                    .collect::<Vec<_>>();
+                if !used_by.is_empty() {
+                    text.push(Line::default());
+                    text.push(Line::from(
+                        format!(
+                            "It is enabled by {}, remove it there first.",
+                            used_by.join(", ")
+                        )
+                        .set_style(theme().warning),
+                    ));
+                }
+                self.dialog = Some(Dialog::Confirm(ConfirmDialog::new(
+                    format!("remove feature {feature}"),
+                    text,
+                    Action::EditFeatures {
+                        package,
+                        edit: FeatureEdit::Remove(feature),
+                    },
+                )));
+                Action::render()
+            }
+
+            Ok(Some(Action::ShowToggleOptional {
+                package,
+                dep_name,
+                dep_kind,
+            })) => {
+                let Some(member) = self.workspace_info.dependency_resolver().package(&package)
+                else {
+                    eyre::bail!("Could not resolve package {package}");
+                };
+                let Some(dep) = member
+                    .dependencies
+                    .iter()
+                    .find(|dep| dep.name == dep_name && dep.kind == dep_kind)
+                else {
+                    eyre::bail!("{dep_name} is not a dependency of {}", member.name);
+                };
+                let key = dep.rename.clone().unwrap_or_else(|| dep.name.clone());
+
+                if dep.optional {
+                    let mut text = vec![Line::from(vec![
+                        Span::raw("Make "),
+                        Span::styled(key.clone(), theme().emphasis),
+                        Span::raw(format!(" a required dependency of {}?", member.name)),
+                    ])];
+                    let enabled_by = member
+                        .features
+                        .iter()
+                        .filter(|(_, entries)| {
+                            entries.iter().any(|e| {
+                                *e == format!("dep:{key}") || e.starts_with(&format!("{key}?/"))
+                            })
+                        })
+                        .map(|(name, _)| name.as_str())
+                        .collect::<Vec<_>>();
+                    if !enabled_by.is_empty() {
+                        text.push(Line::from(
+                            format!(
+                                "Features referring to it are updated: {}",
+                                enabled_by.join(", ")
+                            )
+                            .set_style(theme().dim),
+                        ));
+                    }
+                    self.dialog = Some(Dialog::Confirm(ConfirmDialog::new(
+                        format!("make {key} required"),
+                        text,
+                        Action::EditFeatures {
+                            package,
+                            edit: FeatureEdit::MakeRequired { dep: key },
+                        },
+                    )));
+                } else {
+                    // a feature named like the dependency comes first
+                    let mut suggestions = vec![key.clone()];
+                    suggestions
+                        .extend(member.features.keys().filter(|name| **name != key).cloned());
+                    self.dialog = Some(Dialog::Input(InputDialog::new(
+                        format!("make {key} optional behind feature"),
+                        InputTarget::OptionalDependency { package, dep: key },
+                        suggestions,
+                    )));
+                }
+                Action::render()
+            }
+
+            Ok(Some(Action::EditFeatures { package, edit })) => {
+                self.spawn("editing features", move |info| {
+                    cargo::EditFeatures::new(member(info, &package)?)
+                        .edit(edit)
+                        .apply()
+                })
+            }
+
+            Ok(Some(Action::BumpDependency {
+                package,
+                dep_name,
+                dep_kind,
+                version,
+            })) => self.spawn("upgrading", move |info| {
+                cargo::BumpDependency::new(member(info, &package)?, &dep_name, dep_kind, version)
+                    .workspace_manifest(info.metadata.workspace_root.join("Cargo.toml"))
+                    .apply()
+            }),
+
+            Ok(Some(Action::ShowDisableDefaultFeatures {
+                package,
+                dep_name,
+                dep_kind,
+            })) => {
+                let resolver = self.workspace_info.dependency_resolver();
+                let Some(member) = resolver.package(&package) else {
+                    eyre::bail!("Could not resolve package {package}");
+                };
+                let Some(dep) = member
+                    .dependencies
+                    .iter()
+                    .find(|dep| dep.name == dep_name && dep.kind == dep_kind)
+                else {
+                    eyre::bail!("{dep_name} is not a dependency of {}", member.name);
+                };
+                let Some(dep_package) = resolver.resolve_dependency(&package, &dep_name) else {
+                    eyre::bail!("Could not resolve dependency {dep_name}");
+                };
+                // only features can be re-added, not `dep:x` or `x/y` entries
+                let items = dep_package
+                    .features
+                    .get("default")
+                    .into_iter()
+                    .flatten()
+                    .filter(|f| dep_package.features.contains_key(*f) && !dep.features.contains(f))
+                    .map(|f| (f.clone(), true))
+                    .collect::<Vec<_>>();
+                let mut text = vec![Line::from(vec![
+                    Span::raw("Turn off the default features of "),
+                    Span::styled(dep_name.clone(), theme().emphasis),
+                    Span::raw(format!(" in {}.", member.name)),
+                ])];
+                text.push(Line::from(if items.is_empty() {
+                    "The default feature does not enable other features.".set_style(theme().dim)
+                } else {
+                    "Keep these features of default:".set_style(theme().dim)
+                }));
+                self.dialog = Some(Dialog::Checklist(ChecklistDialog::new(
+                    format!("{dep_name} default-features = false"),
+                    text,
+                    ChecklistTarget::DisableDefaultFeatures {
+                        package,
+                        dep_name,
+                        dep_kind,
+                    },
+                    items,
+                )));
+                Action::render()
+            }
+
+            Ok(Some(Action::SetDefaultFeatures {
+                package,
+                dep_name,
+                dep_kind,
+                enabled,
+                features,
+            })) => self.spawn("setting default features", move |info| {
+                info.set_default_features(package, dep_name, dep_kind, enabled, features)
+            }),
+
+            Ok(Some(Action::ExpandDependency { node })) => {
+                let tree = match self.tab {
+                    Tab::Dependencies => &mut self.by_dependency,
+                    _ => &mut self.dependency_tree,
+                };
+                tree.expand(node, &self.workspace_info);
+                Action::render()
+            }
+
             Ok(Some(Action::ToggleFeature {
                 parent_package,
                 dep_name,
                 dep_kind,
                 feature_name,
                 feature_status,
-            })) => {
-                self.workspace_info.toggle_feature(
+            })) => self.spawn("toggling feature", move |info| {
+                info.toggle_feature(
                     parent_package,
                     dep_name,
                     dep_kind,
                     feature_name,
                     feature_status,
-                )?;
-                self.refresh()
+                )
+            }),
+            Ok(Some(Action::ShowApplyEverywhere {
+                package,
+                dep_name,
+                dep_kind,
+                feature,
+            })) => {
+                let declares = |member: &cargo_metadata::Package| {
+                    member
+                        .dependencies
+                        .iter()
+                        .find(|dep| dep.name == dep_name && dep.kind == dep_kind)
+                        .map(|dep| dep.features.contains(&feature))
+                };
+                let Some(enable) = self
+                    .workspace_info
+                    .dependency_resolver()
+                    .package(&package)
+                    .and_then(declares)
+                    .map(|enabled| !enabled)
+                else {
+                    eyre::bail!("{dep_name} is not a dependency of {package}");
+                };
+
+                let mut affected = Vec::new();
+                let mut unchanged = Vec::new();
+                for member in self.workspace_info.workspace_packages() {
+                    match declares(member) {
+                        Some(enabled) if enabled != enable => affected.push(member),
+                        Some(_) => unchanged.push(member.name.as_str()),
+                        None => {}
+                    }
+                }
+
+                let verb = if enable { "Enable" } else { "Disable" };
+                let mut text = vec![Line::from(vec![
+                    Span::raw(format!("{verb} feature ")),
+                    Span::styled(feature.clone(), theme().emphasis),
+                    Span::raw(match dep_kind {
+                        DependencyKind::Normal => format!(" of {dep_name} in:"),
+                        _ => format!(" of {dep_name} ({dep_kind}) in:"),
+                    }),
+                ])];
+                for member in &affected {
+                    text.push(Line::from(format!("  {}", member.name)));
+                }
+                if !unchanged.is_empty() {
+                    text.push(Line::from(
+                        format!("Already as wanted in {}.", unchanged.join(", "))
+                            .set_style(theme().dim),
+                    ));
+                }
+                let features = affected
+                    .iter()
+                    .map(|member| FeatureToggle {
+                        package: member.id.clone(),
+                        dep_name: dep_name.clone(),
+                        dep_kind,
+                        feature: feature.clone(),
+                    })
+                    .collect();
+                self.dialog = Some(Dialog::Confirm(ConfirmDialog::new(
+                    format!("{} {feature} everywhere", verb.to_lowercase()),
+                    text,
+                    Action::ToggleFeatures { features },
+                )));
+                Action::render()
             }
+
+            Ok(Some(Action::MarkFeature)) => {
+                let tree = match self.tab {
+                    Tab::Dependencies => &mut self.by_dependency,
+                    _ => &mut self.dependency_tree,
+                };
+                tree.toggle_mark(&self.workspace_info);
+                Action::render()
+            }
+
+            Ok(Some(Action::ToggleFeatures { features })) => self
+                .spawn("toggling features", move |info| {
+                    info.toggle_features(&features)
+                }),
             action => action,
         }
     }
@@ -98,30 +938,162 @@ impl Component for DependencyTab {
         }
     }
 
+    fn update(&mut self, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::Tick => self.poll_task(),
+            _ => Action::none(),
+        }
+    }
+
     fn handle_key_events(&mut self, key_event: event::KeyEvent) -> Result<Option<Action>> {
-        match key_event.code {
-            event::KeyCode::Char('q') => return Action::quit(),
-            event::KeyCode::Char('g') => return self.refresh(),
-            event::KeyCode::Esc => {
-                self.view = View::DependencyTree;
+        if let Some(dialog) = &mut self.dialog {
+            let action = dialog.handle_key_events(key_event);
+            return self.apply_action(action);
+        }
+
+        match keymap().command(&key_event) {
+            Some(Command::Quit) => return Action::quit(),
+            Some(Command::Refresh) => return self.refresh(),
+            Some(Command::Help) => return self.apply_action(Ok(Some(Action::Help))),
+            Some(Command::Back) => {
+                match (self.tab, &self.view) {
+                    (Tab::Packages, View::DependencyTree) => {
+                        self.dependency_tree.clear_marks(&self.workspace_info);
+                    }
+                    (Tab::Dependencies, View::DependencyTree)
+                        if self.by_dependency.marked_count() > 0 =>
+                    {
+                        self.by_dependency.clear_marks(&self.workspace_info);
+                    }
+                    (Tab::Dependencies, View::DependencyTree)
+                    | (Tab::Duplicates | Tab::Policy, _) => self.set_tab(Tab::Packages),
+                    _ => self.view = View::DependencyTree,
+                }
                 return Action::render();
             }
-            _ => {}
+            Some(_) => {}
+            None => {
+                if let event::KeyCode::Char(c @ '1'..='9') = key_event.code {
+                    let index = c as usize - '1' as usize;
+                    if let Some(tab) = Tab::ALL.get(index) {
+                        self.set_tab(*tab);
+                        return Action::render();
+                    }
+                }
+            }
         };
 
-        self.dependency_tree
-            .handle_key_events(key_event)
-            .and_then(|action| self.apply_action(Ok(action)))
+        let action = match (self.tab, &mut self.view) {
+            (Tab::Duplicates, _) => self.duplicates.handle_key_events(key_event),
+            (Tab::Policy, _) => self.policy.handle_key_events(key_event),
+            (Tab::Packages, View::DependencyTree) => {
+                self.dependency_tree.handle_key_events(key_event)
+            }
+            (Tab::Dependencies, View::DependencyTree) => {
+                self.by_dependency.handle_key_events(key_event)
+            }
+            (_, View::InverseTree(view)) => view.handle_key_events(key_event),
+            (_, View::CompileTimes(view)) => view.handle_key_events(key_event),
+            (_, View::DependencyMap(view)) => view.handle_key_events(key_event),
+        };
+        action.and_then(|action| self.apply_action(Ok(action)))
+    }
+
+    fn handle_mouse_events(&mut self, mouse: event::MouseEvent) -> Result<Option<Action>> {
+        let click = mouse.kind == event::MouseEventKind::Down(event::MouseButton::Left);
+        if click && self.dialog.is_none() && mouse.row == self.tab_bar.y {
+            if let Some(tab) = self.tab_at(mouse.column) {
+                self.set_tab(tab);
+                return Action::render();
+            }
+            return Action::none();
+        }
+
+        if self.dialog.is_some()
+            || !self.tab.has_tree()
+            || !matches!(self.view, View::DependencyTree)
+        {
+            return Action::none();
+        }
+
+        if mouse.row == self.area.y {
+            if !click {
+                return Action::none();
+            }
+            let Some(location) = self.tree().location() else {
+                return Action::none();
+            };
+            // the breadcrumbs are centered between the corners of the border
+            let width = location
+                .breadcrumbs()
+                .iter()
+                .map(|span| span.width() as u16)
+                .sum::<u16>();
+            let start =
+                self.area.x + 1 + self.area.width.saturating_sub(2).saturating_sub(width) / 2;
+            let Some(level) = mouse
+                .column
+                .checked_sub(start)
+                .and_then(|x| location.breadcrumb_at(x))
+            else {
+                return Action::none();
+            };
+            self.tree().select_level(level);
+            return Action::render();
+        }
+
+        let action = self.tree().handle_mouse_events(mouse);
+        action.and_then(|action| self.apply_action(Ok(action)))
     }
 
     fn render(&mut self, f: &mut Frame, rect: Rect) {
-        let location = self.dependency_tree.location();
+        let [tab_bar, rect] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(rect);
+        self.render_tab_bar(f, tab_bar);
 
-        let breadcrumbs = location
-            .as_ref()
-            .map(|l| l.breadcrumbs())
-            .unwrap_or_default();
-        let help = location.as_ref().map(|l| l.help()).unwrap_or_default();
+        self.area = rect;
+        let (breadcrumbs, help) = match (self.tab, &self.view) {
+            (Tab::Duplicates, _) => (self.duplicates.breadcrumbs(), self.duplicates.help()),
+            (Tab::Policy, _) => (self.policy.breadcrumbs(), self.policy.help()),
+            (Tab::Packages | Tab::Dependencies, View::DependencyTree) => {
+                let tree = match self.tab {
+                    Tab::Dependencies => &self.by_dependency,
+                    _ => &self.dependency_tree,
+                };
+                let location = tree.location();
+                let breadcrumbs = location
+                    .as_ref()
+                    .map(|l| l.breadcrumbs())
+                    .unwrap_or_default();
+                let mut help = location.as_ref().map(|l| l.help()).unwrap_or_default();
+                let marked = tree.marked_count();
+                if marked > 0 && tree.selected_marked() {
+                    help.splice(
+                        0..0,
+                        [
+                            " ".set_style(theme().dim),
+                            "<enter>".set_style(theme().key),
+                            format!(" toggle {marked} marked ").set_style(theme().dim),
+                            "<esc>".set_style(theme().key),
+                            " unmark ".set_style(theme().dim),
+                        ],
+                    );
+                } else if marked > 0 {
+                    help.splice(
+                        0..0,
+                        [
+                            " ".set_style(theme().dim),
+                            "<esc>".set_style(theme().key),
+                            format!(" unmark {marked} ").set_style(theme().dim),
+                        ],
+                    );
+                }
+                (breadcrumbs, help)
+            }
+            (_, View::InverseTree(view)) => (view.breadcrumbs(), view.help()),
+            (_, View::CompileTimes(view)) => (view.breadcrumbs(), view.help()),
+            (_, View::DependencyMap(view)) => (view.breadcrumbs(), view.help()),
+        };
 
         let block = Block::default()
             .title(Title::from(breadcrumbs).position(Position::Top))
@@ -140,6 +1112,42 @@ impl Component for DependencyTab {
             .margin(1)
             .areas(rect);
 
-        self.dependency_tree.render(f, inner);
+        match (self.tab, &mut self.view) {
+            (Tab::Duplicates, _) => self.duplicates.render(f, inner),
+            (Tab::Policy, _) => self.policy.render(f, inner),
+            (Tab::Packages | Tab::Dependencies, View::DependencyTree) => {
+                let [area, details] = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+                    .areas(inner);
+                let tree = match self.tab {
+                    Tab::Dependencies => &mut self.by_dependency,
+                    _ => &mut self.dependency_tree,
+                };
+                let selected = tree
+                    .selected_nodes()
+                    .into_iter()
+                    .map(|(_, node)| node)
+                    .collect::<Vec<_>>();
+                self.details.update(&self.workspace_info, &selected);
+                tree.render(f, area);
+                self.details.render(f, details);
+            }
+            (_, View::InverseTree(view)) => view.render(f, inner),
+            (_, View::CompileTimes(view)) => view.render(f, inner),
+            (_, View::DependencyMap(view)) => view.render(f, inner),
+        }
+
+        if let Some(dialog) = &mut self.dialog {
+            dialog.render(f, rect);
+        }
     }
 }
+
+/// The workspace member `package` of `info`, for the tasks that look it up in
+/// their copy of the workspace info.
+fn member<'a>(info: &'a WorkspaceInfo, package: &PackageId) -> Result<&'a Package> {
+    info.dependency_resolver()
+        .package(package)
+        .ok_or_else(|| eyre::eyre!("Could not resolve package {package}"))
+}
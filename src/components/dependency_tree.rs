@@ -1,114 +1,176 @@
-use cargo_metadata::PackageId;
-use crossterm::event;
+use cargo_metadata::{DependencyKind, PackageId};
+use crossterm::event::{self, MouseButton, MouseEventKind};
 use eyre::Result;
 use ratatui::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
 use tui_tree_widget::{Tree, TreeItem, TreeState};
 
 use crate::action::Action;
+use crate::cargo::FeatureEdit;
 use crate::component::Component;
+use crate::config::config;
+use crate::keymap::{keymap, Command};
 use crate::metadata::dep_tree::{self, DepTree, DepTreeNode};
-use crate::metadata::workspace_info::WorkspaceInfo;
-
-#[derive(Debug)]
-pub struct Icons {
-    pub enabled: String,
-    pub indirectly_enabled: String,
-    pub disabled: String,
-    pub unknown: String,
-}
+use crate::metadata::workspace_info::{FeatureToggle, WorkspaceInfo};
+use crate::metadata::PackageResolver;
+use crate::theme::theme;
 
-lazy_static::lazy_static! {
-    pub static ref ICONS: Icons =
-        Icons {
-            enabled: "✓".to_string(),
-            indirectly_enabled: "—".to_string(),
-            disabled: " ".to_string(),
-            unknown: "?".to_string(),
-        };
-}
+/// Maximum time between the clicks of a double click.
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// Rows scrolled per mouse wheel step.
+const SCROLL_LINES: usize = 3;
 
 #[derive(Debug, Clone)]
 pub enum Location {
+    /// A dependency at the root of the tree built by dependency.
+    DependencyGroup(String),
     Package(PackageId),
+    PackageFeatures(PackageId),
+    PackageFeature((PackageId, String)),
+    PackageFeatureEntry((PackageId, String, String)),
     Dependency((PackageId, String)),
     Feature((PackageId, String, String)),
+    /// The `default-features` switch of a dependency.
+    DefaultFeatures((PackageId, String)),
+    TransitiveDependency((PackageId, String, Vec<String>)),
 }
 
 impl Location {
     pub fn id(&self) -> String {
         let id = match self {
+            Location::DependencyGroup(name) => return name.clone(),
             Location::Package(id) => id,
+            Location::PackageFeatures(id) => id,
+            Location::PackageFeature((id, _)) => id,
+            Location::PackageFeatureEntry((id, _, _)) => id,
             Location::Dependency((id, _)) => id,
             Location::Feature((id, _, _)) => id,
+            Location::DefaultFeatures((id, _)) => id,
+            Location::TransitiveDependency((id, _, _)) => id,
         };
         id.to_string().replace("path+file://", "")
     }
 
-    pub fn breadcrumbs(&self) -> Vec<Span<'static>> {
+    /// One breadcrumb segment per tree level of the location.
+    fn segments(&self) -> Vec<Span<'static>> {
+        let mut segments = vec![Span::styled(self.id(), theme().emphasis)];
         match self {
-            Location::Package(_) => {
-                vec![
-                    Span::raw(" "),
-                    Span::styled(self.id(), Style::default().bold()),
-                    Span::raw(" "),
-                ]
+            Location::DependencyGroup(_) | Location::Package(_) => {}
+            Location::PackageFeatures(_) => {
+                segments.push(Span::raw("features"));
+            }
+            Location::PackageFeature((_, feature)) => {
+                segments.push(Span::raw("features"));
+                segments.push(Span::styled(feature.clone(), theme().emphasis));
+            }
+            Location::PackageFeatureEntry((_, feature, entry)) => {
+                segments.push(Span::raw("features"));
+                segments.push(Span::styled(feature.clone(), theme().emphasis));
+                segments.push(Span::styled(entry.clone(), theme().emphasis));
             }
             Location::Dependency((_, name)) => {
-                vec![
-                    Span::raw(" "),
-                    Span::styled(self.id(), Style::default().bold()),
-                    Span::raw(" > "),
-                    Span::styled(name.clone(), Style::default().bold()),
-                    Span::raw(" "),
-                ]
+                segments.push(Span::styled(name.clone(), theme().emphasis));
             }
-
             Location::Feature((_, name, feature_name)) => {
-                vec![
-                    Span::raw(" "),
-                    Span::styled(self.id(), Style::default().bold()),
-                    Span::raw(" > "),
-                    Span::styled(name.clone(), Style::default().bold()),
-                    Span::raw(" > "),
-                    Span::styled(feature_name.clone(), Style::default().bold()),
-                    Span::raw(" "),
-                ]
+                segments.push(Span::styled(name.clone(), theme().emphasis));
+                segments.push(Span::styled(feature_name.clone(), theme().emphasis));
+            }
+            Location::DefaultFeatures((_, name)) => {
+                segments.push(Span::styled(name.clone(), theme().emphasis));
+                segments.push(Span::raw("default-features"));
+            }
+            Location::TransitiveDependency((_, name, path)) => {
+                segments.push(Span::styled(name.clone(), theme().emphasis));
+                segments.extend(
+                    path.iter()
+                        .map(|name| Span::styled(name.clone(), theme().emphasis)),
+                );
             }
         }
+        segments
     }
 
-    pub fn help(&self) -> Vec<Span<'static>> {
-        let mut help = Vec::new();
-
-        help.push("r".blue());
-        help.push("efresh".dim());
-        help.push(" ".dim());
-        help.push("q".blue());
-        help.push("uit".dim());
-        help.push(" ".dim());
-
-        match self {
-            Location::Package(_) => {
-                help.insert(0, "raph ".dim());
-                help.insert(0, "g".blue());
-                help.insert(0, " ".dim());
-                help
-            }
-            Location::Dependency((_, _)) => {
-                help.insert(0, " ".dim());
-                help
+    pub fn breadcrumbs(&self) -> Vec<Span<'static>> {
+        let mut spans = vec![Span::raw(" ")];
+        for (i, segment) in self.segments().into_iter().enumerate() {
+            if i > 0 {
+                spans.push(Span::raw(" > "));
             }
+            spans.push(segment);
+        }
+        spans.push(Span::raw(" "));
+        spans
+    }
 
-            Location::Feature((_, _, _)) => {
-                help.insert(0, " ".dim());
-                help.insert(0, "<enter>".blue());
-                help.insert(0, "toggle".dim());
-                help.insert(0, " ".dim());
-                help.insert(0, " ".dim());
-                help
+    /// The tree level of the breadcrumb segment at column `x` of the
+    /// [breadcrumbs](Self::breadcrumbs).
+    pub fn breadcrumb_at(&self, x: u16) -> Option<usize> {
+        // leading space
+        let mut start = 1;
+        for (level, segment) in self.segments().iter().enumerate() {
+            let end = start + segment.width() as u16;
+            if (start..end).contains(&x) {
+                return Some(level);
             }
+            // " > "
+            start = end + 3;
         }
+        None
+    }
+
+    /// The commands that apply here, with their labels. The ones available
+    /// everywhere come last.
+    pub fn commands(&self) -> Vec<(Command, &'static str)> {
+        let mut commands = match self {
+            Location::DependencyGroup(_) => vec![(Command::Toggle, "expand")],
+            Location::Package(_) => vec![(Command::Toggle, "expand"), (Command::Map, "map")],
+            Location::PackageFeatures(_) => vec![(Command::NewFeature, "new feature")],
+            Location::PackageFeature(_) => vec![
+                (Command::NewFeature, "new feature"),
+                (Command::AddEntry, "add entry"),
+                (Command::Remove, "remove"),
+            ],
+            Location::PackageFeatureEntry(_) => vec![
+                (Command::AddEntry, "add entry"),
+                (Command::Remove, "remove"),
+            ],
+            Location::Dependency(_) => vec![
+                (Command::Toggle, "graph"),
+                (Command::Inverse, "inverse"),
+                (Command::Map, "map"),
+                (Command::Remove, "remove"),
+                (Command::Upgrade, "upgrade"),
+                (Command::UpgradeBreaking, "upgrade breaking"),
+                (Command::Optional, "optional"),
+            ],
+            // the default features control can't be marked or applied everywhere
+            Location::DefaultFeatures(_) => vec![(Command::Toggle, "toggle")],
+            Location::Feature(_) => vec![
+                (Command::Toggle, "toggle"),
+                (Command::Mark, "mark"),
+                (Command::Everywhere, "everywhere"),
+            ],
+            Location::TransitiveDependency(_) => vec![
+                (Command::Toggle, "expand"),
+                (Command::Inverse, "inverse"),
+                (Command::Map, "map"),
+            ],
+        };
+        commands.extend([
+            (Command::Add, "add"),
+            (Command::Duplicates, "duplicates"),
+            (Command::CompileTimes, "compile times"),
+            (Command::Help, "help"),
+            (Command::Refresh, "refresh"),
+            (Command::Quit, "quit"),
+        ]);
+        commands
+    }
+
+    pub fn help(&self) -> Vec<Span<'static>> {
+        keymap().help(&self.commands())
     }
 }
 
@@ -118,44 +180,269 @@ pub struct DependencyTree {
     items: Vec<TreeItem<'static, String>>,
 
     tree: dep_tree::DepTree,
-    tree_index: HashMap<String, usize>,
+    /// Identifier of every node among its siblings, by node index.
+    ids: Vec<String>,
+    /// Node indexes by the path of identifiers from the root, the paths the
+    /// tree state selects and opens.
+    tree_index: HashMap<Vec<String>, usize>,
+    /// Features marked with space, by their path in the tree state, to
+    /// toggle them at once. Paths outlive rebuilds, unlike node indexes.
+    marked: Vec<(Vec<String>, FeatureToggle)>,
+    /// Where the tree was rendered last, to map clicks to rows.
+    area: Rect,
+    /// Time and row of the last click, to detect double clicks.
+    last_click: Option<(Instant, usize)>,
+    /// Whether the tree is rooted at the dependencies instead of the
+    /// workspace members, see [`DepTree::build_by_dependency`].
+    by_dependency: bool,
 }
 
 impl DependencyTree {
     pub fn new(d: &WorkspaceInfo) -> Result<Self> {
         let mut me = Self::default();
-        me.update(d);
+        me.update(d, d.tree(&config().filter));
         Ok(me)
     }
 
-    pub fn update(&mut self, info: &WorkspaceInfo) {
-        let (items, indexed_items, tree) = Self::tree_items(info);
+    /// A tree with a root per dependency and the members that use it below.
+    pub fn by_dependency(d: &WorkspaceInfo) -> Result<Self> {
+        let mut me = Self {
+            by_dependency: true,
+            ..Self::default()
+        };
+        me.update(d, d.tree_by_dependency(&config().filter));
+        Ok(me)
+    }
+
+    /// Shows `tree`, which was built from `info` like [`DependencyTree::new`]
+    /// or [`DependencyTree::by_dependency`] do, e.g. on a background thread.
+    pub fn update(&mut self, info: &WorkspaceInfo, tree: DepTree) {
+        self.rebuild(info, tree);
+        self.expand_opened(info);
+        if self.by_dependency {
+            self.open_members();
+        }
+        // drop the marks of features that are gone, e.g. with their dependency
+        self.marked
+            .retain(|(path, _)| self.tree_index.contains_key(path));
+
+        // a removed node leaves its closest ancestor selected
+        let selected = self.tree_state.selected();
+        let valid = (0..=selected.len())
+            .rev()
+            .find(|len| *len == 0 || self.tree_index.contains_key(&selected[..*len]))
+            .unwrap_or(0);
+        if valid < selected.len() {
+            self.tree_state.select(selected[..valid].to_vec());
+        }
+    }
+
+    /// Loads the dependencies of the transitive dependencies that are open
+    /// in the tree state, e.g. the ones that were open before a refresh.
+    fn expand_opened(&mut self, info: &WorkspaceInfo) {
+        let resolver = info.dependency_resolver();
+        loop {
+            let pending = self
+                .tree_state
+                .get_all_opened()
+                .iter()
+                .filter_map(|path| self.tree_index.get(path).copied())
+                .filter(|i| {
+                    matches!(
+                        self.tree.items[*i],
+                        DepTreeNode::TransitiveDependency {
+                            expansion: dep_tree::Expansion::Pending,
+                            ..
+                        }
+                    )
+                })
+                .collect::<Vec<_>>();
+            if pending.is_empty() {
+                break;
+            }
+            // the loaded children may be open as well
+            let mut tree = std::mem::take(&mut self.tree);
+            for i in pending {
+                tree.expand(i, &resolver);
+            }
+            self.rebuild(info, tree);
+        }
+    }
+
+    /// Opens the members below every dependency group and their
+    /// dependencies, so opening a group shows the features of all members.
+    fn open_members(&mut self) {
+        let children = |i: usize| self.tree.items[i].children().cloned().unwrap_or_default();
+        for group in &self.tree.children {
+            for member in children(*group) {
+                let path = vec![self.ids[*group].clone(), self.ids[member].clone()];
+                for dep in children(member) {
+                    let mut path = path.clone();
+                    path.push(self.ids[dep].clone());
+                    self.tree_state.open(path);
+                }
+                self.tree_state.open(path);
+            }
+        }
+    }
+
+    pub fn clear_marks(&mut self, info: &WorkspaceInfo) {
+        if !self.marked.is_empty() {
+            self.marked.clear();
+            let tree = std::mem::take(&mut self.tree);
+            self.rebuild(info, tree);
+        }
+    }
+
+    pub fn marked_count(&self) -> usize {
+        self.marked.len()
+    }
+
+    /// Whether the selected node is a marked feature, then toggling it
+    /// toggles all marked features.
+    pub fn selected_marked(&self) -> bool {
+        let selected = self.tree_state.selected();
+        self.marked.iter().any(|(path, _)| *path == selected)
+    }
+
+    /// Marks the selected feature, or unmarks it if it is marked already.
+    pub fn toggle_mark(&mut self, info: &WorkspaceInfo) {
+        let toggle = match &self.selected_nodes()[..] {
+            [(_, DepTreeNode::WorkspacePackage { id, .. }), (_, DepTreeNode::Dependency { name, kind, .. }), (_, DepTreeNode::Feature { name: feature, .. })] => {
+                (
+                    self.tree_state.selected(),
+                    FeatureToggle {
+                        package: id.clone(),
+                        dep_name: name.clone(),
+                        dep_kind: *kind,
+                        feature: feature.clone(),
+                    },
+                )
+            }
+            _ => return,
+        };
+        match self.marked.iter().position(|(path, _)| *path == toggle.0) {
+            Some(pos) => {
+                self.marked.remove(pos);
+            }
+            None => self.marked.push(toggle),
+        }
+        let tree = std::mem::take(&mut self.tree);
+        self.rebuild(info, tree);
+    }
+
+    /// Loads the transitive dependencies of the node at `node` and re-renders
+    /// the tree items. Indexes of existing nodes don't change, so the tree
+    /// state stays valid.
+    pub fn expand(&mut self, node: usize, info: &WorkspaceInfo) {
+        let mut tree = std::mem::take(&mut self.tree);
+        if tree.expand(node, &info.dependency_resolver()) {
+            self.tree_state.open(self.tree_state.selected());
+        }
+        self.rebuild(info, tree);
+    }
+
+    fn rebuild(&mut self, info: &WorkspaceInfo, tree: DepTree) {
+        let (ids, tree_index) = Self::identifiers(&tree);
+        let marked = self
+            .marked
+            .iter()
+            .filter_map(|(path, _)| tree_index.get(path).copied())
+            .collect();
+        let resolver = info.dependency_resolver();
+        let items = Self::tree_items(info, &resolver, &tree, &ids, &marked);
         info!(
             "updated dependency tree with {} items ({} root nodes)",
-            indexed_items.len(),
+            tree_index.len(),
             items.len()
         );
         self.items = items;
-        self.tree_index = indexed_items;
+        self.ids = ids;
+        self.tree_index = tree_index;
         self.tree = tree;
     }
 
+    /// The identifiers of the nodes and the index of their paths. Siblings
+    /// with the same [widget id](DepTreeNode::widget_id), like an entry
+    /// listed twice in a feature, get a counter appended.
+    fn identifiers(tree: &DepTree) -> (Vec<String>, HashMap<Vec<String>, usize>) {
+        let mut ids = vec![String::new(); tree.items.len()];
+        let mut index = HashMap::new();
+        let mut stack = vec![(Vec::new(), &tree.children)];
+        while let Some((path, children)) = stack.pop() {
+            let mut seen = HashMap::<String, usize>::new();
+            for &i in children {
+                let node = &tree.items[i];
+                let mut id = node.widget_id();
+                let count = seen.entry(id.clone()).or_default();
+                *count += 1;
+                if *count > 1 {
+                    id = format!("{id}#{count}");
+                }
+                ids[i] = id.clone();
+
+                let mut path = path.clone();
+                path.push(id);
+                if let Some(children) = node.children() {
+                    stack.push((path.clone(), children));
+                }
+                index.insert(path, i);
+            }
+        }
+        (ids, index)
+    }
+
+    /// The path of the selected node, starting at the workspace member. In
+    /// the tree by dependency the [`DepTreeNode::DependencyGroup`] above the
+    /// member is left out, so a member's dependency is the same path in both
+    /// trees. The path is empty if a group is selected.
+    pub fn selected_nodes(&self) -> Vec<(usize, &DepTreeNode)> {
+        let mut nodes = self.selected_path();
+        if let Some((_, DepTreeNode::DependencyGroup { .. })) = nodes.first() {
+            nodes.remove(0);
+        }
+        nodes
+    }
+
+    fn selected_path(&self) -> Vec<(usize, &DepTreeNode)> {
+        let selected = self.tree_state.selected();
+        (1..=selected.len())
+            .filter_map(|len| {
+                self.tree_index
+                    .get(&selected[..len])
+                    .and_then(|i| self.tree.items.get(*i).map(|node| (*i, node)))
+            })
+            .collect()
+    }
+
     pub fn location(&self) -> Option<Location> {
         use DepTreeNode::*;
 
         let selected = self
-            .tree_state
-            .selected()
+            .selected_nodes()
             .into_iter()
-            .filter_map(|s| {
-                self.tree_index
-                    .get(&s)
-                    .and_then(|i| self.tree.items.get(*i))
-            })
+            .map(|(_, node)| node)
             .collect::<Vec<_>>();
 
+        if let [(_, DependencyGroup { name, .. })] = &self.selected_path()[..] {
+            return Some(Location::DependencyGroup(name.clone()));
+        }
+
         match &selected[..] {
             [WorkspacePackage { id, .. }] => Some(Location::Package(id.clone())),
+            [WorkspacePackage { id, .. }, PackageFeatures { .. }] => {
+                Some(Location::PackageFeatures(id.clone()))
+            }
+            [WorkspacePackage { id, .. }, PackageFeatures { .. }, PackageFeature { name, .. }] => {
+                Some(Location::PackageFeature((id.clone(), name.clone())))
+            }
+            [WorkspacePackage { id, .. }, PackageFeatures { .. }, PackageFeature { name, .. }, PackageFeatureEntry { value }] => {
+                Some(Location::PackageFeatureEntry((
+                    id.clone(),
+                    name.clone(),
+                    value.clone(),
+                )))
+            }
             [WorkspacePackage { id, .. }, Dependency { name, .. }] => {
                 Some(Location::Dependency((id.clone(), name.clone())))
             }
@@ -166,36 +453,121 @@ impl DependencyTree {
                 name.clone(),
                 feature_name.clone(),
             ))),
+            [WorkspacePackage { id, .. }, Dependency { name, .. }, DefaultFeatures { .. }] => {
+                Some(Location::DefaultFeatures((id.clone(), name.clone())))
+            }
+            [WorkspacePackage { id, .. }, Dependency { name, .. }, rest @ ..]
+                if !rest.is_empty()
+                    && rest
+                        .iter()
+                        .all(|n| matches!(n, TransitiveDependency { .. })) =>
+            {
+                let path = rest
+                    .iter()
+                    .filter_map(|n| match n {
+                        TransitiveDependency { name, .. } => Some(name.clone()),
+                        _ => None,
+                    })
+                    .collect();
+                Some(Location::TransitiveDependency((
+                    id.clone(),
+                    name.clone(),
+                    path,
+                )))
+            }
             _ => None,
         }
     }
 
+    /// The selected node if it is a transitive dependency whose own
+    /// dependencies are not loaded yet.
+    fn selected_pending_dependency(&self) -> Option<usize> {
+        match self.selected_nodes().last() {
+            Some((
+                i,
+                DepTreeNode::TransitiveDependency {
+                    expansion: dep_tree::Expansion::Pending,
+                    ..
+                },
+            )) => Some(*i),
+            _ => None,
+        }
+    }
+
+    /// The compile time of a package if timings are loaded.
+    fn compile_time(
+        info: &WorkspaceInfo,
+        resolver: &PackageResolver,
+        id: &PackageId,
+    ) -> Option<Span<'static>> {
+        let timings = info.timings.as_ref()?;
+        let package = resolver.package(id)?;
+        let seconds = timings.seconds(package)?;
+        Some(Span::styled(format!(" {seconds:.2}s"), theme().dim))
+    }
+
     fn tree_items(
         workspace_info: &WorkspaceInfo,
-    ) -> (
-        Vec<TreeItem<'static, String>>,
-        HashMap<String, usize>,
-        DepTree,
-    ) {
-        let mut index = HashMap::new();
-        let tree = workspace_info.tree();
-        let items = tree.visit_post_order(&mut |node, i, children| {
+        resolver: &PackageResolver,
+        tree: &DepTree,
+        ids: &[String],
+        marked: &HashSet<usize>,
+    ) -> Vec<TreeItem<'static, String>> {
+        let workspace_packages = workspace_info.workspace_packages();
+        tree.visit_post_order(&mut |node, i, children| {
             use DepTreeNode::*;
 
             match (node, children) {
                 (WorkspacePackage { id, .. }, Some(children)) => {
-                    let key = node.widget_id();
-                    index.insert(key.clone(), i);
-                    let workspace_packages = &workspace_info.workspace_packages();
+                    let key = ids[i].clone();
                     let p = workspace_packages.iter().find(|&&p| &p.id == id).unwrap();
-                    let span = Span::styled(p.name.clone(), Style::default().white().bold());
-                    TreeItem::new(id.to_string(), span, children).expect("tree failed")
+                    let span = Span::styled(p.name.clone(), theme().package);
+                    TreeItem::new(key, span, children).expect("tree failed")
+                }
+
+                (DependencyGroup { name, .. }, Some(children)) => {
+                    let key = ids[i].clone();
+                    let members = match children.len() {
+                        1 => " (1 member)".to_string(),
+                        count => format!(" ({count} members)"),
+                    };
+                    let label = Line::from(vec![
+                        Span::styled(name.clone(), theme().dependency.bold()),
+                        Span::styled(members, theme().dim),
+                    ]);
+                    TreeItem::new(key, label, children).expect("tree failed")
+                }
+
+                (PackageFeatures { .. }, Some(children)) => {
+                    let key = ids[i].clone();
+                    let label = Line::from(vec![
+                        Span::styled("features", theme().package_feature),
+                        Span::styled(format!(" ({})", children.len()), theme().dim),
+                    ]);
+                    TreeItem::new(key, label, children).expect("tree failed")
                 }
 
-                (UnresolvedDependency { name, kind }, None) => {
-                    let key = format!("{i}:{}", node.widget_id());
-                    index.insert(key.clone(), i);
-                    let icon = &ICONS.unknown;
+                (PackageFeature { name, .. }, Some(children)) => {
+                    let key = ids[i].clone();
+                    let label = Span::styled(name.clone(), theme().package_feature.bold());
+                    TreeItem::new(key, label, children).expect("tree failed")
+                }
+
+                (PackageFeatureEntry { value }, None) => {
+                    let key = ids[i].clone();
+                    let style = if value.starts_with("dep:") {
+                        theme().dep_entry
+                    } else if value.contains('/') {
+                        theme().dep_feature_entry
+                    } else {
+                        theme().package_feature
+                    };
+                    TreeItem::new_leaf(key, Span::styled(value.clone(), style))
+                }
+
+                (UnresolvedDependency { name, kind, .. }, None) => {
+                    let key = ids[i].clone();
+                    let icon = theme().icons.unknown;
                     let label = match kind {
                         cargo_metadata::DependencyKind::Normal => format!("{icon} {name}"),
                         _ => format!("{icon} {name} ({kind})"),
@@ -203,34 +575,106 @@ impl DependencyTree {
                     TreeItem::new_leaf(key, label)
                 }
 
-                (Dependency { name, kind, .. }, Some(children)) => {
-                    let key = format!("{i}:{}", node.widget_id());
-                    index.insert(key.clone(), i);
+                (
+                    Dependency {
+                        id,
+                        name,
+                        kind,
+                        target,
+                        req,
+                        upgrade,
+                        ..
+                    },
+                    Some(children),
+                ) => {
+                    let key = ids[i].clone();
                     let label = match kind {
                         cargo_metadata::DependencyKind::Normal => name.clone(),
                         _ => format!("{name} ({kind})"),
                     };
-                    let span = Span::styled(label, Style::default().white());
-                    TreeItem::new(key, span, children).expect("tree failed")
+                    let mut spans = vec![Span::styled(label, theme().dependency)];
+                    if let Some(target) = target {
+                        spans.push(Span::styled(format!(" [{target}]"), theme().dim));
+                    }
+                    spans.push(Span::styled(format!(" {req}"), theme().dim));
+                    if let Some(package) = resolver.package(id) {
+                        spans.push(Span::styled(format!(" v{}", package.version), theme().dim));
+                    }
+                    if let Some(version) = &upgrade.compatible {
+                        spans.push(Span::styled(
+                            format!(" {}{version}", theme().icons.upgrade),
+                            theme().upgrade_compatible,
+                        ));
+                    }
+                    if let Some(version) = &upgrade.incompatible {
+                        spans.push(Span::styled(
+                            format!(" {}{version}", theme().icons.upgrade),
+                            theme().upgrade_incompatible,
+                        ));
+                    }
+                    spans.extend(Self::compile_time(workspace_info, resolver, id));
+                    TreeItem::new(key, Line::from(spans), children).expect("tree failed")
+                }
+
+                (
+                    TransitiveDependency {
+                        id,
+                        name,
+                        version,
+                        kind,
+                        expansion,
+                        repeated,
+                        ..
+                    },
+                    Some(mut children),
+                ) => {
+                    let key = ids[i].clone();
+                    let mut label = format!("{name} v{version}");
+                    if !matches!(kind, cargo_metadata::DependencyKind::Normal) {
+                        label.push_str(&format!(" ({kind})"));
+                    }
+                    if *repeated {
+                        label.push_str(" (*)");
+                    }
+                    if *expansion == dep_tree::Expansion::Pending && children.is_empty() {
+                        // Placeholder so that the node can be opened, the
+                        // actual dependencies are loaded on expand.
+                        let pending = theme().icons.pending;
+                        children.push(TreeItem::new_leaf(
+                            pending.to_string(),
+                            Span::styled(pending, theme().dim),
+                        ));
+                    }
+                    let mut spans = vec![Span::styled(label, theme().transitive_dependency)];
+                    spans.extend(Self::compile_time(workspace_info, resolver, id));
+                    TreeItem::new(key, Line::from(spans), children).expect("tree failed")
                 }
 
-                (Feature { name, status, deps }, None) => {
+                (
+                    Feature {
+                        name,
+                        status,
+                        deps,
+                        usage,
+                    },
+                    None,
+                ) => {
                     use dep_tree::FeatureStatus::*;
 
                     let mut spans = Vec::new();
 
                     match status {
                         Enabled => spans.push(Span::styled(
-                            format!("{} {name}", ICONS.enabled),
-                            Style::default().green().bold(),
+                            format!("{} {name}", theme().icons.enabled),
+                            theme().feature_enabled,
                         )),
                         IndirectlyEnabled => spans.push(Span::styled(
-                            format!("{} {name}", ICONS.indirectly_enabled),
-                            Style::default().green(),
+                            format!("{} {name}", theme().icons.indirectly_enabled),
+                            theme().feature_indirectly_enabled,
                         )),
                         Disabled => spans.push(Span::styled(
-                            format!("{} {name}", ICONS.disabled),
-                            Style::default().white(),
+                            format!("{} {name}", theme().icons.disabled),
+                            theme().feature_disabled,
                         )),
                     }
 
@@ -238,24 +682,50 @@ impl DependencyTree {
                         spans.push(Span::raw(format!(" ({})", deps.join(", "))));
                     }
 
+                    if usage.is_possibly_unused() {
+                        spans.push(Span::styled(" possibly unused", theme().warning));
+                    }
+
+                    if marked.contains(&i) {
+                        spans.push(Span::styled(
+                            format!(" {}", theme().icons.marked),
+                            theme().warning.bold(),
+                        ));
+                    }
+
                     let text = Text::from(Line::from(spans));
-                    let key = format!("{i}:{}", node.widget_id());
-                    index.insert(key.clone(), i);
+                    let key = ids[i].clone();
                     TreeItem::new_leaf(key, text)
                 }
 
+                (DefaultFeatures { enabled, features }, None) => {
+                    let mut spans = vec![if *enabled {
+                        Span::styled(
+                            format!("{} default-features", theme().icons.enabled),
+                            theme().default_features.bold(),
+                        )
+                    } else {
+                        Span::styled(
+                            format!("{} default-features = false", theme().icons.disabled),
+                            theme().default_features,
+                        )
+                    }];
+                    if !features.is_empty() {
+                        spans.push(Span::raw(format!(" ({})", features.join(", "))));
+                    }
+                    let key = ids[i].clone();
+                    TreeItem::new_leaf(key, Line::from(spans))
+                }
+
                 _ => unreachable!(),
             }
-        });
-
-        (items, index, tree)
+        })
     }
 }
 
 impl Component for DependencyTree {
     fn handle_key_events(&mut self, key_event: event::KeyEvent) -> Result<Option<Action>> {
         match key_event.code {
-            event::KeyCode::Char('q') => Ok(Some(Action::Quit)),
             event::KeyCode::Up => {
                 self.tree_state.key_up(&self.items);
                 Ok(Some(Action::Render))
@@ -266,29 +736,264 @@ impl Component for DependencyTree {
             }
             event::KeyCode::Right => {
                 self.tree_state.key_right();
-                // self.tree_state.selected()
+                if let Some(node) = self.selected_pending_dependency() {
+                    return Ok(Some(Action::ExpandDependency { node }));
+                }
                 Ok(Some(Action::Render))
             }
             event::KeyCode::Left => {
                 self.tree_state.key_left();
                 Ok(Some(Action::Render))
             }
+            _ => self.handle_command(keymap().command(&key_event)),
+        }
+    }
 
-            event::KeyCode::Enter => {
+    fn handle_mouse_events(&mut self, mouse: event::MouseEvent) -> Result<Option<Action>> {
+        match mouse.kind {
+            MouseEventKind::ScrollDown => {
+                self.tree_state.scroll_down(SCROLL_LINES);
+                Ok(Some(Action::Render))
+            }
+            MouseEventKind::ScrollUp => {
+                self.tree_state.scroll_up(SCROLL_LINES);
+                Ok(Some(Action::Render))
+            }
+            MouseEventKind::Down(MouseButton::Left) => self.click(mouse.column, mouse.row),
+            _ => Ok(None),
+        }
+    }
+
+    fn render(&mut self, f: &mut Frame, rect: Rect) {
+        self.area = rect;
+        let tree = Tree::new(self.items.clone())
+            .expect("tree failed")
+            .highlight_style(theme().highlight)
+            .node_closed_symbol(theme().icons.node_closed)
+            .node_open_symbol(theme().icons.node_open);
+
+        f.render_stateful_widget(tree, rect, &mut self.tree_state);
+    }
+}
+
+impl DependencyTree {
+    /// Selects the clicked row. Clicking the arrow of a node opens or closes
+    /// it, a double click does what the toggle key does.
+    fn click(&mut self, column: u16, row: u16) -> Result<Option<Action>> {
+        let area = self.area;
+        if !(area.left()..area.right()).contains(&column)
+            || !(area.top()..area.bottom()).contains(&row)
+        {
+            return Ok(None);
+        }
+        let index = self.tree_state.get_offset() + (row - area.y) as usize;
+        let flattened = self.tree_state.flatten(&self.items);
+        let Some(clicked) = flattened.get(index) else {
+            return Ok(None);
+        };
+        let identifier = clicked.identifier.clone();
+        let arrow = clicked.depth() as u16 * 2;
+        let on_arrow =
+            !clicked.item.children().is_empty() && (arrow..arrow + 2).contains(&(column - area.x));
+
+        let double_click = self
+            .last_click
+            .is_some_and(|(time, row)| row == index && time.elapsed() < DOUBLE_CLICK);
+        self.last_click = (!double_click).then(|| (Instant::now(), index));
+
+        self.tree_state.select(identifier.clone());
+        if on_arrow {
+            self.tree_state.toggle(identifier);
+            if let Some(node) = self.selected_pending_dependency() {
+                return Ok(Some(Action::ExpandDependency { node }));
+            }
+        } else if double_click {
+            return self.handle_command(Some(Command::Toggle));
+        }
+        Ok(Some(Action::Render))
+    }
+
+    /// Selects the ancestor of the selected node at breadcrumb `level`, 0
+    /// being the workspace package.
+    pub fn select_level(&mut self, level: usize) {
+        let mut selected = self.tree_state.selected();
+        // the breadcrumbs below a dependency group start at the member
+        let offset = usize::from(self.by_dependency && selected.len() > 1);
+        selected.truncate(level + 1 + offset);
+        self.tree_state.select(selected);
+    }
+
+    fn handle_command(&mut self, command: Option<Command>) -> Result<Option<Action>> {
+        let Some(command) = command else {
+            return Ok(None);
+        };
+        match command {
+            Command::Quit => Ok(Some(Action::Quit)),
+            Command::Duplicates => Ok(Some(Action::ShowDuplicates)),
+            Command::CompileTimes => Ok(Some(Action::ShowCompileTimes)),
+
+            Command::Add => match self.selected_nodes().first() {
+                Some((_, DepTreeNode::WorkspacePackage { id, .. })) => {
+                    Ok(Some(Action::ShowAddDependency {
+                        package: id.clone(),
+                    }))
+                }
+                _ => Ok(None),
+            },
+
+            Command::Map => match self.selected_nodes().last() {
+                Some((_, DepTreeNode::WorkspacePackage { id, .. }))
+                | Some((_, DepTreeNode::Dependency { id, .. }))
+                | Some((_, DepTreeNode::TransitiveDependency { id, .. })) => {
+                    Ok(Some(Action::ShowDependencyMap {
+                        package: id.clone(),
+                    }))
+                }
+                _ => Ok(None),
+            },
+
+            Command::Upgrade | Command::UpgradeBreaking => match &self.selected_nodes()[..] {
+                [(_, DepTreeNode::WorkspacePackage { id, .. }), (
+                    _,
+                    DepTreeNode::Dependency {
+                        name,
+                        kind,
+                        upgrade,
+                        ..
+                    },
+                )] => {
+                    // `U` allows breaking changes
+                    let version = if command == Command::UpgradeBreaking {
+                        upgrade.newest()
+                    } else {
+                        upgrade.compatible.as_ref()
+                    };
+                    Ok(version.map(|version| Action::BumpDependency {
+                        package: id.clone(),
+                        dep_name: name.clone(),
+                        dep_kind: *kind,
+                        version: version.clone(),
+                    }))
+                }
+                _ => Ok(None),
+            },
+
+            Command::Optional => match &self.selected_nodes()[..] {
+                [(_, DepTreeNode::WorkspacePackage { id, .. }), (_, DepTreeNode::Dependency { name, kind, .. })]
+                | [(_, DepTreeNode::WorkspacePackage { id, .. }), (_, DepTreeNode::UnresolvedDependency { name, kind, .. })]
+                    if *kind != DependencyKind::Development =>
+                {
+                    Ok(Some(Action::ShowToggleOptional {
+                        package: id.clone(),
+                        dep_name: name.clone(),
+                        dep_kind: *kind,
+                    }))
+                }
+                _ => Ok(None),
+            },
+
+            Command::NewFeature => match self.selected_nodes()[..] {
+                [(_, DepTreeNode::WorkspacePackage { id, .. }), (_, DepTreeNode::PackageFeatures { .. }), ..] => {
+                    Ok(Some(Action::ShowFeatureInput {
+                        package: id.clone(),
+                        feature: None,
+                    }))
+                }
+                _ => Ok(None),
+            },
+
+            Command::AddEntry => match self.selected_nodes()[..] {
+                [(_, DepTreeNode::WorkspacePackage { id, .. }), (_, DepTreeNode::PackageFeatures { .. }), (_, DepTreeNode::PackageFeature { name, .. }), ..] => {
+                    Ok(Some(Action::ShowFeatureInput {
+                        package: id.clone(),
+                        feature: Some(name.clone()),
+                    }))
+                }
+                _ => Ok(None),
+            },
+
+            Command::Remove => match &self.selected_nodes()[..] {
+                [(_, DepTreeNode::WorkspacePackage { id, .. }), (_, DepTreeNode::Dependency { name, kind, .. })]
+                | [(_, DepTreeNode::WorkspacePackage { id, .. }), (_, DepTreeNode::UnresolvedDependency { name, kind, .. })] => {
+                    Ok(Some(Action::ShowRemoveDependency {
+                        package: id.clone(),
+                        dep_name: name.clone(),
+                        dep_kind: *kind,
+                    }))
+                }
+                [(_, DepTreeNode::WorkspacePackage { id, .. }), (_, DepTreeNode::PackageFeatures { .. }), (_, DepTreeNode::PackageFeature { name, .. })] => {
+                    Ok(Some(Action::ShowRemoveFeature {
+                        package: id.clone(),
+                        feature: name.clone(),
+                    }))
+                }
+                [(_, DepTreeNode::WorkspacePackage { id, .. }), (_, DepTreeNode::PackageFeatures { .. }), (_, DepTreeNode::PackageFeature { name, .. }), (_, DepTreeNode::PackageFeatureEntry { value })] => {
+                    Ok(Some(Action::EditFeatures {
+                        package: id.clone(),
+                        edit: FeatureEdit::RemoveEntry {
+                            feature: name.clone(),
+                            entry: value.clone(),
+                        },
+                    }))
+                }
+                _ => Ok(None),
+            },
+
+            Command::Everywhere => match &self.selected_nodes()[..] {
+                [(_, DepTreeNode::WorkspacePackage { id, .. }), (_, DepTreeNode::Dependency { name, kind, .. }), (_, DepTreeNode::Feature { name: feature, .. })] => {
+                    Ok(Some(Action::ShowApplyEverywhere {
+                        package: id.clone(),
+                        dep_name: name.clone(),
+                        dep_kind: *kind,
+                        feature: feature.clone(),
+                    }))
+                }
+                _ => Ok(None),
+            },
+
+            Command::Inverse => match self.selected_nodes().last() {
+                Some((_, DepTreeNode::Dependency { id, .. }))
+                | Some((_, DepTreeNode::TransitiveDependency { id, .. })) => {
+                    Ok(Some(Action::ShowInverseTree {
+                        package: id.clone(),
+                    }))
+                }
+                _ => Ok(None),
+            },
+
+            Command::Mark => match self.selected_nodes().last() {
+                Some((_, DepTreeNode::Feature { .. })) => Ok(Some(Action::MarkFeature)),
+                _ => Ok(None),
+            },
+
+            // the marks are applied, the tree shows the result once reloaded
+            Command::Toggle if self.selected_marked() => Ok(Some(Action::ToggleFeatures {
+                features: std::mem::take(&mut self.marked)
+                    .into_iter()
+                    .map(|(_, t)| t)
+                    .collect(),
+            })),
+
+            Command::Toggle => {
                 use DepTreeNode::*;
 
+                if let Some(node) = self.selected_pending_dependency() {
+                    return Ok(Some(Action::ExpandDependency { node }));
+                }
+
                 let selected = self
-                    .tree_state
-                    .selected()
+                    .selected_nodes()
                     .into_iter()
-                    .filter_map(|s| {
-                        self.tree_index
-                            .get(&s)
-                            .and_then(|i| self.tree.items.get(*i))
-                    })
+                    .map(|(_, node)| node)
                     .collect::<Vec<_>>();
 
                 match &selected[..] {
+                    // a dependency group
+                    [] => {
+                        self.tree_state.toggle_selected();
+                        Ok(Some(Action::Render))
+                    }
+
                     [WorkspacePackage { id, .. }, Dependency { name, .. }] => {
                         Ok(Some(Action::ShowFeatureTree {
                             parent_package: id.clone(),
@@ -308,18 +1013,46 @@ impl Component for DependencyTree {
                         feature_status: status.clone(),
                     })),
 
-                    _ => todo!(),
+                    [WorkspacePackage { id, .. }, Dependency { name, kind, .. }, DefaultFeatures { enabled, .. }] => {
+                        if *enabled {
+                            Ok(Some(Action::ShowDisableDefaultFeatures {
+                                package: id.clone(),
+                                dep_name: name.clone(),
+                                dep_kind: *kind,
+                            }))
+                        } else {
+                            Ok(Some(Action::SetDefaultFeatures {
+                                package: id.clone(),
+                                dep_name: name.clone(),
+                                dep_kind: *kind,
+                                enabled: true,
+                                features: Vec::new(),
+                            }))
+                        }
+                    }
+
+                    [WorkspacePackage { .. }]
+                    | [WorkspacePackage { .. }, PackageFeatures { .. }]
+                    | [WorkspacePackage { .. }, PackageFeatures { .. }, PackageFeature { .. }] => {
+                        self.tree_state.toggle_selected();
+                        Ok(Some(Action::Render))
+                    }
+
+                    [WorkspacePackage { .. }, PackageFeatures { .. }, PackageFeature { .. }, PackageFeatureEntry { .. }] => {
+                        Ok(None)
+                    }
+
+                    [WorkspacePackage { .. }, Dependency { .. }, .., TransitiveDependency { .. }] =>
+                    {
+                        self.tree_state.toggle_selected();
+                        Ok(Some(Action::Render))
+                    }
+
+                    // e.g. an unresolved (disabled optional) dependency
+                    _ => Ok(None),
                 }
             }
             _ => Ok(None),
         }
     }
-
-    fn render(&mut self, f: &mut Frame, rect: Rect) {
-        let tree = Tree::new(self.items.clone())
-            .expect("tree failed")
-            .highlight_style(Style::default().on_dark_gray());
-
-        f.render_stateful_widget(tree, rect, &mut self.tree_state);
-    }
 }
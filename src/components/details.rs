@@ -0,0 +1,335 @@
+use cargo_metadata::{DependencyKind, PackageId};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+
+use crate::component::Component;
+use crate::metadata::dep_tree::{DepTreeNode, FeatureStatus};
+use crate::metadata::feature_docs::FeatureDocs;
+use crate::metadata::feature_resolver::{FeatureResolver, RemovedFeature};
+use crate::metadata::unused_features::FeatureUsage;
+use crate::metadata::workspace_info::WorkspaceInfo;
+
+/// Shows information about the selected node of the dependency tree.
+#[derive(Debug, Default)]
+pub struct Details {
+    /// Identifies the selection the lines were computed for.
+    key: Option<String>,
+    lines: Vec<Line<'static>>,
+}
+
+impl Details {
+    /// Forget the computed details, e.g. after the workspace changed.
+    pub fn clear(&mut self) {
+        self.key = None;
+        self.lines.clear();
+    }
+
+    pub fn update(&mut self, info: &WorkspaceInfo, selected: &[&DepTreeNode]) {
+        let key = selected
+            .iter()
+            .map(|node| node.widget_id())
+            .collect::<Vec<_>>()
+            .join("/");
+        if self.key.as_ref() == Some(&key) {
+            return;
+        }
+        self.key = Some(key);
+        self.lines = Self::lines(info, selected);
+    }
+
+    /// The key of a dependency in the manifest of `member`, its rename if it
+    /// has one. The feature resolver identifies dependencies by it.
+    fn manifest_key<'a>(
+        info: &'a WorkspaceInfo,
+        member: &PackageId,
+        dep_name: &'a str,
+        kind: DependencyKind,
+    ) -> &'a str {
+        info.metadata
+            .packages
+            .iter()
+            .find(|p| &p.id == member)
+            .and_then(|p| {
+                p.dependencies
+                    .iter()
+                    .find(|dep| dep.name == dep_name && dep.kind == kind)
+            })
+            .and_then(|dep| dep.rename.as_deref())
+            .unwrap_or(dep_name)
+    }
+
+    fn lines(info: &WorkspaceInfo, selected: &[&DepTreeNode]) -> Vec<Line<'static>> {
+        use DepTreeNode::*;
+
+        let resolver = info.dependency_resolver();
+        let mut lines = Vec::new();
+
+        match selected {
+            [WorkspacePackage { id, .. }] => {
+                if let Some(package) = resolver.package(id) {
+                    lines.push(Line::from(vec![
+                        Span::styled(package.name.clone(), Style::default().bold()),
+                        Span::raw(format!(" v{}", package.version)),
+                    ]));
+                    lines.push(Line::from(package.manifest_path.to_string().dim()));
+                }
+            }
+
+            [WorkspacePackage { id, .. }, Dependency {
+                name,
+                kind,
+                upgrade,
+                ..
+            }] => {
+                let Some(dep) = resolver.package(id).and_then(|p| {
+                    p.dependencies
+                        .iter()
+                        .find(|d| &d.name == name && d.kind == *kind)
+                }) else {
+                    return lines;
+                };
+                let resolved = resolver.resolve_dependency(id, name);
+                lines.push(Line::from(vec![
+                    Span::styled(name.clone(), Style::default().bold()),
+                    Span::raw(format!(" {}", dep.req)),
+                ]));
+                if let Some(resolved) = resolved {
+                    lines.push(Line::from(format!("resolved to v{}", resolved.version)));
+                    if let Some(version) = &upgrade.compatible {
+                        lines.push(Line::from(vec![
+                            Span::raw("compatible upgrade: "),
+                            format!("v{version}").green(),
+                            " (u)".dim(),
+                        ]));
+                    }
+                    if let Some(version) = &upgrade.incompatible {
+                        lines.push(Line::from(vec![
+                            Span::raw("breaking upgrade: "),
+                            format!("v{version}").red(),
+                            " (U)".dim(),
+                        ]));
+                    }
+                    let seconds = info.timings.as_ref().and_then(|t| t.seconds(resolved));
+                    if let Some(seconds) = seconds {
+                        lines.push(Line::from(format!("compile time: {seconds:.2}s")));
+                    }
+                }
+                if !matches!(kind, DependencyKind::Normal) {
+                    lines.push(Line::from(format!("{kind} dependency")));
+                }
+                lines.push(Line::from(format!(
+                    "default features: {}",
+                    if dep.uses_default_features {
+                        "yes"
+                    } else {
+                        "no"
+                    }
+                )));
+                if !dep.features.is_empty() {
+                    lines.push(Line::from(format!("features: {}", dep.features.join(", "))));
+                }
+            }
+
+            [WorkspacePackage { id, .. }, Dependency {
+                name: dep_name,
+                kind,
+                ..
+            }, Feature {
+                name,
+                status,
+                deps,
+                usage,
+            }] => {
+                lines.push(Line::from(vec![
+                    Span::raw("feature "),
+                    Span::styled(name.clone(), Style::default().bold()),
+                    Span::raw(format!(" of {dep_name}")),
+                ]));
+                if !deps.is_empty() {
+                    lines.push(Line::from(format!("enables: {}", deps.join(", "))));
+                }
+                lines.push(Line::raw(""));
+
+                if let Some(package) = resolver.resolve_dependency(id, dep_name) {
+                    let docs = FeatureDocs::load(package, name);
+                    for comment in &docs.comments {
+                        lines.push(Line::from(comment.clone().italic()));
+                    }
+                    if !docs.readme.is_empty() {
+                        if !docs.comments.is_empty() {
+                            lines.push(Line::raw(""));
+                        }
+                        lines.push(Line::from("README:".dim()));
+                        for mention in &docs.readme {
+                            lines.push(Line::from(format!("  {mention}")));
+                        }
+                    }
+                    if let Some(docs_rs) = docs.docs_rs {
+                        lines.push(Line::from(docs_rs.dim()));
+                    }
+                    lines.push(Line::from(FeatureDocs::docs_rs_url(package).dim()));
+                    lines.push(Line::raw(""));
+                }
+
+                match status {
+                    FeatureStatus::Enabled => {
+                        let removed = RemovedFeature {
+                            member: id,
+                            dep_name: Self::manifest_key(info, id, dep_name, *kind),
+                            dep_kind: *kind,
+                            feature: name,
+                        };
+                        let pruned = FeatureResolver::new(&info.metadata).removal_impact(&removed);
+                        if pruned.is_empty() {
+                            lines.push(Line::from(
+                                "Disabling it does not remove any packages from the build.",
+                            ));
+                        } else {
+                            lines.push(Line::from(vec![
+                                Span::raw("Disabling it removes "),
+                                Span::styled(pruned.len().to_string(), Style::default().bold()),
+                                Span::raw(if pruned.len() == 1 {
+                                    " package from the build:"
+                                } else {
+                                    " packages from the build:"
+                                }),
+                            ]));
+                            for package in &pruned {
+                                lines.push(Line::from(format!(
+                                    "  {} v{}",
+                                    package.name, package.version
+                                )));
+                            }
+                            if let Some(timings) = &info.timings {
+                                lines.push(Line::from(format!(
+                                    "Their compile time: {:.2}s",
+                                    timings.total(pruned.iter().copied())
+                                )));
+                            }
+                        }
+
+                        match usage {
+                            FeatureUsage::PossiblyUnused { items } => {
+                                lines.push(Line::raw(""));
+                                lines.push(Line::from(
+                                    "Possibly unused: none of the items it gates are referenced:"
+                                        .yellow(),
+                                ));
+                                lines.push(Line::from(format!("  {}", items.join(", "))));
+                            }
+                            FeatureUsage::Used { items } => {
+                                lines.push(Line::raw(""));
+                                lines.push(Line::from(format!("Used items: {}", items.join(", "))));
+                            }
+                            FeatureUsage::Unknown => {}
+                        }
+                    }
+                    FeatureStatus::IndirectlyEnabled => {
+                        lines.push(Line::from(
+                            "Enabled through other features, disable those to turn it off.",
+                        ));
+                    }
+                    FeatureStatus::Disabled => {
+                        lines.push(Line::from("Not enabled."));
+                    }
+                }
+            }
+
+            [WorkspacePackage { id, .. }, Dependency {
+                name: dep_name,
+                kind,
+                ..
+            }, DefaultFeatures { enabled, features }] => {
+                lines.push(Line::from(vec![
+                    Span::raw("default features of "),
+                    Span::styled(dep_name.clone(), Style::default().bold()),
+                    Span::raw(if *enabled { ": on" } else { ": off" }),
+                ]));
+                if !features.is_empty() {
+                    lines.push(Line::from(format!("default: {}", features.join(", "))));
+                }
+                lines.push(Line::raw(""));
+                if *enabled {
+                    let removed = RemovedFeature {
+                        member: id,
+                        dep_name: Self::manifest_key(info, id, dep_name, *kind),
+                        dep_kind: *kind,
+                        feature: "default",
+                    };
+                    let pruned = FeatureResolver::new(&info.metadata).removal_impact(&removed);
+                    if pruned.is_empty() {
+                        lines.push(Line::from(
+                            "Turning them off does not remove any packages from the build.",
+                        ));
+                    } else {
+                        lines.push(Line::from(format!(
+                            "Turning them off, without keeping any of them, removes {} packages from the build:",
+                            pruned.len()
+                        )));
+                        for package in &pruned {
+                            lines.push(Line::from(format!(
+                                "  {} v{}",
+                                package.name, package.version
+                            )));
+                        }
+                    }
+                } else {
+                    lines.push(Line::from("Enter turns them back on."));
+                }
+            }
+
+            [.., TransitiveDependency { name, version, .. }] => {
+                lines.push(Line::from(vec![
+                    Span::styled(name.clone(), Style::default().bold()),
+                    Span::raw(format!(" v{version}")),
+                ]));
+            }
+
+            [WorkspacePackage { id, .. }, PackageFeatures { .. }, PackageFeature { name, .. }, ..] =>
+            {
+                let Some(package) = resolver.package(id) else {
+                    return lines;
+                };
+                lines.push(Line::from(vec![
+                    Span::raw("feature "),
+                    Span::styled(name.clone(), Style::default().bold()),
+                    Span::raw(format!(" of {}", package.name)),
+                ]));
+                let entries = package.features.get(name).cloned().unwrap_or_default();
+                if entries.is_empty() {
+                    lines.push(Line::from("enables nothing".dim()));
+                } else {
+                    lines.push(Line::from(format!("enables: {}", entries.join(", "))));
+                }
+                let enabled_by = package
+                    .features
+                    .iter()
+                    .filter(|(_, entries)| entries.contains(name))
+                    .map(|(feature, _)| feature.as_str())
+                    .collect::<Vec<_>>();
+                if !enabled_by.is_empty() {
+                    lines.push(Line::from(format!("enabled by: {}", enabled_by.join(", "))));
+                }
+            }
+
+            _ => {}
+        }
+
+        lines
+    }
+}
+
+impl Component for Details {
+    fn render(&mut self, f: &mut Frame, rect: Rect) {
+        let paragraph = Paragraph::new(self.lines.clone())
+            .block(
+                Block::default()
+                    .borders(Borders::LEFT)
+                    .padding(ratatui::widgets::Padding::horizontal(1)),
+            )
+            .wrap(Wrap { trim: false });
+        f.render_widget(paragraph, rect);
+    }
+}
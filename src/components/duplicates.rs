@@ -0,0 +1,162 @@
+use crossterm::event;
+use eyre::Result;
+use ratatui::prelude::*;
+use tui_tree_widget::{Tree, TreeItem, TreeState};
+
+use crate::action::Action;
+use crate::component::Component;
+use crate::keymap::keymap;
+use crate::metadata::duplicates::{find_duplicates, DuplicateCrate};
+use crate::metadata::workspace_info::WorkspaceInfo;
+use crate::theme::theme;
+
+/// Lists crates that are resolved in multiple versions, see
+/// [`find_duplicates`].
+#[derive(Debug, Clone, Default)]
+pub struct DuplicatesView {
+    count: usize,
+    tree_state: TreeState<String>,
+    items: Vec<TreeItem<'static, String>>,
+}
+
+impl DuplicatesView {
+    pub fn new(info: &WorkspaceInfo) -> Self {
+        let duplicates = find_duplicates(&info.metadata);
+        let items = duplicates.iter().map(Self::tree_item).collect::<Vec<_>>();
+        let mut tree_state = TreeState::default();
+        tree_state.select_first(&items);
+        Self {
+            count: duplicates.len(),
+            tree_state,
+            items,
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    fn tree_item(duplicate: &DuplicateCrate) -> TreeItem<'static, String> {
+        let versions = duplicate
+            .versions
+            .iter()
+            .map(|v| format!("v{}", v.version))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let label = Line::from(vec![
+            Span::styled(duplicate.name.clone(), Style::default().white().bold()),
+            Span::raw(format!(" ({versions})")),
+        ]);
+
+        let mut children = duplicate
+            .versions
+            .iter()
+            .map(|version| {
+                let paths = version
+                    .paths
+                    .iter()
+                    .enumerate()
+                    .map(|(i, path)| {
+                        TreeItem::new_leaf(
+                            format!("{i}:{}", path.member().id),
+                            Span::raw(path.describe()),
+                        )
+                    })
+                    .collect();
+                let label = format!(
+                    "v{} used by {}",
+                    version.version,
+                    version
+                        .paths
+                        .iter()
+                        .map(|p| p.member().name.clone())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+                TreeItem::new(
+                    version.id.to_string(),
+                    Span::styled(label, Style::default().white()),
+                    paths,
+                )
+                .expect("tree failed")
+            })
+            .collect::<Vec<_>>();
+
+        if !duplicate.suggestions.is_empty() {
+            let suggestions = duplicate
+                .suggestions
+                .iter()
+                .enumerate()
+                .map(|(i, s)| {
+                    TreeItem::new_leaf(
+                        i.to_string(),
+                        Span::styled(s.clone(), Style::default().green()),
+                    )
+                })
+                .collect();
+            children.push(
+                TreeItem::new(
+                    "suggestions".to_string(),
+                    "suggestions".green(),
+                    suggestions,
+                )
+                .expect("tree failed"),
+            );
+        }
+
+        TreeItem::new(duplicate.name.clone(), label, children).expect("tree failed")
+    }
+
+    pub fn breadcrumbs(&self) -> Vec<Span<'static>> {
+        vec![
+            Span::raw(" "),
+            Span::styled(
+                format!("{} crates with multiple versions", self.count),
+                Style::default().bold(),
+            ),
+            Span::raw(" "),
+        ]
+    }
+
+    pub fn help(&self) -> Vec<Span<'static>> {
+        keymap().global_help()
+    }
+}
+
+impl Component for DuplicatesView {
+    fn handle_key_events(&mut self, key_event: event::KeyEvent) -> Result<Option<Action>> {
+        match key_event.code {
+            event::KeyCode::Up => {
+                self.tree_state.key_up(&self.items);
+                Action::render()
+            }
+            event::KeyCode::Down => {
+                self.tree_state.key_down(&self.items);
+                Action::render()
+            }
+            event::KeyCode::Right => {
+                self.tree_state.key_right();
+                Action::render()
+            }
+            event::KeyCode::Left => {
+                self.tree_state.key_left();
+                Action::render()
+            }
+            event::KeyCode::Enter => {
+                self.tree_state.toggle_selected();
+                Action::render()
+            }
+            _ => Action::none(),
+        }
+    }
+
+    fn render(&mut self, f: &mut Frame, rect: Rect) {
+        let tree = Tree::new(self.items.clone())
+            .expect("tree failed")
+            .highlight_style(theme().highlight)
+            .node_closed_symbol(theme().icons.node_closed)
+            .node_open_symbol(theme().icons.node_open);
+
+        f.render_stateful_widget(tree, rect, &mut self.tree_state);
+    }
+}
@@ -0,0 +1,88 @@
+use crossterm::event;
+use eyre::Result;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, BorderType, Borders, Cell, Clear, Row, Table},
+};
+
+use crate::action::Action;
+use crate::component::Component;
+use crate::keymap::{keymap, Command};
+use crate::theme::theme;
+
+/// Popup listing the key bindings of the current location. Any key closes
+/// it.
+#[derive(Debug)]
+pub struct HelpDialog {
+    title: String,
+    /// Keys and what they do.
+    rows: Vec<(String, String)>,
+}
+
+impl HelpDialog {
+    /// `commands` are the bound ones with their labels, `other` are keys that
+    /// can't be rebound, like the arrows.
+    pub fn new(
+        title: impl Into<String>,
+        commands: &[(Command, &str)],
+        other: &[(&str, &str)],
+    ) -> Self {
+        let keymap = keymap();
+        let mut rows = other
+            .iter()
+            .map(|(key, label)| (key.to_string(), label.to_string()))
+            .collect::<Vec<_>>();
+        for (command, label) in commands {
+            if let Some(keys) = keymap.key_names(*command) {
+                rows.push((keys, label.to_string()));
+            }
+        }
+        Self {
+            title: title.into(),
+            rows,
+        }
+    }
+}
+
+impl Component for HelpDialog {
+    fn handle_key_events(&mut self, _key_event: event::KeyEvent) -> Result<Option<Action>> {
+        Ok(Some(Action::CloseDialog))
+    }
+
+    fn render(&mut self, f: &mut Frame, rect: Rect) {
+        let key_width = self
+            .rows
+            .iter()
+            .map(|(keys, _)| keys.chars().count())
+            .max()
+            .unwrap_or(0) as u16;
+        // borders
+        let height = (self.rows.len() as u16 + 2).min(rect.height);
+        let [area] = Layout::vertical([Constraint::Length(height)])
+            .flex(layout::Flex::Center)
+            .areas(rect);
+        let [area] = Layout::horizontal([Constraint::Length(50.max(key_width + 30))])
+            .flex(layout::Flex::Center)
+            .areas(area);
+
+        let rows = self.rows.iter().map(|(keys, label)| {
+            Row::new([
+                Cell::from(keys.clone().set_style(theme().key)),
+                Cell::from(Span::raw(label.clone())),
+            ])
+        });
+        let table = Table::new(rows, [Constraint::Length(key_width), Constraint::Min(0)])
+            .column_spacing(2)
+            .block(
+                Block::default()
+                    .title(format!(" {} ", self.title))
+                    .title_bottom(" any key closes ".dim())
+                    .title_alignment(Alignment::Center)
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Double)
+                    .padding(ratatui::widgets::Padding::horizontal(1)),
+            );
+        f.render_widget(Clear, area);
+        f.render_widget(table, area);
+    }
+}
@@ -0,0 +1,214 @@
+use cargo_metadata::PackageId;
+use crossterm::event;
+use eyre::Result;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+};
+
+use crate::action::Action;
+use crate::cargo::FeatureEdit;
+use crate::component::Component;
+
+/// What the text of an [`InputDialog`] is used for.
+#[derive(Debug, Clone)]
+pub enum InputTarget {
+    /// The name of a new feature of a workspace package.
+    NewFeature { package: PackageId },
+    /// An entry to add to a feature of a workspace package.
+    FeatureEntry { package: PackageId, feature: String },
+    /// The feature that makes the dependency `dep` optional.
+    OptionalDependency { package: PackageId, dep: String },
+}
+
+impl InputTarget {
+    fn action(&self, value: String) -> Action {
+        match self {
+            InputTarget::NewFeature { package } => Action::EditFeatures {
+                package: package.clone(),
+                edit: FeatureEdit::Create(value),
+            },
+            InputTarget::FeatureEntry { package, feature } => Action::EditFeatures {
+                package: package.clone(),
+                edit: FeatureEdit::AddEntry {
+                    feature: feature.clone(),
+                    entry: value,
+                },
+            },
+            InputTarget::OptionalDependency { package, dep } => Action::EditFeatures {
+                package: package.clone(),
+                edit: FeatureEdit::MakeOptional {
+                    dep: dep.clone(),
+                    feature: value,
+                },
+            },
+        }
+    }
+}
+
+/// Popup with a text input and optional suggestions that are filtered by the
+/// input. Enter submits the selected suggestion, or the text if none is
+/// selected.
+#[derive(Debug)]
+pub struct InputDialog {
+    title: String,
+    target: InputTarget,
+    input: String,
+    suggestions: Vec<String>,
+    /// Suggestions containing the input.
+    matches: Vec<String>,
+    list_state: ListState,
+    error: Option<String>,
+}
+
+impl InputDialog {
+    pub fn new(title: impl Into<String>, target: InputTarget, suggestions: Vec<String>) -> Self {
+        let mut dialog = Self {
+            title: title.into(),
+            target,
+            input: String::new(),
+            matches: Vec::new(),
+            suggestions,
+            list_state: ListState::default(),
+            error: None,
+        };
+        dialog.filter();
+        dialog
+    }
+
+    /// Shows an error, e.g. if the submitted value was rejected.
+    pub fn set_error(&mut self, err: eyre::Report) {
+        self.error = Some(format!("{err:#}"));
+    }
+
+    fn filter(&mut self) {
+        let input = self.input.to_lowercase();
+        self.matches = self
+            .suggestions
+            .iter()
+            .filter(|s| s.to_lowercase().contains(&input))
+            .cloned()
+            .collect();
+        self.list_state.select(None);
+    }
+
+    fn submit(&self) -> Option<Action> {
+        let value = match self.list_state.selected() {
+            Some(i) => self.matches.get(i)?.clone(),
+            None => self.input.trim().to_string(),
+        };
+        if value.is_empty() {
+            return None;
+        }
+        Some(self.target.action(value))
+    }
+}
+
+impl Component for InputDialog {
+    fn handle_key_events(&mut self, key_event: event::KeyEvent) -> Result<Option<Action>> {
+        self.error = None;
+        match key_event.code {
+            event::KeyCode::Esc => return Ok(Some(Action::CloseDialog)),
+            event::KeyCode::Enter => return Ok(self.submit()),
+            event::KeyCode::Up => {
+                let i = match self.list_state.selected() {
+                    Some(0) | None => None,
+                    Some(i) => Some(i - 1),
+                };
+                self.list_state.select(i);
+            }
+            event::KeyCode::Down if !self.matches.is_empty() => {
+                let i = self
+                    .list_state
+                    .selected()
+                    .map_or(0, |i| (i + 1).min(self.matches.len() - 1));
+                self.list_state.select(Some(i));
+            }
+            event::KeyCode::Tab => {
+                // complete the input with the selected suggestion
+                if let Some(value) = self.list_state.selected().and_then(|i| self.matches.get(i)) {
+                    self.input = value.clone();
+                    self.filter();
+                }
+            }
+            event::KeyCode::Char(c) => {
+                self.input.push(c);
+                self.filter();
+            }
+            event::KeyCode::Backspace => {
+                self.input.pop();
+                self.filter();
+            }
+            _ => return Action::none(),
+        }
+        Action::render()
+    }
+
+    fn render(&mut self, f: &mut Frame, rect: Rect) {
+        let list_height = if self.suggestions.is_empty() {
+            0
+        } else {
+            self.matches.len().clamp(1, 15) as u16 + 1
+        };
+        let error_height = if self.error.is_some() { 5 } else { 0 };
+        // borders and input line
+        let height = (3 + list_height + error_height).min(rect.height);
+        let [area] = Layout::vertical([Constraint::Length(height)])
+            .flex(layout::Flex::Center)
+            .areas(rect);
+        let [area] = Layout::horizontal([Constraint::Percentage(50)])
+            .flex(layout::Flex::Center)
+            .areas(area);
+
+        let mut help = vec![" ".dim(), "<enter>".blue(), " ok".dim(), " ".dim()];
+        if !self.suggestions.is_empty() {
+            help.extend(["<tab>".blue(), " complete".dim(), " ".dim()]);
+        }
+        help.extend(["<esc>".blue(), " cancel".dim(), " ".dim()]);
+        let block = Block::default()
+            .title(format!(" {} ", self.title))
+            .title_bottom(help)
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Double);
+        let inner = block.inner(area);
+        f.render_widget(Clear, area);
+        f.render_widget(block, area);
+
+        let [input, list, error] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(list_height),
+            Constraint::Length(error_height),
+        ])
+        .areas(inner);
+
+        f.render_widget(
+            Paragraph::new(Line::from(vec![Span::raw(self.input.clone()), "▏".blue()])),
+            input,
+        );
+
+        if list_height > 0 {
+            let [_, list] =
+                Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(list);
+            let items = self
+                .matches
+                .iter()
+                .map(|s| ListItem::new(s.clone()))
+                .collect::<Vec<_>>();
+            f.render_stateful_widget(
+                List::new(items).highlight_style(Style::default().on_dark_gray()),
+                list,
+                &mut self.list_state,
+            );
+        }
+
+        if let Some(err) = &self.error {
+            let [_, error] =
+                Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(error);
+            f.render_widget(
+                Paragraph::new(err.clone().red()).wrap(Wrap { trim: false }),
+                error,
+            );
+        }
+    }
+}
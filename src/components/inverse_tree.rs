@@ -0,0 +1,113 @@
+use cargo_metadata::PackageId;
+use crossterm::event;
+use eyre::Result;
+use ratatui::prelude::*;
+use tui_tree_widget::{Tree, TreeItem, TreeState};
+
+use crate::action::Action;
+use crate::component::Component;
+use crate::keymap::keymap;
+use crate::metadata::inverse_tree::{InverseNode, InverseTree};
+use crate::metadata::workspace_info::WorkspaceInfo;
+use crate::theme::theme;
+
+/// Shows who depends on a package, see [`InverseTree`].
+#[derive(Debug, Clone)]
+pub struct InverseTreeView {
+    package_name: String,
+    tree_state: TreeState<String>,
+    items: Vec<TreeItem<'static, String>>,
+}
+
+impl InverseTreeView {
+    pub fn new(info: &WorkspaceInfo, package: &PackageId) -> Result<Self> {
+        let Some(tree) = InverseTree::build(&info.metadata, package) else {
+            eyre::bail!("Could not resolve package {package}");
+        };
+
+        let root_id = Self::widget_id(0, &tree.root);
+        let items = vec![Self::tree_item(root_id.clone(), &tree.root)];
+        let mut tree_state = TreeState::default();
+        tree_state.open(vec![root_id.clone()]);
+        tree_state.select(vec![root_id]);
+
+        Ok(Self {
+            package_name: format!("{} v{}", tree.root.name, tree.root.version),
+            tree_state,
+            items,
+        })
+    }
+
+    fn widget_id(i: usize, node: &InverseNode) -> String {
+        format!("{i}:{}", node.id)
+    }
+
+    fn tree_item(key: String, node: &InverseNode) -> TreeItem<'static, String> {
+        let style = if node.is_workspace_member {
+            Style::default().white().bold()
+        } else if node.repeated {
+            Style::default().dim()
+        } else {
+            Style::default().white()
+        };
+        let span = Span::styled(node.label(), style);
+
+        let children = node
+            .children
+            .iter()
+            .enumerate()
+            .map(|(i, child)| Self::tree_item(Self::widget_id(i, child), child))
+            .collect();
+        TreeItem::new(key, span, children).expect("tree failed")
+    }
+
+    pub fn breadcrumbs(&self) -> Vec<Span<'static>> {
+        vec![
+            Span::raw(" who depends on "),
+            Span::styled(self.package_name.clone(), Style::default().bold()),
+            Span::raw(" "),
+        ]
+    }
+
+    pub fn help(&self) -> Vec<Span<'static>> {
+        keymap().global_help()
+    }
+}
+
+impl Component for InverseTreeView {
+    fn handle_key_events(&mut self, key_event: event::KeyEvent) -> Result<Option<Action>> {
+        match key_event.code {
+            event::KeyCode::Up => {
+                self.tree_state.key_up(&self.items);
+                Action::render()
+            }
+            event::KeyCode::Down => {
+                self.tree_state.key_down(&self.items);
+                Action::render()
+            }
+            event::KeyCode::Right => {
+                self.tree_state.key_right();
+                Action::render()
+            }
+            event::KeyCode::Left => {
+                self.tree_state.key_left();
+                Action::render()
+            }
+            event::KeyCode::Enter => {
+                self.tree_state.toggle_selected();
+                Action::render()
+            }
+            _ => Action::none(),
+        }
+    }
+
+    fn render(&mut self, f: &mut Frame, rect: Rect) {
+        let tree = Tree::new(self.items.clone())
+            .expect("tree failed")
+            .highlight_style(theme().highlight)
+            .node_closed_symbol(theme().icons.node_closed)
+            .node_open_symbol(theme().icons.node_open);
+
+        f.render_stateful_widget(tree, rect, &mut self.tree_state);
+    }
+}
@@ -1,4 +1,16 @@
+mod add_dependency;
 pub mod app;
+mod checklist;
+mod compile_times;
+mod confirm;
+mod dependency_map;
 mod dependency_tab;
 mod dependency_tree;
+mod details;
+mod duplicates;
+mod help;
+mod input;
+mod inverse_tree;
 mod package_info;
+mod policy;
+mod treemap;
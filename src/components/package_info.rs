@@ -1,9 +1,7 @@
-use ratatui::{
-    prelude::*,
-    widgets::{Block, BorderType, Borders, Paragraph, Wrap},
-};
+use ratatui::widgets::{Block, BorderType, Borders, Paragraph, Wrap};
 
 use crate::component::Component;
+use crate::theme::theme;
 
 pub struct PackageInfo<'a> {
     pub metadata: &'a cargo_metadata::Metadata,
@@ -21,7 +19,7 @@ impl<'a> Component for PackageInfo<'a> {
         let name = &self.metadata.workspace_default_members[0].to_string();
 
         let paragraph = Paragraph::new(name.as_str())
-            .style(Style::default().fg(Color::Gray))
+            .style(theme().dim)
             .block(
                 Block::default()
                     .title("Package")
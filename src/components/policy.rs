@@ -0,0 +1,127 @@
+use crossterm::event;
+use eyre::Result;
+use ratatui::prelude::*;
+use tui_tree_widget::{Tree, TreeItem, TreeState};
+
+use crate::action::Action;
+use crate::component::Component;
+use crate::keymap::keymap;
+use crate::metadata::policy::{find_violations, Rule, Violation};
+use crate::metadata::workspace_info::WorkspaceInfo;
+use crate::theme::theme;
+
+/// Lists the dependency declarations that break a [`Rule`], grouped by rule.
+#[derive(Debug, Clone, Default)]
+pub struct PolicyView {
+    count: usize,
+    tree_state: TreeState<String>,
+    items: Vec<TreeItem<'static, String>>,
+}
+
+impl PolicyView {
+    pub fn new(info: &WorkspaceInfo) -> Self {
+        let violations = find_violations(&info.metadata);
+        let mut tree_state = TreeState::default();
+        let mut items = Vec::new();
+        for rule in Rule::ALL {
+            let violations = violations
+                .iter()
+                .filter(|v| v.rule == rule)
+                .collect::<Vec<_>>();
+            if !violations.is_empty() {
+                items.push(Self::tree_item(rule, &violations));
+                tree_state.open(vec![format!("{rule:?}")]);
+            }
+        }
+        tree_state.select_first(&items);
+        Self {
+            count: violations.len(),
+            tree_state,
+            items,
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    fn tree_item(rule: Rule, violations: &[&Violation]) -> TreeItem<'static, String> {
+        let label = Line::from(vec![
+            Span::styled(rule.description(), theme().warning.bold()),
+            Span::styled(format!(" ({})", violations.len()), theme().dim),
+        ]);
+        let children = violations
+            .iter()
+            .enumerate()
+            .map(|(i, violation)| {
+                let mut spans = vec![Span::styled(violation.dep_name.clone(), theme().dependency)];
+                // the message of diverging versions names the members already
+                if rule != Rule::DivergingVersions {
+                    spans.push(Span::styled(
+                        format!(" {}", violation.members.join(", ")),
+                        theme().dim,
+                    ));
+                }
+                spans.push(Span::raw(format!(": {}", violation.message)));
+                let label = Line::from(spans);
+                TreeItem::new_leaf(format!("{i}:{}", violation.dep_name), label)
+            })
+            .collect();
+        TreeItem::new(format!("{rule:?}"), label, children).expect("tree failed")
+    }
+
+    pub fn breadcrumbs(&self) -> Vec<Span<'static>> {
+        let text = match self.count {
+            0 => "no policy violations".to_string(),
+            1 => "1 policy violation".to_string(),
+            count => format!("{count} policy violations"),
+        };
+        vec![
+            Span::raw(" "),
+            Span::styled(text, theme().emphasis),
+            Span::raw(" "),
+        ]
+    }
+
+    pub fn help(&self) -> Vec<Span<'static>> {
+        keymap().global_help()
+    }
+}
+
+impl Component for PolicyView {
+    fn handle_key_events(&mut self, key_event: event::KeyEvent) -> Result<Option<Action>> {
+        match key_event.code {
+            event::KeyCode::Up => {
+                self.tree_state.key_up(&self.items);
+                Action::render()
+            }
+            event::KeyCode::Down => {
+                self.tree_state.key_down(&self.items);
+                Action::render()
+            }
+            event::KeyCode::Right => {
+                self.tree_state.key_right();
+                Action::render()
+            }
+            event::KeyCode::Left => {
+                self.tree_state.key_left();
+                Action::render()
+            }
+            event::KeyCode::Enter => {
+                self.tree_state.toggle_selected();
+                Action::render()
+            }
+            _ => Action::none(),
+        }
+    }
+
+    fn render(&mut self, f: &mut Frame, rect: Rect) {
+        let tree = Tree::new(self.items.clone())
+            .expect("tree failed")
+            .highlight_style(theme().highlight)
+            .node_closed_symbol(theme().icons.node_closed)
+            .node_open_symbol(theme().icons.node_open);
+
+        f.render_stateful_widget(tree, rect, &mut self.tree_state);
+    }
+}
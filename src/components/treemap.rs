@@ -0,0 +1,231 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, BorderType, Borders, Paragraph},
+};
+
+/// A rectangle of a [`Treemap`].
+#[derive(Debug, Clone)]
+pub struct TreemapItem {
+    pub label: String,
+    /// Shown below the label, e.g. the formatted value.
+    pub caption: String,
+    pub value: f64,
+    /// Usually one of the `map_*` styles of the theme.
+    pub style: Style,
+}
+
+/// Draws items as rectangles whose area is proportional to their value, laid
+/// out with the squarified treemap algorithm so that the rectangles stay close
+/// to squares. Items must be sorted by value, largest first.
+#[derive(Debug)]
+pub struct Treemap<'a> {
+    items: &'a [TreemapItem],
+    selected: Option<usize>,
+}
+
+/// Terminal cells are about twice as high as wide, the layout is computed in
+/// square units and stretched horizontally afterwards.
+const CELL_ASPECT: f64 = 2.0;
+
+#[derive(Debug, Clone, Copy)]
+struct Area {
+    x: f64,
+    y: f64,
+    w: f64,
+    h: f64,
+}
+
+impl<'a> Treemap<'a> {
+    pub fn new(items: &'a [TreemapItem]) -> Self {
+        Self {
+            items,
+            selected: None,
+        }
+    }
+
+    pub fn select(mut self, selected: Option<usize>) -> Self {
+        self.selected = selected;
+        self
+    }
+
+    /// The rectangle of each item inside `rect`. Items too small to get a cell
+    /// get an empty rectangle.
+    pub fn layout(items: &[TreemapItem], rect: Rect) -> Vec<Rect> {
+        let total = items.iter().map(|item| item.value.max(0.0)).sum::<f64>();
+        if items.is_empty() || total <= 0.0 || rect.area() == 0 {
+            return vec![Rect::default(); items.len()];
+        }
+
+        let area = Area {
+            x: 0.0,
+            y: 0.0,
+            w: rect.width as f64 / CELL_ASPECT,
+            h: rect.height as f64,
+        };
+        let scale = area.w * area.h / total;
+        let sizes = items
+            .iter()
+            .map(|item| item.value.max(0.0) * scale)
+            .collect::<Vec<_>>();
+
+        squarify(&sizes, area)
+            .into_iter()
+            .map(|a| {
+                let x0 = (a.x * CELL_ASPECT).round() as u16;
+                let x1 = ((a.x + a.w) * CELL_ASPECT).round() as u16;
+                let y0 = a.y.round() as u16;
+                let y1 = (a.y + a.h).round() as u16;
+                Rect::new(
+                    rect.x + x0,
+                    rect.y + y0,
+                    x1.saturating_sub(x0),
+                    y1.saturating_sub(y0),
+                )
+                .intersection(rect)
+            })
+            .collect()
+    }
+}
+
+impl Widget for Treemap<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let rects = Self::layout(self.items, area);
+        for (i, (item, rect)) in self.items.iter().zip(rects).enumerate() {
+            if rect.area() == 0 {
+                continue;
+            }
+            let selected = self.selected == Some(i);
+            let style = if selected {
+                item.style.reversed().bold()
+            } else {
+                item.style
+            };
+
+            if rect.width < 4 || rect.height < 3 {
+                buf.set_style(rect, item.style.reversed());
+                continue;
+            }
+
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_type(if selected {
+                    BorderType::Thick
+                } else {
+                    BorderType::Rounded
+                })
+                .style(style);
+            let text = vec![
+                Line::from(item.label.clone()),
+                Line::from(item.caption.clone()),
+            ];
+            Paragraph::new(text).block(block).render(rect, buf);
+        }
+    }
+}
+
+/// Squarified treemap layout (Bruls, Huizing, van Wijk). `sizes` are areas
+/// that sum up to the area of `area`, sorted descending.
+fn squarify(sizes: &[f64], mut area: Area) -> Vec<Area> {
+    let mut result = Vec::with_capacity(sizes.len());
+    let mut i = 0;
+    while i < sizes.len() {
+        let side = area.w.min(area.h);
+        let mut end = i + 1;
+        while end < sizes.len() && worst(&sizes[i..=end], side) <= worst(&sizes[i..end], side) {
+            end += 1;
+        }
+
+        let row = &sizes[i..end];
+        let row_size = row.iter().sum::<f64>();
+        if area.w >= area.h {
+            // column along the left edge
+            let width = if area.h > 0.0 { row_size / area.h } else { 0.0 };
+            let mut y = area.y;
+            for size in row {
+                let height = if width > 0.0 { size / width } else { 0.0 };
+                result.push(Area {
+                    x: area.x,
+                    y,
+                    w: width,
+                    h: height,
+                });
+                y += height;
+            }
+            area.x += width;
+            area.w -= width;
+        } else {
+            // row along the top edge
+            let height = if area.w > 0.0 { row_size / area.w } else { 0.0 };
+            let mut x = area.x;
+            for size in row {
+                let width = if height > 0.0 { size / height } else { 0.0 };
+                result.push(Area {
+                    x,
+                    y: area.y,
+                    w: width,
+                    h: height,
+                });
+                x += width;
+            }
+            area.y += height;
+            area.h -= height;
+        }
+
+        i = end;
+    }
+    result
+}
+
+/// The worst aspect ratio of the rectangles of `row` laid out along `side`.
+fn worst(row: &[f64], side: f64) -> f64 {
+    let sum = row.iter().sum::<f64>();
+    let max = row.iter().cloned().fold(f64::MIN, f64::max);
+    let min = row.iter().cloned().fold(f64::MAX, f64::min);
+    if sum <= 0.0 || min <= 0.0 {
+        return f64::MAX;
+    }
+    let side = side * side;
+    let sum = sum * sum;
+    (side * max / sum).max(sum / (side * min))
+}
+
+/// Moves a selection to the item whose rectangle is next to the selected one
+/// in `direction`. Rectangles that share an edge segment with the selected
+/// one are preferred, the closest one in the given direction wins.
+pub fn neighbor(rects: &[Rect], selected: usize, direction: Direction, forward: bool) -> usize {
+    let Some(current) = rects.get(selected) else {
+        return selected;
+    };
+    // (start, end) on the axis of movement and across it
+    let spans = |r: &Rect| {
+        let x = (r.x as i32, r.x as i32 + r.width as i32);
+        let y = (r.y as i32, r.y as i32 + r.height as i32);
+        match direction {
+            Direction::Horizontal => (x, y),
+            Direction::Vertical => (y, x),
+        }
+    };
+    let (along, across) = spans(current);
+
+    rects
+        .iter()
+        .enumerate()
+        .filter(|(i, r)| *i != selected && r.area() > 0)
+        .filter_map(|(i, r)| {
+            let (other_along, other_across) = spans(r);
+            let distance = if forward {
+                other_along.0 - along.1
+            } else {
+                along.0 - other_along.1
+            };
+            if distance < 0 {
+                return None;
+            }
+            let overlap = across.1.min(other_across.1) - across.0.max(other_across.0);
+            let offset = ((other_across.0 + other_across.1) - (across.0 + across.1)).abs();
+            Some((i, (overlap <= 0, distance, offset)))
+        })
+        .min_by_key(|(_, key)| *key)
+        .map(|(i, _)| i)
+        .unwrap_or(selected)
+}
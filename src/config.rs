@@ -0,0 +1,335 @@
+//! Settings from the user-level `config.toml` in the config dir and the
+//! project-level `.omd.toml` next to the manifest (or in a parent directory).
+//! The project settings override the user settings, command line flags
+//! override both:
+//!
+//! ```toml
+//! theme = "light"
+//! icons = "ascii"
+//! verify-edits = false
+//!
+//! [keys]
+//! refresh = "r"
+//!
+//! [filter]
+//! kinds = ["normal", "build"]
+//!
+//! [graph]
+//! format = "mermaid"
+//! open = "firefox"
+//! ```
+
+use cargo_metadata::DependencyKind;
+use clap::ValueEnum;
+use eyre::{Context, Result};
+use std::{
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use crate::{args::Opt, keymap::Keymap, logging::get_config_dir};
+
+/// Name of the project-level config file.
+pub const PROJECT_CONFIG: &str = ".omd.toml";
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// The active settings. The defaults until [`init`] was called.
+pub fn config() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+/// Sets the settings, only the first call has an effect.
+pub fn init(config: Config) {
+    if CONFIG.set(config).is_err() {
+        warn!("config is already initialized");
+    }
+}
+
+/// Colors of the UI, see [`crate::theme`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ThemeName {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+    /// No colors, the default if `NO_COLOR` is set.
+    Monochrome,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum IconSet {
+    #[default]
+    Unicode,
+    Ascii,
+}
+
+/// How the feature graph of a dependency is written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum GraphFormat {
+    /// A page that renders the graph with mermaid.js.
+    #[default]
+    Html,
+    /// The mermaid source.
+    Mermaid,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GraphConfig {
+    pub format: GraphFormat,
+    /// Program to open the graph with instead of the system default.
+    pub open: Option<String>,
+}
+
+/// What the dependency tree shows.
+#[derive(Debug, Clone)]
+pub struct Filter {
+    pub kinds: Vec<DependencyKind>,
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Self {
+            kinds: vec![
+                DependencyKind::Normal,
+                DependencyKind::Development,
+                DependencyKind::Build,
+            ],
+        }
+    }
+}
+
+impl Filter {
+    pub fn shows(&self, kind: DependencyKind) -> bool {
+        self.kinds.contains(&kind)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub theme: ThemeName,
+    pub icons: IconSet,
+    pub keys: Keymap,
+    pub filter: Filter,
+    /// Whether to try dependency edits in a temporary crate before writing
+    /// the manifest.
+    pub verify_edits: bool,
+    pub graph: GraphConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            theme: ThemeName::default(),
+            icons: IconSet::default(),
+            keys: Keymap::default(),
+            filter: Filter::default(),
+            verify_edits: true,
+            graph: GraphConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    /// The defaults changed by the user config and then by the project config
+    /// of the workspace at `manifest_path`. A non-empty `NO_COLOR` makes
+    /// monochrome the default theme, see <https://no-color.org>.
+    pub fn load(manifest_path: &Path) -> Result<Self> {
+        let mut config = Self::default();
+        if std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+            config.theme = ThemeName::Monochrome;
+        }
+        config.apply_file(&get_config_dir().join("config.toml"))?;
+        if let Some(path) = project_config(manifest_path) {
+            config.apply_file(&path)?;
+        }
+        Ok(config)
+    }
+
+    fn apply_file(&mut self, path: &Path) -> Result<()> {
+        if !path.exists() {
+            return Ok(());
+        }
+        let content = std::fs::read_to_string(path).with_context(|| format!("reading {path:?}"))?;
+        let doc = content
+            .parse::<toml_edit::DocumentMut>()
+            .with_context(|| format!("parsing {path:?}"))?;
+        self.apply(doc.as_table())
+            .with_context(|| format!("invalid config {path:?}"))
+    }
+
+    /// Takes over the settings in `table`, the others are kept.
+    pub fn apply(&mut self, table: &toml_edit::Table) -> Result<()> {
+        for (key, item) in table.iter() {
+            match key {
+                "theme" => self.theme = parse_enum(key, item)?,
+                "icons" => self.icons = parse_enum(key, item)?,
+                "verify-edits" => {
+                    self.verify_edits = item
+                        .as_bool()
+                        .ok_or_else(|| eyre::eyre!("{key}: expected true or false"))?
+                }
+                "keys" => self.keys.apply(item).context("[keys]")?,
+                "filter" => {
+                    for (key, item) in sub_table(key, item)? {
+                        match key {
+                            "kinds" => {
+                                let kinds = item.as_array().ok_or_else(|| {
+                                    eyre::eyre!("{key}: expected a list of dependency kinds")
+                                })?;
+                                self.filter.kinds = kinds
+                                    .iter()
+                                    .map(|kind| match kind.as_str() {
+                                        Some(kind) => parse_kind(kind).map_err(eyre::Report::msg),
+                                        None => eyre::bail!("{key}: expected a kind, got {kind}"),
+                                    })
+                                    .collect::<Result<_>>()?;
+                            }
+                            _ => eyre::bail!("unknown setting `filter.{key}`"),
+                        }
+                    }
+                }
+                "graph" => {
+                    for (key, item) in sub_table(key, item)? {
+                        match key {
+                            "format" => self.graph.format = parse_enum(key, item)?,
+                            "open" => {
+                                let open = item
+                                    .as_str()
+                                    .ok_or_else(|| eyre::eyre!("{key}: expected a program"))?;
+                                self.graph.open = Some(open.to_string());
+                            }
+                            _ => eyre::bail!("unknown setting `graph.{key}`"),
+                        }
+                    }
+                }
+                _ => eyre::bail!("unknown setting `{key}`"),
+            }
+        }
+        Ok(())
+    }
+
+    /// Takes over the settings given on the command line.
+    pub fn apply_args(&mut self, opt: &Opt) {
+        if let Some(theme) = opt.theme {
+            self.theme = theme;
+        }
+        if let Some(icons) = opt.icons {
+            self.icons = icons;
+        }
+        if opt.no_verify {
+            self.verify_edits = false;
+        }
+        if !opt.kinds.is_empty() {
+            self.filter.kinds = opt.kinds.clone();
+        }
+        if let Some(format) = opt.graph_format {
+            self.graph.format = format;
+        }
+        if let Some(open) = &opt.open {
+            self.graph.open = Some(open.clone());
+        }
+    }
+}
+
+/// The `.omd.toml` in the directory of the manifest or the closest parent
+/// directory that has one.
+fn project_config(manifest_path: &Path) -> Option<PathBuf> {
+    let manifest_path = std::path::absolute(manifest_path).ok()?;
+    let dir = if manifest_path.is_dir() {
+        manifest_path.as_path()
+    } else {
+        manifest_path.parent()?
+    };
+    dir.ancestors()
+        .map(|dir| dir.join(PROJECT_CONFIG))
+        .find(|path| path.exists())
+}
+
+fn sub_table<'a>(
+    key: &str,
+    item: &'a toml_edit::Item,
+) -> Result<impl Iterator<Item = (&'a str, &'a toml_edit::Item)>> {
+    let table = item
+        .as_table_like()
+        .ok_or_else(|| eyre::eyre!("{key}: expected a table"))?;
+    Ok(table.iter())
+}
+
+fn parse_enum<T: ValueEnum>(key: &str, item: &toml_edit::Item) -> Result<T> {
+    let value = item
+        .as_str()
+        .ok_or_else(|| eyre::eyre!("{key}: expected a string"))?;
+    T::from_str(value, true).map_err(|_| {
+        let variants = T::value_variants()
+            .iter()
+            .filter_map(|v| v.to_possible_value())
+            .map(|v| v.get_name().to_string())
+            .collect::<Vec<_>>();
+        eyre::eyre!(
+            "{key}: expected one of {}, got {value:?}",
+            variants.join(", ")
+        )
+    })
+}
+
+/// Parses `normal`, `dev` or `build`.
+pub fn parse_kind(kind: &str) -> Result<DependencyKind, String> {
+    match kind {
+        "normal" => Ok(DependencyKind::Normal),
+        "dev" => Ok(DependencyKind::Development),
+        "build" => Ok(DependencyKind::Build),
+        _ => Err(format!(
+            "unknown dependency kind {kind:?}, expected normal, dev or build"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge() {
+        let mut config = Config::default();
+        let user = "verify-edits = false\n[graph]\nformat = \"mermaid\"\nopen = \"firefox\"\n"
+            .parse::<toml_edit::DocumentMut>()
+            .unwrap();
+        config.apply(user.as_table()).unwrap();
+        let project = "[filter]\nkinds = [\"normal\", \"build\"]\n[graph]\nformat = \"html\"\n"
+            .parse::<toml_edit::DocumentMut>()
+            .unwrap();
+        config.apply(project.as_table()).unwrap();
+
+        assert!(!config.verify_edits);
+        assert_eq!(config.graph.format, GraphFormat::Html);
+        assert_eq!(config.graph.open.as_deref(), Some("firefox"));
+        assert!(!config.filter.shows(DependencyKind::Development));
+        assert!(config.filter.shows(DependencyKind::Build));
+
+        let opt = <crate::Args as clap::Parser>::parse_from([
+            "cargo",
+            "omd",
+            "--kinds",
+            "dev",
+            "--graph-format",
+            "mermaid",
+        ]);
+        let crate::Args::Omd(opt) = opt;
+        config.apply_args(&opt);
+        assert_eq!(config.filter.kinds, vec![DependencyKind::Development]);
+        assert_eq!(config.graph.format, GraphFormat::Mermaid);
+
+        for invalid in [
+            "colors = 1",
+            "theme = \"pink\"",
+            "[filter]\nkinds = [\"x\"]",
+        ] {
+            let doc = invalid.parse::<toml_edit::DocumentMut>().unwrap();
+            assert!(
+                Config::default().apply(doc.as_table()).is_err(),
+                "{invalid}"
+            );
+        }
+    }
+}
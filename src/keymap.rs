@@ -0,0 +1,376 @@
+//! Key bindings of the dependency tree and the global commands. The defaults
+//! can be changed in the `[keys]` table of the [config](crate::config):
+//!
+//! ```toml
+//! [keys]
+//! refresh = "r"
+//! quit = ["q", "ctrl-c"]
+//! ```
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use eyre::Result;
+use ratatui::prelude::*;
+
+use crate::theme::theme;
+use std::{fmt, str::FromStr};
+
+/// The active key bindings.
+pub fn keymap() -> &'static Keymap {
+    &crate::config::config().keys
+}
+
+/// What a key can be bound to. Arrow keys always navigate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Command {
+    Quit,
+    Help,
+    Refresh,
+    Back,
+    Duplicates,
+    CompileTimes,
+    Map,
+    Inverse,
+    Add,
+    Remove,
+    Upgrade,
+    UpgradeBreaking,
+    Optional,
+    NewFeature,
+    AddEntry,
+    Toggle,
+    Mark,
+    Everywhere,
+}
+
+impl Command {
+    pub const ALL: [Command; 18] = [
+        Command::Quit,
+        Command::Help,
+        Command::Refresh,
+        Command::Back,
+        Command::Duplicates,
+        Command::CompileTimes,
+        Command::Map,
+        Command::Inverse,
+        Command::Add,
+        Command::Remove,
+        Command::Upgrade,
+        Command::UpgradeBreaking,
+        Command::Optional,
+        Command::NewFeature,
+        Command::AddEntry,
+        Command::Toggle,
+        Command::Mark,
+        Command::Everywhere,
+    ];
+
+    /// The commands available everywhere.
+    pub const GLOBAL: [Command; 4] = [
+        Command::Help,
+        Command::Refresh,
+        Command::Back,
+        Command::Quit,
+    ];
+
+    /// Name in the `[keys]` table.
+    pub fn name(self) -> &'static str {
+        match self {
+            Command::Quit => "quit",
+            Command::Help => "help",
+            Command::Refresh => "refresh",
+            Command::Back => "back",
+            Command::Duplicates => "duplicates",
+            Command::CompileTimes => "compile-times",
+            Command::Map => "map",
+            Command::Inverse => "inverse",
+            Command::Add => "add",
+            Command::Remove => "remove",
+            Command::Upgrade => "upgrade",
+            Command::UpgradeBreaking => "upgrade-breaking",
+            Command::Optional => "optional",
+            Command::NewFeature => "new-feature",
+            Command::AddEntry => "add-entry",
+            Command::Toggle => "toggle",
+            Command::Mark => "mark",
+            Command::Everywhere => "everywhere",
+        }
+    }
+
+    /// Label in the help line, places can use a more specific one.
+    pub fn label(self) -> &'static str {
+        match self {
+            Command::Quit => "quit",
+            Command::Help => "help",
+            Command::Refresh => "refresh",
+            Command::Back => "back",
+            Command::Duplicates => "duplicates",
+            Command::CompileTimes => "compile times",
+            Command::Map => "map",
+            Command::Inverse => "inverse",
+            Command::Add => "add",
+            Command::Remove => "remove",
+            Command::Upgrade => "upgrade",
+            Command::UpgradeBreaking => "upgrade breaking",
+            Command::Optional => "optional",
+            Command::NewFeature => "new feature",
+            Command::AddEntry => "add entry",
+            Command::Toggle => "toggle",
+            Command::Mark => "mark",
+            Command::Everywhere => "everywhere",
+        }
+    }
+
+    fn default_keys(self) -> &'static [&'static str] {
+        match self {
+            Command::Quit => &["q"],
+            Command::Help => &["?"],
+            Command::Refresh => &["g"],
+            Command::Back => &["esc"],
+            Command::Duplicates => &["d"],
+            Command::CompileTimes => &["c"],
+            Command::Map => &["m"],
+            Command::Inverse => &["i"],
+            Command::Add => &["a"],
+            Command::Remove => &["x"],
+            Command::Upgrade => &["u"],
+            Command::UpgradeBreaking => &["U"],
+            Command::Optional => &["o"],
+            Command::NewFeature => &["n"],
+            Command::AddEntry => &["+"],
+            Command::Toggle => &["enter"],
+            Command::Mark => &["space"],
+            Command::Everywhere => &["e"],
+        }
+    }
+}
+
+impl FromStr for Command {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Command::ALL
+            .into_iter()
+            .find(|c| c.name() == s)
+            .ok_or_else(|| eyre::eyre!("unknown command {s:?}"))
+    }
+}
+
+/// A key with modifiers, written like `q`, `U`, `enter` or `ctrl-r`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    fn matches(&self, event: &KeyEvent) -> bool {
+        // the case of a char already says whether shift was pressed
+        let modifiers = match event.code {
+            KeyCode::Char(_) => event.modifiers - KeyModifiers::SHIFT,
+            _ => event.modifiers,
+        };
+        self.code == event.code && self.modifiers == modifiers
+    }
+}
+
+impl FromStr for Key {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+        // `-` alone is a key, `ctrl--` is ctrl and `-`
+        while let Some((modifier, key)) = rest.split_once('-').filter(|(_, key)| !key.is_empty()) {
+            modifiers |= match modifier {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                _ => break,
+            };
+            rest = key;
+        }
+
+        let code = match rest {
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "space" => KeyCode::Char(' '),
+            "tab" => KeyCode::Tab,
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            _ if rest.chars().count() == 1 => KeyCode::Char(rest.chars().next().unwrap_or(' ')),
+            _ => match rest.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                Some(n) => KeyCode::F(n),
+                None => eyre::bail!("invalid key {s:?}"),
+            },
+        };
+        Ok(Self { code, modifiers })
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut name = match self.code {
+            KeyCode::Char(' ') => "space".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Enter => "enter".to_string(),
+            KeyCode::Esc => "esc".to_string(),
+            KeyCode::Tab => "tab".to_string(),
+            KeyCode::Backspace => "backspace".to_string(),
+            KeyCode::Delete => "delete".to_string(),
+            KeyCode::Home => "home".to_string(),
+            KeyCode::End => "end".to_string(),
+            KeyCode::PageUp => "pageup".to_string(),
+            KeyCode::PageDown => "pagedown".to_string(),
+            KeyCode::F(n) => format!("f{n}"),
+            code => format!("{code:?}").to_lowercase(),
+        };
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            name = format!("alt-{name}");
+        }
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            name = format!("ctrl-{name}");
+        }
+        // single chars as they are, like the help of the dialogs
+        if name.chars().count() == 1 {
+            write!(f, "{name}")
+        } else {
+            write!(f, "<{name}>")
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(Key, Command)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = Command::ALL
+            .into_iter()
+            .flat_map(|command| {
+                command.default_keys().iter().map(move |key| {
+                    let key = key.parse().expect("invalid default key");
+                    (key, command)
+                })
+            })
+            .collect();
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    /// Binds the commands of a `[keys]` table. Their default keys and the new
+    /// keys of other commands are unbound.
+    pub fn apply(&mut self, keys: &toml_edit::Item) -> Result<()> {
+        let Some(keys) = keys.as_table_like() else {
+            eyre::bail!("expected a table");
+        };
+        for (name, value) in keys.iter() {
+            let command = name.parse::<Command>()?;
+            let keys = match value.as_value() {
+                Some(toml_edit::Value::String(key)) => vec![key.value().parse::<Key>()?],
+                Some(toml_edit::Value::Array(keys)) => keys
+                    .iter()
+                    .map(|key| match key.as_str() {
+                        Some(key) => key.parse::<Key>(),
+                        None => eyre::bail!("{name}: expected a key, got {key}"),
+                    })
+                    .collect::<Result<_>>()?,
+                _ => eyre::bail!("{name}: expected a key or a list of keys"),
+            };
+            self.bindings
+                .retain(|(key, c)| *c != command && !keys.contains(key));
+            self.bindings
+                .extend(keys.into_iter().map(|key| (key, command)));
+        }
+        Ok(())
+    }
+
+    pub fn command(&self, event: &KeyEvent) -> Option<Command> {
+        self.bindings
+            .iter()
+            .find(|(key, _)| key.matches(event))
+            .map(|(_, command)| *command)
+    }
+
+    pub fn keys(&self, command: Command) -> impl Iterator<Item = &Key> {
+        self.bindings
+            .iter()
+            .filter(move |(_, c)| *c == command)
+            .map(|(key, _)| key)
+    }
+
+    /// The keys of `command` for the help, like `q` or `<ctrl-c>/q`.
+    pub fn key_names(&self, command: Command) -> Option<String> {
+        let keys = self.keys(command).map(Key::to_string).collect::<Vec<_>>();
+        (!keys.is_empty()).then(|| keys.join("/"))
+    }
+
+    /// Help line spans for the commands available everywhere.
+    pub fn global_help(&self) -> Vec<Span<'static>> {
+        self.help(&Command::GLOBAL.map(|command| (command, command.label())))
+    }
+
+    /// Help line spans for commands with their labels. Unbound commands are
+    /// left out.
+    pub fn help(&self, commands: &[(Command, &str)]) -> Vec<Span<'static>> {
+        let mut help = vec![" ".set_style(theme().dim)];
+        for (command, label) in commands {
+            let Some(keys) = self.key_names(*command) else {
+                continue;
+            };
+            help.push(keys.set_style(theme().key));
+            help.push(format!(" {label} ").set_style(theme().dim));
+        }
+        help
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebind() {
+        let mut keymap = Keymap::default();
+        let doc = "[keys]\nrefresh = \"r\"\nquit = [\"q\", \"ctrl-c\"]\nadd = \"space\"\n"
+            .parse::<toml_edit::DocumentMut>()
+            .unwrap();
+        keymap.apply(&doc["keys"]).unwrap();
+
+        let key = |code, modifiers| KeyEvent::new(code, modifiers);
+        assert_eq!(
+            keymap.command(&key(KeyCode::Char('r'), KeyModifiers::NONE)),
+            Some(Command::Refresh)
+        );
+        assert_eq!(
+            keymap.command(&key(KeyCode::Char('g'), KeyModifiers::NONE)),
+            None
+        );
+        assert_eq!(
+            keymap.command(&key(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            Some(Command::Quit)
+        );
+        assert_eq!(
+            keymap.command(&key(KeyCode::Char('U'), KeyModifiers::SHIFT)),
+            Some(Command::UpgradeBreaking)
+        );
+        // space is taken from mark
+        assert_eq!(
+            keymap.command(&key(KeyCode::Char(' '), KeyModifiers::NONE)),
+            Some(Command::Add)
+        );
+        assert_eq!(keymap.key_names(Command::Mark), None);
+        assert_eq!(keymap.key_names(Command::Quit).unwrap(), "q/<ctrl-c>");
+
+        assert!("foo".parse::<Key>().is_err());
+        assert!("nope = \"x\"\n"
+            .parse::<toml_edit::DocumentMut>()
+            .map(|doc| Keymap::default().apply(doc.as_item()).is_err())
+            .unwrap());
+    }
+}
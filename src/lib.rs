@@ -4,15 +4,19 @@ extern crate tracing;
 mod action;
 mod args;
 pub mod cargo;
+pub mod commands;
 mod component;
 mod components;
+pub mod config;
+mod keymap;
 pub mod logging;
 mod mermaid;
 pub mod metadata;
 pub mod run;
+mod theme;
 pub mod tui;
 
-pub use args::Args;
+pub use args::{Args, Opt};
 pub use components::app::App;
 pub use logging::initialize_logging;
 pub use run::run_loop;
@@ -13,6 +13,10 @@ lazy_static! {
     pub static ref DATA_FOLDER: Option<PathBuf> = std::env::var(format!("{}_DATA", &*PROJECT_NAME))
         .ok()
         .map(PathBuf::from);
+    pub static ref CONFIG_FOLDER: Option<PathBuf> =
+        std::env::var(format!("{}_CONFIG", &*PROJECT_NAME))
+            .ok()
+            .map(PathBuf::from);
     pub static ref LOG_ENV: String = format!("{}_LOGLEVEL", &*PROJECT_NAME);
     pub static ref LOG_FILE: String = format!("{}.log", env!("CARGO_CRATE_NAME"));
 }
@@ -32,6 +36,17 @@ pub fn get_data_dir() -> PathBuf {
     directory
 }
 
+pub fn get_config_dir() -> PathBuf {
+    let directory = if let Some(s) = &*CONFIG_FOLDER {
+        s.clone()
+    } else if let Some(proj_dirs) = project_directory() {
+        proj_dirs.config_local_dir().to_path_buf()
+    } else {
+        PathBuf::from(".").join(".config")
+    };
+    directory
+}
+
 pub fn initialize_logging() -> Result<()> {
     let directory = get_data_dir();
     std::fs::create_dir_all(&directory)?;
@@ -1,4 +1,4 @@
-use cargo_oh_my_dependencies::{initialize_logging, run_loop, tui, Args};
+use cargo_oh_my_dependencies::{commands, initialize_logging, run_loop, tui, Args, Opt};
 
 fn main() {
     color_eyre::install().expect("color_eyre");
@@ -17,6 +17,11 @@ fn run() -> eyre::Result<()> {
     }));
 
     let args = <Args as clap::Parser>::parse();
+    let Args::Omd(Opt { command, .. }) = &args;
+    if let Some(command) = command.clone() {
+        return commands::run(command);
+    }
+
     let mut terminal = tui::Tui::new()?;
     run_loop(args, &mut terminal)?;
     Ok(())
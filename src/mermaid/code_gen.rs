@@ -2,6 +2,8 @@
 
 use eyre::Result;
 
+use crate::config::{GraphConfig, GraphFormat};
+
 static TEMPLATE: &str = r##"
 <!DOCTYPE html>
 <html lang="en">
@@ -106,12 +108,19 @@ impl Graph {
         src
     }
 
-    pub fn render_and_open(&self) -> Result<()> {
-        let html = render_mermaid(&self.render());
-        let temp_file = tempfile::Builder::new().suffix(".html").tempfile()?;
-
-        std::fs::write(temp_file.path(), html)?;
-        open::that(temp_file.path())?;
+    /// Writes the graph to a temp file in the configured format and opens it.
+    pub fn render_and_open(&self, config: &GraphConfig) -> Result<()> {
+        let (content, suffix) = match config.format {
+            GraphFormat::Html => (render_mermaid(&self.render()), ".html"),
+            GraphFormat::Mermaid => (self.render(), ".mmd"),
+        };
+        let temp_file = tempfile::Builder::new().suffix(suffix).tempfile()?;
+
+        std::fs::write(temp_file.path(), content)?;
+        match &config.open {
+            Some(program) => open::with(temp_file.path(), program)?,
+            None => open::that(temp_file.path())?,
+        }
         // std::thread::sleep(std::time::Duration::from_secs(3));
         let _ = temp_file.keep()?;
         Ok(())
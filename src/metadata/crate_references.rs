@@ -0,0 +1,197 @@
+use cargo_metadata::{Dependency, Package};
+use std::path::PathBuf;
+
+use super::unused_features::package_rust_files;
+
+/// A line in the sources of a package that refers to a crate.
+#[derive(Debug, Clone)]
+pub struct CrateReference {
+    pub path: PathBuf,
+    /// 1-based
+    pub line: usize,
+    pub text: String,
+}
+
+/// The name a dependency is referred to in code: its rename or the name of its
+/// library target, with `-` replaced by `_`.
+pub fn crate_identifier(dep: &Dependency, resolved: Option<&Package>) -> String {
+    let lib_name = resolved.and_then(|package| {
+        package
+            .targets
+            .iter()
+            .find(|t| {
+                t.kind
+                    .iter()
+                    .any(|k| matches!(k.as_str(), "lib" | "rlib" | "proc-macro"))
+            })
+            .map(|t| t.name.as_str())
+    });
+    dep.rename
+        .as_deref()
+        .or(lib_name)
+        .unwrap_or(&dep.name)
+        .replace('-', "_")
+}
+
+/// Lines in the sources of `package` that mention the crate `ident` as a path
+/// root (`ident::`, `use ident;`) or with `extern crate`. Comments and strings
+/// are skipped, macros and derives that expand to the crate's paths are not
+/// detected.
+pub fn find_crate_references(package: &Package, ident: &str) -> Vec<CrateReference> {
+    let mut references = Vec::new();
+    for path in package_rust_files(package) {
+        let Ok(source) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        for (i, line) in source.lines().enumerate() {
+            if !refers_to_crate(line, ident) {
+                continue;
+            }
+            references.push(CrateReference {
+                path: path.clone(),
+                line: i + 1,
+                text: line.trim().to_string(),
+            });
+        }
+    }
+    references
+}
+
+fn refers_to_crate(line: &str, ident: &str) -> bool {
+    let code = code(line);
+    let line = code.as_str();
+    let is_ident_char = |c: char| c.is_alphanumeric() || c == '_';
+    let mut rest = line;
+    let mut offset = 0;
+    while let Some(pos) = rest.find(ident) {
+        let start = offset + pos;
+        let end = start + ident.len();
+        offset = end;
+        rest = &line[end..];
+
+        let before = &line[..start];
+        let after = &line[end..];
+        if before.ends_with(is_ident_char) || after.starts_with(is_ident_char) {
+            continue;
+        }
+        if let Some(path) = before.strip_suffix("::") {
+            // `other::ident` is a path segment of something else, `::ident` is
+            // an absolute path to the crate
+            if path.ends_with(is_ident_char) || path.ends_with('>') {
+                continue;
+            }
+            return true;
+        }
+        let after = after.trim_start();
+        let before = before.trim_end();
+        if after.starts_with("::")
+            || before.ends_with("extern crate")
+            || (before.ends_with("use") && (after.starts_with(';') || after.starts_with("as ")))
+        {
+            return true;
+        }
+    }
+    false
+}
+
+/// `line` without the contents of its string literals and without a trailing
+/// `//` comment. Strings that span lines and raw strings are not recognized.
+fn code(line: &str) -> String {
+    let mut code = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '"' => {
+                    in_string = false;
+                    code.push(c);
+                }
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => {
+                in_string = true;
+                code.push(c);
+            }
+            '/' if chars.peek() == Some(&'/') => break,
+            // `'"'` doesn't start a string
+            '\'' if chars.clone().take(2).eq(['"', '\'']) => {
+                code.push(c);
+                code.extend(chars.by_ref().take(2));
+            }
+            _ => code.push(c),
+        }
+    }
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths() {
+        assert!(refers_to_crate("use x::foo;", "x"));
+        assert!(refers_to_crate("use x::{foo, bar};", "x"));
+        assert!(refers_to_crate("use x;", "x"));
+        assert!(refers_to_crate("use x as y;", "x"));
+        assert!(refers_to_crate("    let a = x::foo();", "x"));
+        assert!(refers_to_crate("impl ::x::Foo for Bar {}", "x"));
+        assert!(refers_to_crate("let s = Vec::<x::Foo>::new();", "x"));
+    }
+
+    #[test]
+    fn extern_crate() {
+        assert!(refers_to_crate("extern crate x;", "x"));
+        assert!(refers_to_crate("extern crate x as y;", "x"));
+        // the alias is not the crate
+        assert!(!refers_to_crate("extern crate x as y;", "y"));
+    }
+
+    #[test]
+    fn other_identifiers() {
+        assert!(!refers_to_crate("use xy::foo;", "x"));
+        assert!(!refers_to_crate("use my_x::foo;", "x"));
+        assert!(!refers_to_crate("use other::x::foo;", "x"));
+        assert!(!refers_to_crate("let x = 1;", "x"));
+        assert!(!refers_to_crate("x.foo();", "x"));
+        assert!(!refers_to_crate("<T as Trait>::x::foo()", "x"));
+    }
+
+    #[test]
+    fn comments_and_strings() {
+        assert!(!refers_to_crate("// use x::foo;", "x"));
+        assert!(!refers_to_crate("/// see x::foo", "x"));
+        assert!(!refers_to_crate("let a = 1; // x::foo()", "x"));
+        assert!(!refers_to_crate(r#"println!("x::foo");"#, "x"));
+        assert!(!refers_to_crate(r#"let s = "a \" x::foo";"#, "x"));
+        assert!(refers_to_crate(r#"x::foo("//");"#, "x"));
+        assert!(refers_to_crate(r#"let q = '"'; x::foo();"#, "x"));
+    }
+
+    #[test]
+    fn hyphenated_name() {
+        let metadata = cargo_metadata::MetadataCommand::new()
+            .manifest_path(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"))
+            .no_deps()
+            .exec()
+            .expect("could not get metadata");
+        let package = metadata.workspace_packages()[0];
+        let dep = package
+            .dependencies
+            .iter()
+            .find(|dep| dep.name == "tui-tree-widget")
+            .unwrap();
+
+        let ident = crate_identifier(dep, None);
+        assert_eq!(ident, "tui_tree_widget");
+        assert!(refers_to_crate("use tui_tree_widget::TreeState;", &ident));
+        assert!(!refers_to_crate("use tui-tree-widget::TreeState;", &ident));
+    }
+}
@@ -1,11 +1,23 @@
-use cargo_metadata::{DependencyKind, PackageId};
+use cargo_metadata::{semver::VersionReq, Dependency, DependencyKind, Package, PackageId};
+use std::collections::{BTreeMap, HashSet};
 
-use super::{workspace_info::WorkspaceInfo, Features};
+use crate::config::Filter;
+
+use super::{
+    package_resolver::node_dep_kind,
+    unused_features::{FeatureUsage, SourceScanner},
+    upgrades::Upgrade,
+    workspace_info::WorkspaceInfo,
+    Features, PackageResolver,
+};
 
 #[derive(Default, Debug, Clone)]
 pub struct DepTree {
     pub items: Vec<DepTreeNode>,
     pub children: Vec<usize>,
+    /// Packages whose transitive dependencies were already loaded somewhere in
+    /// the tree. Used to mark repeated packages like `cargo tree` does with `(*)`.
+    loaded: HashSet<PackageId>,
 }
 
 #[derive(Debug, Clone)]
@@ -15,24 +27,87 @@ pub enum DepTreeNode {
         children: Vec<usize>,
     },
 
+    /// The `[features]` table of a workspace package.
+    PackageFeatures {
+        children: Vec<usize>,
+    },
+
+    /// A feature of a workspace package.
+    PackageFeature {
+        name: String,
+        children: Vec<usize>,
+    },
+
+    /// An entry of a [`DepTreeNode::PackageFeature`]: another feature,
+    /// `dep:name` or `name/feature`.
+    PackageFeatureEntry {
+        value: String,
+    },
+
+    /// Root of the tree built by [`DepTree::build_by_dependency`], the
+    /// members that declare the dependency are its children.
+    DependencyGroup {
+        name: String,
+        children: Vec<usize>,
+    },
+
     UnresolvedDependency {
         name: String,
         kind: DependencyKind,
+        target: Option<String>,
     },
 
     Dependency {
+        id: PackageId,
         name: String,
         kind: DependencyKind,
+        /// The platform of a `[target.'cfg(..)'.*]` table.
+        target: Option<String>,
+        req: VersionReq,
+        /// Newer versions in the registry index, only for registry
+        /// dependencies.
+        upgrade: Upgrade,
         children: Vec<usize>,
     },
 
+    TransitiveDependency {
+        id: PackageId,
+        name: String,
+        version: String,
+        kind: DependencyKind,
+        expansion: Expansion,
+        repeated: bool,
+        children: Vec<usize>,
+    },
+
+    /// The `default-features` setting of a dependency whose package has a
+    /// `default` feature.
+    DefaultFeatures {
+        enabled: bool,
+        /// The entries of the `default` feature.
+        features: Vec<String>,
+    },
+
     Feature {
         name: String,
         status: FeatureStatus,
         deps: Vec<String>,
+        /// Only checked for enabled features.
+        usage: FeatureUsage,
     },
 }
 
+/// Load state of the dependencies of a [`DepTreeNode::TransitiveDependency`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expansion {
+    /// The package has dependencies that were not loaded yet.
+    Pending,
+    /// The dependencies are loaded (or there are none).
+    Loaded,
+    /// The package is one of its own ancestors, expanding it would not end.
+    Cycle,
+}
+
 #[derive(Debug, Clone)]
 pub enum FeatureStatus {
     Enabled,
@@ -51,33 +126,74 @@ impl DepTreeNode {
         }
     }
 
-    fn resolved(name: impl ToString, kind: DependencyKind) -> Self {
-        DepTreeNode::Dependency {
+    fn package_feature(name: impl ToString) -> Self {
+        DepTreeNode::PackageFeature {
             name: name.to_string(),
-            kind,
             children: Vec::new(),
         }
     }
 
-    fn unresolved(name: impl ToString, kind: DependencyKind) -> Self {
+    fn resolved(id: PackageId, dep: &Dependency, upgrade: Upgrade) -> Self {
+        DepTreeNode::Dependency {
+            id,
+            name: dep.name.clone(),
+            kind: dep.kind,
+            target: dep.target.as_ref().map(ToString::to_string),
+            req: dep.req.clone(),
+            upgrade,
+            children: Vec::new(),
+        }
+    }
+
+    fn unresolved(dep: &Dependency) -> Self {
         DepTreeNode::UnresolvedDependency {
+            name: dep.name.clone(),
+            kind: dep.kind,
+            target: dep.target.as_ref().map(ToString::to_string),
+        }
+    }
+
+    fn transitive(
+        id: PackageId,
+        name: impl ToString,
+        version: impl ToString,
+        kind: DependencyKind,
+        expansion: Expansion,
+        repeated: bool,
+    ) -> Self {
+        DepTreeNode::TransitiveDependency {
+            id,
             name: name.to_string(),
+            version: version.to_string(),
             kind,
+            expansion,
+            repeated,
+            children: Vec::new(),
         }
     }
 
-    fn feature(name: impl ToString, status: FeatureStatus, deps: Vec<String>) -> Self {
+    fn feature(
+        name: impl ToString,
+        status: FeatureStatus,
+        deps: Vec<String>,
+        usage: FeatureUsage,
+    ) -> Self {
         DepTreeNode::Feature {
             name: name.to_string(),
             status,
             deps,
+            usage,
         }
     }
 
-    fn children(&self) -> Option<&Vec<usize>> {
+    pub fn children(&self) -> Option<&Vec<usize>> {
         match self {
             DepTreeNode::WorkspacePackage { children, .. } => Some(children),
+            DepTreeNode::PackageFeatures { children } => Some(children),
+            DepTreeNode::PackageFeature { children, .. } => Some(children),
+            DepTreeNode::DependencyGroup { children, .. } => Some(children),
             DepTreeNode::Dependency { children, .. } => Some(children),
+            DepTreeNode::TransitiveDependency { children, .. } => Some(children),
             _ => None,
         }
     }
@@ -88,19 +204,50 @@ impl DepTreeNode {
                 children: ref mut c,
                 ..
             } => *c = children,
+            DepTreeNode::PackageFeatures {
+                children: ref mut c,
+            } => *c = children,
+            DepTreeNode::PackageFeature {
+                children: ref mut c,
+                ..
+            } => *c = children,
+            DepTreeNode::DependencyGroup {
+                children: ref mut c,
+                ..
+            } => *c = children,
             DepTreeNode::Dependency {
                 children: ref mut c,
                 ..
             } => *c = children,
+            DepTreeNode::TransitiveDependency {
+                children: ref mut c,
+                ..
+            } => *c = children,
             _ => {}
         }
     }
 
+    /// Identifies the node among its siblings. It only depends on what the
+    /// node is, not on where it is in the tree, so it stays the same when the
+    /// tree is rebuilt.
     pub fn widget_id(&self) -> String {
         match self {
             DepTreeNode::WorkspacePackage { id, .. } => id.to_string(),
-            DepTreeNode::UnresolvedDependency { name, kind, .. } => format!("{name}:{kind}"),
-            DepTreeNode::Dependency { name, kind, .. } => format!("{name}:{kind}"),
+            DepTreeNode::PackageFeatures { .. } => "[features]".to_string(),
+            DepTreeNode::PackageFeature { name, .. } => format!("[features]:{name}"),
+            DepTreeNode::PackageFeatureEntry { value } => value.clone(),
+            DepTreeNode::DependencyGroup { name, .. } => format!("[dependency]:{name}"),
+            DepTreeNode::UnresolvedDependency {
+                name, kind, target, ..
+            }
+            | DepTreeNode::Dependency {
+                name, kind, target, ..
+            } => match target {
+                Some(target) => format!("{name}:{kind}:{target}"),
+                None => format!("{name}:{kind}"),
+            },
+            DepTreeNode::TransitiveDependency { id, .. } => id.to_string(),
+            DepTreeNode::DefaultFeatures { .. } => "[default-features]".to_string(),
             DepTreeNode::Feature { name, .. } => name.clone(),
         }
     }
@@ -145,10 +292,12 @@ impl DepTreeNode {
 }
 
 impl DepTree {
-    pub fn build(workspace_info: &WorkspaceInfo) -> Self {
+    pub fn build(workspace_info: &WorkspaceInfo, filter: &Filter) -> Self {
         let resolver = workspace_info.dependency_resolver();
+        let mut scanner = workspace_info.sources.lock().unwrap();
         let mut items = Vec::new();
         let mut children = Vec::new();
+        let mut loaded = HashSet::new();
 
         for p in &workspace_info.workspace_packages() {
             let i = items.len();
@@ -157,41 +306,283 @@ impl DepTree {
             let mut children = Vec::new();
             items.push(DepTreeNode::package(p.id.clone()));
 
-            for dep in &p.dependencies {
-                let i = items.len();
-                children.push(i);
-                let Some(dep_package) = resolver.resolve_dependency(&p.id, &dep.name) else {
-                    warn!("Could not resolve package {}", dep.name);
-                    items.push(DepTreeNode::unresolved(&dep.name, dep.kind));
-                    continue;
-                };
-                items.push(DepTreeNode::resolved(dep.name.clone(), dep.kind));
-
-                let mut children = Vec::new();
-                let features = Features::new(dep, dep_package);
-                let active_features = features.active_features();
-                let indirectly_active_features = features.indirectly_active_features();
-
-                for (feature, feature_deps) in dep_package.features.iter() {
-                    let status = if active_features.contains(feature) {
-                        FeatureStatus::Enabled
-                    } else if indirectly_active_features.contains(feature) {
-                        FeatureStatus::IndirectlyEnabled
-                    } else {
-                        FeatureStatus::Disabled
-                    };
-
-                    children.push(items.len());
-                    items.push(DepTreeNode::feature(feature, status, feature_deps.clone()));
-                }
+            let features_i = items.len();
+            children.push(features_i);
+            items.push(DepTreeNode::PackageFeatures {
+                children: Vec::new(),
+            });
+            let mut feature_children = Vec::new();
+            for (feature, entries) in &p.features {
+                let feature_i = items.len();
+                feature_children.push(feature_i);
+                items.push(DepTreeNode::package_feature(feature));
+                let entry_children = entries
+                    .iter()
+                    .map(|value| {
+                        items.push(DepTreeNode::PackageFeatureEntry {
+                            value: value.clone(),
+                        });
+                        items.len() - 1
+                    })
+                    .collect();
+                items[feature_i].set_children(entry_children);
+            }
+            items[features_i].set_children(feature_children);
 
-                items.get_mut(i).unwrap().set_children(children);
+            for dep in p.dependencies.iter().filter(|dep| filter.shows(dep.kind)) {
+                children.push(Self::push_dependency(
+                    &mut items,
+                    &mut loaded,
+                    &mut scanner,
+                    &resolver,
+                    workspace_info,
+                    p,
+                    dep,
+                ));
             }
 
             items.get_mut(i).unwrap().set_children(children);
         }
 
-        DepTree { items, children }
+        DepTree {
+            items,
+            children,
+            loaded,
+        }
+    }
+
+    /// Builds the pivot of [`DepTree::build`]: one [`DepTreeNode::DependencyGroup`]
+    /// per dependency name, with the workspace members that declare it as
+    /// children. Below a member the dependency looks like in the package
+    /// tree, so it can be edited the same way.
+    pub fn build_by_dependency(workspace_info: &WorkspaceInfo, filter: &Filter) -> Self {
+        let resolver = workspace_info.dependency_resolver();
+        let mut scanner = workspace_info.sources.lock().unwrap();
+        let mut items = Vec::new();
+        let mut children = Vec::new();
+        let mut loaded = HashSet::new();
+
+        let members = workspace_info.workspace_packages();
+        let mut declarations = BTreeMap::<&str, Vec<(&Package, Vec<&Dependency>)>>::new();
+        for p in &members {
+            for dep in p.dependencies.iter().filter(|dep| filter.shows(dep.kind)) {
+                let members = declarations.entry(&dep.name).or_default();
+                match members.last_mut() {
+                    Some((member, deps)) if member.id == p.id => deps.push(dep),
+                    _ => members.push((p, vec![dep])),
+                }
+            }
+        }
+
+        for (name, members) in declarations {
+            let group_i = items.len();
+            children.push(group_i);
+            items.push(DepTreeNode::DependencyGroup {
+                name: name.to_string(),
+                children: Vec::new(),
+            });
+
+            let mut group_children = Vec::new();
+            for (p, deps) in members {
+                let member_i = items.len();
+                group_children.push(member_i);
+                items.push(DepTreeNode::package(p.id.clone()));
+                let member_children = deps
+                    .into_iter()
+                    .map(|dep| {
+                        Self::push_dependency(
+                            &mut items,
+                            &mut loaded,
+                            &mut scanner,
+                            &resolver,
+                            workspace_info,
+                            p,
+                            dep,
+                        )
+                    })
+                    .collect();
+                items[member_i].set_children(member_children);
+            }
+            items[group_i].set_children(group_children);
+        }
+
+        DepTree {
+            items,
+            children,
+            loaded,
+        }
+    }
+
+    /// Pushes the node of the dependency `dep` of the workspace member `p`
+    /// with its features and direct dependencies. Returns its index.
+    fn push_dependency(
+        items: &mut Vec<DepTreeNode>,
+        loaded: &mut HashSet<PackageId>,
+        scanner: &mut SourceScanner,
+        resolver: &PackageResolver,
+        workspace_info: &WorkspaceInfo,
+        p: &Package,
+        dep: &Dependency,
+    ) -> usize {
+        let i = items.len();
+        let Some(dep_package) = resolver.resolve_dependency(&p.id, &dep.name) else {
+            warn!("Could not resolve package {}", dep.name);
+            items.push(DepTreeNode::unresolved(dep));
+            return i;
+        };
+        let is_registry = dep
+            .source
+            .as_deref()
+            .is_some_and(|source| source.starts_with("registry+") || source.starts_with("sparse+"));
+        let upgrade = if is_registry {
+            workspace_info
+                .upgrades
+                .upgrade(&dep_package.name, &dep_package.version)
+        } else {
+            Upgrade::default()
+        };
+        items.push(DepTreeNode::resolved(dep_package.id.clone(), dep, upgrade));
+
+        let mut children = Vec::new();
+        let features = Features::new(dep, dep_package);
+        let active_features = features.active_features();
+        let indirectly_active_features = features.indirectly_active_features();
+
+        if let Some(default) = dep_package.features.get("default") {
+            children.push(items.len());
+            items.push(DepTreeNode::DefaultFeatures {
+                enabled: dep.uses_default_features || dep.features.iter().any(|f| f == "default"),
+                features: default.clone(),
+            });
+        }
+
+        for (feature, feature_deps) in dep_package.features.iter() {
+            // shown as the default features control
+            if feature == "default" {
+                continue;
+            }
+            let mut usage = FeatureUsage::Unknown;
+            let status = if active_features.contains(feature) {
+                usage = scanner.usage(p, dep_package, feature);
+                FeatureStatus::Enabled
+            } else if indirectly_active_features.contains(feature) {
+                FeatureStatus::IndirectlyEnabled
+            } else {
+                FeatureStatus::Disabled
+            };
+
+            children.push(items.len());
+            items.push(DepTreeNode::feature(
+                feature,
+                status,
+                feature_deps.clone(),
+                usage,
+            ));
+        }
+
+        let ancestors = [p.id.clone(), dep_package.id.clone()];
+        loaded.insert(dep_package.id.clone());
+        children.extend(Self::push_transitive_dependencies(
+            items,
+            loaded,
+            resolver,
+            &dep_package.id,
+            &ancestors,
+        ));
+
+        items.get_mut(i).unwrap().set_children(children);
+        i
+    }
+
+    /// Loads the dependencies of the [`DepTreeNode::TransitiveDependency`] at
+    /// index `i` if they are still pending. Returns `true` if the tree changed.
+    pub fn expand(&mut self, i: usize, resolver: &PackageResolver) -> bool {
+        let Some(DepTreeNode::TransitiveDependency {
+            id,
+            expansion: Expansion::Pending,
+            ..
+        }) = self.items.get(i)
+        else {
+            return false;
+        };
+        let id = id.clone();
+
+        let mut ancestors = self
+            .ancestors(i)
+            .into_iter()
+            .filter_map(|i| match &self.items[i] {
+                DepTreeNode::WorkspacePackage { id, .. }
+                | DepTreeNode::Dependency { id, .. }
+                | DepTreeNode::TransitiveDependency { id, .. } => Some(id.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        ancestors.push(id.clone());
+
+        self.loaded.insert(id.clone());
+        let children = Self::push_transitive_dependencies(
+            &mut self.items,
+            &self.loaded,
+            resolver,
+            &id,
+            &ancestors,
+        );
+
+        let node = &mut self.items[i];
+        node.set_children(children);
+        if let DepTreeNode::TransitiveDependency { expansion, .. } = node {
+            *expansion = Expansion::Loaded;
+        }
+
+        true
+    }
+
+    /// Indexes of the nodes on the path from a root to the node at `i`,
+    /// excluding `i` itself.
+    fn ancestors(&self, i: usize) -> Vec<usize> {
+        let mut path = Vec::new();
+        let mut current = i;
+        while let Some(parent) = self.items.iter().position(|node| {
+            node.children()
+                .map(|children| children.contains(&current))
+                .unwrap_or(false)
+        }) {
+            path.push(parent);
+            current = parent;
+        }
+        path.reverse();
+        path
+    }
+
+    fn push_transitive_dependencies(
+        items: &mut Vec<DepTreeNode>,
+        loaded: &HashSet<PackageId>,
+        resolver: &PackageResolver,
+        id: &PackageId,
+        ancestors: &[PackageId],
+    ) -> Vec<usize> {
+        let mut children = Vec::new();
+        for (dep, package) in resolver.dependencies(id) {
+            let expansion = if ancestors.contains(&package.id) {
+                Expansion::Cycle
+            } else if resolver.dependencies(&package.id).is_empty() {
+                Expansion::Loaded
+            } else {
+                Expansion::Pending
+            };
+            let repeated = expansion == Expansion::Cycle || loaded.contains(&package.id);
+
+            children.push(items.len());
+            items.push(DepTreeNode::transitive(
+                package.id.clone(),
+                &package.name,
+                &package.version,
+                node_dep_kind(dep),
+                expansion,
+                repeated,
+            ));
+        }
+        children
     }
 
     pub fn visit(&self, visitor: &mut dyn FnMut(&DepTreeNode, Option<&DepTreeNode>)) {
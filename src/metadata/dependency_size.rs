@@ -0,0 +1,93 @@
+use cargo_metadata::{DependencyKind, Metadata, Package, PackageId};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::{package_resolver::node_dep_kind, unused_features::rust_files, PackageResolver};
+
+/// A direct dependency of a package together with everything it pulls in.
+#[derive(Debug, Clone)]
+pub struct DependencySize {
+    pub id: PackageId,
+    pub name: String,
+    pub version: String,
+    pub kind: DependencyKind,
+    /// The dependency and all packages reachable from it, each counted once.
+    pub packages: Vec<PackageId>,
+}
+
+/// Lines of the library sources of packages, counted on demand and cached.
+#[derive(Debug, Clone, Default)]
+pub struct SourceLines {
+    counts: HashMap<PackageId, usize>,
+}
+
+impl SourceLines {
+    pub fn count(&mut self, package: &Package) -> usize {
+        if let Some(count) = self.counts.get(&package.id) {
+            return *count;
+        }
+
+        let src_dir = package
+            .targets
+            .iter()
+            .find(|t| t.kind.iter().any(|k| k == "lib" || k == "proc-macro"))
+            .and_then(|lib| lib.src_path.parent());
+        let count = src_dir
+            .map(|dir| rust_files(dir.as_std_path()))
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|file| std::fs::read_to_string(file).ok())
+            .map(|source| source.lines().count())
+            .sum();
+
+        self.counts.insert(package.id.clone(), count);
+        count
+    }
+}
+
+impl DependencySize {
+    /// Total source lines of [`Self::packages`].
+    pub fn lines(&self, metadata: &Metadata, source_lines: &mut SourceLines) -> usize {
+        let resolver = PackageResolver::new(metadata);
+        self.packages
+            .iter()
+            .filter_map(|id| resolver.package(id))
+            .map(|package| source_lines.count(package))
+            .sum()
+    }
+}
+
+/// The direct dependencies of `package` and what each of them brings in.
+pub fn dependency_sizes(metadata: &Metadata, package: &PackageId) -> Vec<DependencySize> {
+    let resolver = PackageResolver::new(metadata);
+    resolver
+        .dependencies(package)
+        .into_iter()
+        .map(|(node_dep, dependency)| DependencySize {
+            id: dependency.id.clone(),
+            name: dependency.name.clone(),
+            version: dependency.version.to_string(),
+            kind: node_dep_kind(node_dep),
+            packages: reachable(&resolver, &dependency.id),
+        })
+        .collect()
+}
+
+/// `id` and the packages it depends on, recursively. Dev-dependencies are not
+/// followed, they are not built for dependents.
+fn reachable(resolver: &PackageResolver, id: &PackageId) -> Vec<PackageId> {
+    let mut seen = HashSet::from([id]);
+    let mut queue = VecDeque::from([id]);
+    while let Some(id) = queue.pop_front() {
+        for (node_dep, package) in resolver.dependencies(id) {
+            let dev_only = !node_dep.dep_kinds.is_empty()
+                && node_dep
+                    .dep_kinds
+                    .iter()
+                    .all(|k| k.kind == DependencyKind::Development);
+            if !dev_only && seen.insert(&package.id) {
+                queue.push_back(&package.id);
+            }
+        }
+    }
+    seen.into_iter().cloned().collect()
+}
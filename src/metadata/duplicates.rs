@@ -0,0 +1,226 @@
+use cargo_metadata::{semver::Version, Metadata, Package, PackageId};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+use super::{inverse_tree::DependencyEdge, PackageResolver};
+
+/// A crate that is part of the resolved dependency graph in more than one
+/// version.
+#[derive(Debug, Clone)]
+pub struct DuplicateCrate {
+    pub name: String,
+    /// Sorted from oldest to newest.
+    pub versions: Vec<DuplicateVersion>,
+    pub suggestions: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DuplicateVersion {
+    pub id: PackageId,
+    pub version: Version,
+    /// The shortest path from each workspace member that pulls in this version.
+    pub paths: Vec<DependencyPath>,
+}
+
+/// A chain of dependencies starting at a workspace member.
+#[derive(Debug, Clone)]
+pub struct DependencyPath {
+    pub steps: Vec<PathStep>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PathStep {
+    pub id: PackageId,
+    pub name: String,
+    pub version: Version,
+    /// How the previous package of the path depends on this one. `None` for
+    /// the workspace member the path starts at.
+    pub edge: Option<DependencyEdge>,
+}
+
+impl DependencyPath {
+    pub fn member(&self) -> &PathStep {
+        &self.steps[0]
+    }
+
+    pub fn describe(&self) -> String {
+        self.steps
+            .iter()
+            .map(|step| {
+                let edge = step
+                    .edge
+                    .as_ref()
+                    .map(|e| e.describe())
+                    .filter(|e| !e.is_empty());
+                match edge {
+                    Some(edge) => format!("{} v{} {edge}", step.name, step.version),
+                    None => format!("{} v{}", step.name, step.version),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" → ")
+    }
+}
+
+/// Finds every crate with multiple resolved versions.
+pub fn find_duplicates(metadata: &Metadata) -> Vec<DuplicateCrate> {
+    let resolver = PackageResolver::new(metadata);
+    let workspace_members = metadata.workspace_members.iter().collect::<HashSet<_>>();
+
+    let mut by_name = BTreeMap::<&str, Vec<&Package>>::new();
+    for package in &metadata.packages {
+        // only count packages that are part of the resolved graph
+        if resolver.node(&package.id).is_some() {
+            by_name.entry(&package.name).or_default().push(package);
+        }
+    }
+
+    by_name
+        .into_iter()
+        .filter(|(_, packages)| packages.len() > 1)
+        .map(|(name, mut packages)| {
+            packages.sort_by(|a, b| a.version.cmp(&b.version));
+            let versions = packages
+                .iter()
+                .map(|p| DuplicateVersion {
+                    id: p.id.clone(),
+                    version: p.version.clone(),
+                    paths: shortest_paths(&resolver, &workspace_members, &p.id),
+                })
+                .collect::<Vec<_>>();
+            let suggestions = suggestions(name, &versions);
+            DuplicateCrate {
+                name: name.to_string(),
+                versions,
+                suggestions,
+            }
+        })
+        .collect()
+}
+
+/// Shortest path from every workspace member that depends on `target`,
+/// found with a breadth first search over the reverse dependency graph.
+fn shortest_paths(
+    resolver: &PackageResolver,
+    workspace_members: &HashSet<&PackageId>,
+    target: &PackageId,
+) -> Vec<DependencyPath> {
+    // package -> (dependency it was reached from, edge to that dependency)
+    let mut reached_from = HashMap::<&PackageId, Option<(&PackageId, DependencyEdge)>>::new();
+    let Some(target_package) = resolver.package(target) else {
+        return Vec::new();
+    };
+    let target = &target_package.id;
+    reached_from.insert(target, None);
+
+    let mut members = Vec::new();
+    let mut queue = VecDeque::from([target]);
+    while let Some(id) = queue.pop_front() {
+        if workspace_members.contains(id) {
+            members.push(id);
+        }
+        let Some(package) = resolver.package(id) else {
+            continue;
+        };
+        for (dependent_id, node_dep) in resolver.dependents(id) {
+            if reached_from.contains_key(dependent_id) {
+                continue;
+            }
+            let Some(dependent) = resolver.package(dependent_id) else {
+                continue;
+            };
+            let active_features = resolver
+                .node(dependent_id)
+                .map(|n| n.features.as_slice())
+                .unwrap_or_default();
+            let edge = DependencyEdge::new(dependent, active_features, node_dep, package);
+            reached_from.insert(dependent_id, Some((id, edge)));
+            queue.push_back(dependent_id);
+        }
+    }
+
+    members
+        .into_iter()
+        .filter_map(|member| {
+            let mut steps = Vec::new();
+            let mut current = member;
+            let mut edge_to_current = None;
+            loop {
+                let package = resolver.package(current)?;
+                steps.push(PathStep {
+                    id: package.id.clone(),
+                    name: package.name.clone(),
+                    version: package.version.clone(),
+                    edge: edge_to_current.take(),
+                });
+                match reached_from.get(current)? {
+                    Some((next, edge)) => {
+                        edge_to_current = Some(edge.clone());
+                        current = next;
+                    }
+                    None => break,
+                }
+            }
+            Some(DependencyPath { steps })
+        })
+        .collect()
+}
+
+/// Heuristics for what would unify the versions: dependencies that pull in an
+/// older version need a bump, and optional dependencies that are only enabled
+/// by a feature could be turned off.
+fn suggestions(name: &str, versions: &[DuplicateVersion]) -> Vec<String> {
+    let Some(newest) = versions.last() else {
+        return Vec::new();
+    };
+
+    let mut suggestions = BTreeSet::new();
+    for outdated in &versions[..versions.len() - 1] {
+        for path in &outdated.paths {
+            let member = &path.member().name;
+            match &path.steps[..] {
+                [_, _] => {
+                    suggestions.insert(format!(
+                        "bump {name} in {member} from {} to {}",
+                        outdated.version, newest.version
+                    ));
+                }
+                [_, direct, _] => {
+                    suggestions.insert(format!(
+                        "bump {} in {member}: v{} depends on {name} v{}",
+                        direct.name, direct.version, outdated.version
+                    ));
+                }
+                [_, direct, .., dependent, _] => {
+                    suggestions.insert(format!(
+                        "bump {} in {member}: it pulls in {name} v{} via {} v{}",
+                        direct.name, outdated.version, dependent.name, dependent.version
+                    ));
+                }
+                _ => {}
+            }
+
+            // Optional dependencies on the path could be disabled instead.
+            for window in path.steps.windows(2) {
+                let [dependent, dependency] = window else {
+                    continue;
+                };
+                let Some(edge) = &dependency.edge else {
+                    continue;
+                };
+                if edge.enabled_by.is_empty() {
+                    continue;
+                }
+                let features = edge.enabled_by.iter().cloned().collect::<Vec<_>>();
+                suggestions.insert(format!(
+                    "{name} v{} is only pulled in through optional dependency {} of {}, enabled by feature {}",
+                    outdated.version,
+                    dependency.name,
+                    dependent.name,
+                    features.join(", ")
+                ));
+            }
+        }
+    }
+
+    suggestions.into_iter().collect()
+}
@@ -0,0 +1,377 @@
+use cargo_metadata::Package;
+use std::path::PathBuf;
+
+/// Documentation of a feature gathered from the local source of a package.
+#[derive(Debug, Clone, Default)]
+pub struct FeatureDocs {
+    /// Comments above (and next to) the feature in the `[features]` table.
+    pub comments: Vec<String>,
+    /// What `[package.metadata.docs.rs]` says about the feature.
+    pub docs_rs: Option<String>,
+    /// Paragraphs, list items or table rows of the README's features section
+    /// that mention the feature.
+    pub readme: Vec<String>,
+}
+
+impl FeatureDocs {
+    pub fn load(package: &Package, feature: &str) -> Self {
+        Self {
+            comments: manifest_comments(package, feature),
+            docs_rs: docs_rs(package, feature),
+            readme: readme_mentions(package, feature),
+        }
+    }
+
+    /// The page docs.rs renders for the features of the package.
+    pub fn docs_rs_url(package: &Package) -> String {
+        format!(
+            "https://docs.rs/crate/{}/{}/features",
+            package.name, package.version
+        )
+    }
+}
+
+/// Comments of the feature in the manifest. Packages from a registry come with
+/// a normalized `Cargo.toml` without comments, the original one is kept as
+/// `Cargo.toml.orig`.
+fn manifest_comments(package: &Package, feature: &str) -> Vec<String> {
+    let manifest_path = package.manifest_path.as_std_path();
+    let original = manifest_path.with_extension("toml.orig");
+    let path = if original.exists() {
+        original
+    } else {
+        manifest_path.to_path_buf()
+    };
+
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    let Ok(doc) = content.parse::<toml_edit::DocumentMut>() else {
+        warn!("could not parse {path:?}");
+        return Vec::new();
+    };
+    let Some(features) = doc.get("features").and_then(|f| f.as_table_like()) else {
+        return Vec::new();
+    };
+    let Some((key, value)) = features.get_key_value(feature) else {
+        return Vec::new();
+    };
+
+    let mut comments = comment_lines(key.leaf_decor().prefix().and_then(|p| p.as_str()));
+    let suffix = value
+        .as_value()
+        .and_then(|v| v.decor().suffix())
+        .and_then(|s| s.as_str());
+    comments.extend(comment_lines(suffix));
+    comments
+}
+
+/// The comment lines of a toml decor, e.g. `# foo` or `## foo` (the style of
+/// the `document-features` crate). Comments separated from the key by a blank
+/// line usually belong to something else and are dropped.
+fn comment_lines(decor: Option<&str>) -> Vec<String> {
+    let Some(decor) = decor else {
+        return Vec::new();
+    };
+    let mut lines = Vec::new();
+    for line in decor.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            lines.clear();
+            continue;
+        }
+        if let Some(comment) = line.strip_prefix('#') {
+            lines.push(comment.trim_start_matches('#').trim().to_string());
+        }
+    }
+    lines
+}
+
+fn docs_rs(package: &Package, feature: &str) -> Option<String> {
+    let config = package.metadata.get("docs")?.get("rs")?;
+    if config.get("all-features").and_then(|v| v.as_bool()) == Some(true) {
+        return Some("docs.rs documents all features".to_string());
+    }
+    let features = config
+        .get("features")
+        .and_then(|f| f.as_array())
+        .map(|features| {
+            features
+                .iter()
+                .filter_map(|f| f.as_str())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    if features.contains(&feature) {
+        return Some("docs.rs documents this feature".to_string());
+    }
+    let no_default = config.get("no-default-features").and_then(|v| v.as_bool()) == Some(true);
+    match (features.is_empty(), no_default) {
+        (true, false) => None,
+        (true, true) => Some("docs.rs builds without default features".to_string()),
+        (false, _) => Some(format!(
+            "docs.rs builds with features {}",
+            features.join(", ")
+        )),
+    }
+}
+
+fn readme_path(package: &Package) -> Option<PathBuf> {
+    let dir = package.manifest_path.parent()?.as_std_path();
+    if let Some(readme) = &package.readme {
+        let readme = readme.as_std_path();
+        return Some(if readme.is_absolute() {
+            readme.to_path_buf()
+        } else {
+            dir.join(readme)
+        });
+    }
+    ["README.md", "README", "readme.md"]
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.exists())
+}
+
+fn readme_mentions(package: &Package, feature: &str) -> Vec<String> {
+    let Some(path) = readme_path(package) else {
+        return Vec::new();
+    };
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    features_section_mentions(&content, feature)
+}
+
+/// Chunks of the markdown sections with "feature" in their heading that
+/// mention `feature` as code (`` `feature` ``) or in bold.
+fn features_section_mentions(markdown: &str, feature: &str) -> Vec<String> {
+    let patterns = [format!("`{feature}`"), format!("**{feature}**")];
+    let mut mentions = Vec::new();
+
+    // heading level of the features section we are in
+    let mut section_level = None;
+    let mut chunk = Vec::new();
+    let mut in_code_block = false;
+
+    let mut flush = |chunk: &mut Vec<String>| {
+        let text = chunk.join(" ");
+        chunk.clear();
+        if patterns.iter().any(|p| text.contains(p.as_str())) {
+            mentions.push(text);
+        }
+    };
+
+    for line in markdown.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        if let Some(level) = heading_level(trimmed) {
+            flush(&mut chunk);
+            if section_level.is_some_and(|section| level <= section) {
+                section_level = None;
+            }
+            if section_level.is_none() && trimmed.to_lowercase().contains("feature") {
+                section_level = Some(level);
+            }
+            continue;
+        }
+        if section_level.is_none() {
+            continue;
+        }
+
+        if trimmed.is_empty()
+            || trimmed.starts_with("- ")
+            || trimmed.starts_with("* ")
+            || trimmed.starts_with('|')
+        {
+            flush(&mut chunk);
+        }
+        if trimmed.starts_with('|') {
+            let row = table_row(trimmed);
+            if !row.is_empty() {
+                chunk.push(row);
+            }
+            continue;
+        }
+        if !trimmed.is_empty() {
+            chunk.push(trimmed.to_string());
+        }
+    }
+    flush(&mut chunk);
+
+    mentions
+}
+
+fn heading_level(line: &str) -> Option<usize> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if level > 0 && line[level..].starts_with(' ') {
+        Some(level)
+    } else {
+        None
+    }
+}
+
+/// The cells of a markdown table row, separator rows are empty.
+fn table_row(line: &str) -> String {
+    line.split('|')
+        .map(str::trim)
+        .filter(|cell| !cell.is_empty() && !cell.chars().all(|c| matches!(c, '-' | ':')))
+        .collect::<Vec<_>>()
+        .join(" – ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A package with the manifest `manifest`, which is completed with a
+    /// `[package]` table.
+    fn package(manifest: &str) -> (tempfile::TempDir, Package) {
+        let dir = tempfile::Builder::new()
+            .prefix("cargo-oh-my-dependencies")
+            .tempdir()
+            .unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "").unwrap();
+        std::fs::write(
+            dir.path().join("Cargo.toml"),
+            format!("[package]\nname = \"a\"\nversion = \"0.1.0\"\nedition = \"2021\"\n{manifest}"),
+        )
+        .unwrap();
+        let metadata = cargo_metadata::MetadataCommand::new()
+            .current_dir(dir.path())
+            .no_deps()
+            .exec()
+            .unwrap();
+        let package = metadata.packages.into_iter().next().unwrap();
+        (dir, package)
+    }
+
+    #[test]
+    fn comments() {
+        assert!(comment_lines(None).is_empty());
+        assert_eq!(
+            comment_lines(Some("\n# Enables foo.\n## See `bar`.\n")),
+            ["Enables foo.", "See `bar`."]
+        );
+        // separated by a blank line
+        assert_eq!(comment_lines(Some("# unrelated\n\n# foo\n")), ["foo"]);
+
+        let (_dir, package) = package(
+            "\n[features]\ndefault = [\"std\"]\n# unrelated\n\n## Enables the standard library.\n## Implies `alloc`.\nstd = [\"alloc\"] # on by default\nalloc = []\n",
+        );
+        assert_eq!(
+            manifest_comments(&package, "std"),
+            [
+                "Enables the standard library.",
+                "Implies `alloc`.",
+                "on by default"
+            ]
+        );
+        assert!(manifest_comments(&package, "alloc").is_empty());
+        assert!(manifest_comments(&package, "missing").is_empty());
+    }
+
+    #[test]
+    fn docs_rs_config() {
+        let (_dir, features) = package(
+            "\n[package.metadata.docs.rs]\nfeatures = [\"std\"]\n\n[features]\nstd = []\nalloc = []\n",
+        );
+        assert_eq!(
+            docs_rs(&features, "std").as_deref(),
+            Some("docs.rs documents this feature")
+        );
+        assert_eq!(
+            docs_rs(&features, "alloc").as_deref(),
+            Some("docs.rs builds with features std")
+        );
+
+        let (_dir, all) = package("\n[package.metadata.docs.rs]\nall-features = true\n");
+        assert_eq!(
+            docs_rs(&all, "std").as_deref(),
+            Some("docs.rs documents all features")
+        );
+
+        let (_dir, no_default) =
+            package("\n[package.metadata.docs.rs]\nno-default-features = true\n");
+        assert_eq!(
+            docs_rs(&no_default, "std").as_deref(),
+            Some("docs.rs builds without default features")
+        );
+
+        let (_dir, none) = package("");
+        assert_eq!(docs_rs(&none, "std"), None);
+    }
+
+    #[test]
+    fn headings() {
+        assert_eq!(heading_level("# Features"), Some(1));
+        assert_eq!(heading_level("### Optional features"), Some(3));
+        assert_eq!(heading_level("#features"), None);
+        assert_eq!(heading_level("#![no_std]"), None);
+        assert_eq!(heading_level("features"), None);
+    }
+
+    #[test]
+    fn table_rows() {
+        assert_eq!(table_row("| `std` | Enables std |"), "`std` – Enables std");
+        assert_eq!(table_row("|---|:---:|"), "");
+        assert_eq!(table_row("| `alloc` | |"), "`alloc`");
+    }
+
+    #[test]
+    fn readme_sections() {
+        let readme = "\
+# a
+
+Use `std` outside of the features section.
+
+## Features
+
+- `std`: enables the standard library
+- `alloc`: allocation only
+
+The **std** feature is
+on by default.
+
+| Feature | Description |
+|---------|-------------|
+| `std`   | Standard library |
+
+```toml
+a = { features = [\"`std`\"] }
+```
+
+### Details
+
+Without `std`, `alloc` is needed.
+
+## License
+
+`std` is not a license.
+";
+        assert_eq!(
+            features_section_mentions(readme, "std"),
+            [
+                "- `std`: enables the standard library",
+                "The **std** feature is on by default.",
+                "`std` – Standard library",
+                "Without `std`, `alloc` is needed.",
+            ]
+        );
+        assert_eq!(
+            features_section_mentions(readme, "alloc"),
+            [
+                "- `alloc`: allocation only",
+                "Without `std`, `alloc` is needed.",
+            ]
+        );
+        assert!(features_section_mentions(readme, "serde").is_empty());
+    }
+}
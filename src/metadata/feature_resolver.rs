@@ -0,0 +1,365 @@
+use cargo_metadata::{Dependency, DependencyKind, Metadata, Package, PackageId};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+use super::PackageResolver;
+
+/// A simplified version of cargo's feature resolver that runs over the
+/// `cargo metadata` output. It is used to answer "what if" questions, e.g.
+/// which packages would drop out of the build if a feature was disabled,
+/// without having to run cargo.
+///
+/// Target specific dependencies are always considered, like `cargo metadata`
+/// does without `--filter-platform`.
+pub struct FeatureResolver<'a> {
+    metadata: &'a Metadata,
+    resolver: PackageResolver<'a>,
+}
+
+/// A feature that a workspace member should not request from one of its
+/// dependencies.
+#[derive(Debug, Clone)]
+pub struct RemovedFeature<'a> {
+    pub member: &'a PackageId,
+    /// The key of the dependency in the manifest of the member: its rename if
+    /// it has one, its name otherwise.
+    pub dep_name: &'a str,
+    pub dep_kind: DependencyKind,
+    pub feature: &'a str,
+}
+
+/// The packages that are part of the build and their active features.
+#[derive(Debug, Default, Clone)]
+pub struct ResolvedFeatures {
+    pub packages: HashMap<PackageId, BTreeSet<String>>,
+}
+
+enum Work<'a> {
+    Package(&'a PackageId),
+    Feature(&'a PackageId, String),
+    Dependency(&'a PackageId, &'a Dependency),
+}
+
+impl<'a> FeatureResolver<'a> {
+    pub fn new(metadata: &'a Metadata) -> Self {
+        Self {
+            metadata,
+            resolver: PackageResolver::new(metadata),
+        }
+    }
+
+    pub fn resolve(&self, removed: Option<&RemovedFeature>) -> ResolvedFeatures {
+        let mut packages = HashSet::<&PackageId>::new();
+        let mut features = HashMap::<&PackageId, BTreeSet<String>>::new();
+        // (package, dependency name) of activated dependency declarations
+        let mut activated_deps = HashSet::<(&PackageId, &str)>::new();
+        // (package, dependency name) -> features of weak `dep?/feature` entries
+        let mut weak_features = HashMap::<(&PackageId, &str), Vec<String>>::new();
+
+        let mut queue = VecDeque::new();
+        for member in &self.metadata.workspace_members {
+            queue.push_back(Work::Package(member));
+            let features = self
+                .resolver
+                .node(member)
+                .map(|n| n.features.clone())
+                .unwrap_or_default();
+            for feature in features {
+                queue.push_back(Work::Feature(member, feature));
+            }
+        }
+
+        while let Some(work) = queue.pop_front() {
+            match work {
+                Work::Package(id) => {
+                    if !packages.insert(id) {
+                        continue;
+                    }
+                    let Some(package) = self.resolver.package(id) else {
+                        continue;
+                    };
+                    let is_member = self.metadata.workspace_members.contains(id);
+                    for dep in &package.dependencies {
+                        if dep.optional || (dep.kind == DependencyKind::Development && !is_member) {
+                            continue;
+                        }
+                        queue.push_back(Work::Dependency(id, dep));
+                    }
+                }
+
+                Work::Feature(id, feature) => {
+                    queue.push_back(Work::Package(id));
+                    if !features.entry(id).or_default().insert(feature.clone()) {
+                        continue;
+                    }
+                    let Some(package) = self.resolver.package(id) else {
+                        continue;
+                    };
+
+                    let Some(entries) = package.features.get(&feature) else {
+                        // implicit feature of an optional dependency
+                        for dep in Self::declarations(package, &feature) {
+                            queue.push_back(Work::Dependency(id, dep));
+                        }
+                        continue;
+                    };
+
+                    for entry in entries {
+                        if let Some(dep_name) = entry.strip_prefix("dep:") {
+                            for dep in Self::declarations(package, dep_name) {
+                                queue.push_back(Work::Dependency(id, dep));
+                            }
+                        } else if let Some((dep_name, dep_feature)) = entry.split_once('/') {
+                            let (dep_name, weak) = match dep_name.strip_suffix('?') {
+                                Some(dep_name) => (dep_name, true),
+                                None => (dep_name, false),
+                            };
+                            if weak && !activated_deps.contains(&(id, dep_name)) {
+                                weak_features
+                                    .entry((id, dep_name))
+                                    .or_default()
+                                    .push(dep_feature.to_string());
+                                continue;
+                            }
+                            for dep in Self::declarations(package, dep_name) {
+                                queue.push_back(Work::Dependency(id, dep));
+                                if let Some(dep_package) = self.dependency_package(id, dep) {
+                                    queue.push_back(Work::Feature(
+                                        &dep_package.id,
+                                        dep_feature.to_string(),
+                                    ));
+                                }
+                            }
+                        } else if package.features.contains_key(entry) {
+                            queue.push_back(Work::Feature(id, entry.clone()));
+                        } else {
+                            for dep in Self::declarations(package, entry) {
+                                queue.push_back(Work::Dependency(id, dep));
+                            }
+                        }
+                    }
+                }
+
+                Work::Dependency(id, dep) => {
+                    let Some(dep_package) = self.dependency_package(id, dep) else {
+                        continue;
+                    };
+                    let dep_name = dep.rename.as_deref().unwrap_or(&dep.name);
+                    activated_deps.insert((id, dep_name));
+
+                    let mut dep_features = dep.features.clone();
+                    let mut uses_default_features = dep.uses_default_features;
+                    if let Some(removed) = removed {
+                        if removed.member == id
+                            && removed.dep_name == dep_name
+                            && removed.dep_kind == dep.kind
+                        {
+                            dep_features.retain(|f| f != removed.feature);
+                            if removed.feature == "default" {
+                                uses_default_features = false;
+                            }
+                        }
+                    }
+                    if uses_default_features && dep_package.features.contains_key("default") {
+                        dep_features.push("default".to_string());
+                    }
+                    if let Some(weak) = weak_features.remove(&(id, dep_name)) {
+                        dep_features.extend(weak);
+                    }
+
+                    queue.push_back(Work::Package(&dep_package.id));
+                    for feature in dep_features {
+                        queue.push_back(Work::Feature(&dep_package.id, feature));
+                    }
+                }
+            }
+        }
+
+        ResolvedFeatures {
+            packages: packages
+                .into_iter()
+                .map(|id| (id.clone(), features.remove(id).unwrap_or_default()))
+                .collect(),
+        }
+    }
+
+    /// Packages that would no longer be part of the build if `removed` was not
+    /// requested anymore.
+    pub fn removal_impact(&self, removed: &RemovedFeature) -> Vec<&'a Package> {
+        self.removal_impact_on(&self.resolve(None), removed)
+    }
+
+    /// Like [`Self::removal_impact`], against a `baseline` from
+    /// `resolve(None)` that is shared when asking about many features.
+    pub fn removal_impact_on(
+        &self,
+        baseline: &ResolvedFeatures,
+        removed: &RemovedFeature,
+    ) -> Vec<&'a Package> {
+        let after = self.resolve(Some(removed));
+        let mut pruned = baseline
+            .packages
+            .keys()
+            .filter(|id| !after.packages.contains_key(*id))
+            .filter_map(|id| self.resolver.package(id))
+            .collect::<Vec<_>>();
+        pruned.sort_by(|a, b| a.name.cmp(&b.name).then(a.version.cmp(&b.version)));
+        pruned
+    }
+
+    /// Dependency declarations of `package` that are referred to as `name` in
+    /// its `[features]` table.
+    fn declarations<'p>(package: &'p Package, name: &str) -> Vec<&'p Dependency> {
+        package
+            .dependencies
+            .iter()
+            .filter(|d| d.rename.as_deref().unwrap_or(&d.name) == name)
+            .collect()
+    }
+
+    /// The resolved package of a dependency declaration.
+    fn dependency_package(&self, dependent: &PackageId, dep: &Dependency) -> Option<&'a Package> {
+        let candidates = self
+            .resolver
+            .dependencies(dependent)
+            .into_iter()
+            .filter(|(node_dep, package)| {
+                package.name == dep.name && node_dep.dep_kinds.iter().any(|k| k.kind == dep.kind)
+            })
+            .map(|(_, package)| package)
+            .collect::<Vec<_>>();
+        candidates
+            .iter()
+            .find(|package| dep.req.matches(&package.version))
+            .or_else(|| candidates.first())
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The package `app` and its path dependencies, outside of the workspace
+    /// of `app`.
+    fn fixture() -> (tempfile::TempDir, Metadata) {
+        let dir = tempfile::Builder::new()
+            .prefix("cargo-oh-my-dependencies")
+            .tempdir()
+            .unwrap();
+        let crates = [
+            ("app", "[dependencies]\nbase = { path = \"../base\", features = [\"w\", \"x\"] }\ntiny = { package = \"small\", path = \"../small\", features = [\"s\"] }\n\n[dev-dependencies]\ntester = { path = \"../tester\" }\n"),
+            ("base", "[dependencies]\nbig = { path = \"../big\", optional = true }\nweak = { path = \"../weak\", optional = true }\n\n[dev-dependencies]\ndevonly = { path = \"../devonly\" }\n\n[features]\ndefault = [\"big\"]\nbig = [\"dep:big\"]\nw = [\"weak?/extra\"]\nx = [\"dep:weak\"]\n"),
+            ("big", ""),
+            ("weak", "[features]\nextra = []\n"),
+            ("devonly", ""),
+            ("small", "[dependencies]\nleaf = { path = \"../leaf\", optional = true }\n\n[features]\ns = [\"dep:leaf\"]\n"),
+            ("leaf", ""),
+            ("tester", ""),
+        ];
+        for (name, deps) in crates {
+            let src = dir.path().join(name).join("src");
+            std::fs::create_dir_all(&src).unwrap();
+            std::fs::write(src.join("lib.rs"), "").unwrap();
+            std::fs::write(
+                dir.path().join(name).join("Cargo.toml"),
+                format!(
+                    "[package]\nname = {name:?}\nversion = \"0.1.0\"\nedition = \"2021\"\n\n{deps}"
+                ),
+            )
+            .unwrap();
+        }
+        let metadata = cargo_metadata::MetadataCommand::new()
+            .manifest_path(dir.path().join("app/Cargo.toml"))
+            .exec()
+            .unwrap();
+        (dir, metadata)
+    }
+
+    fn app(metadata: &Metadata) -> &PackageId {
+        &metadata.workspace_members[0]
+    }
+
+    fn names(packages: Vec<&Package>) -> Vec<&str> {
+        packages.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    fn features<'a>(
+        resolved: &'a ResolvedFeatures,
+        metadata: &Metadata,
+        name: &str,
+    ) -> Option<Vec<&'a str>> {
+        let package = metadata.packages.iter().find(|p| p.name == name)?;
+        let features = resolved.packages.get(&package.id)?;
+        Some(features.iter().map(String::as_str).collect())
+    }
+
+    fn removed<'a>(
+        metadata: &'a Metadata,
+        dep_name: &'a str,
+        feature: &'a str,
+    ) -> RemovedFeature<'a> {
+        RemovedFeature {
+            member: app(metadata),
+            dep_name,
+            dep_kind: DependencyKind::Normal,
+            feature,
+        }
+    }
+
+    #[test]
+    fn default_features() {
+        let (_dir, metadata) = fixture();
+        let resolver = FeatureResolver::new(&metadata);
+        let resolved = resolver.resolve(None);
+        assert_eq!(
+            features(&resolved, &metadata, "base").unwrap(),
+            ["big", "default", "w", "x"]
+        );
+        assert_eq!(
+            names(resolver.removal_impact(&removed(&metadata, "base", "default"))),
+            ["big"]
+        );
+    }
+
+    #[test]
+    fn weak_and_optional_dependencies() {
+        let (_dir, metadata) = fixture();
+        let resolver = FeatureResolver::new(&metadata);
+        let resolved = resolver.resolve(None);
+        // `x` turns on the optional dependency, `w` one of its features
+        assert_eq!(features(&resolved, &metadata, "weak").unwrap(), ["extra"]);
+        assert_eq!(
+            names(resolver.removal_impact(&removed(&metadata, "base", "x"))),
+            ["weak"]
+        );
+        // a weak feature doesn't turn the dependency on by itself
+        assert!(resolver
+            .removal_impact(&removed(&metadata, "base", "w"))
+            .is_empty());
+        let without_x = resolver.resolve(Some(&removed(&metadata, "base", "x")));
+        assert_eq!(features(&without_x, &metadata, "weak"), None);
+    }
+
+    #[test]
+    fn renamed_dependency() {
+        let (_dir, metadata) = fixture();
+        let resolver = FeatureResolver::new(&metadata);
+        assert_eq!(
+            names(resolver.removal_impact(&removed(&metadata, "tiny", "s"))),
+            ["leaf"]
+        );
+        // the package name is not the manifest key
+        assert!(resolver
+            .removal_impact(&removed(&metadata, "small", "s"))
+            .is_empty());
+    }
+
+    #[test]
+    fn dev_dependencies() {
+        let (_dir, metadata) = fixture();
+        let resolved = FeatureResolver::new(&metadata).resolve(None);
+        // only the ones of workspace members are built
+        assert!(features(&resolved, &metadata, "tester").is_some());
+        assert!(features(&resolved, &metadata, "devonly").is_none());
+    }
+}
@@ -0,0 +1,237 @@
+use cargo_metadata::{DependencyKind, Metadata, NodeDep, Package, PackageId};
+use std::collections::{BTreeSet, HashSet};
+
+use super::{package_resolver::node_dep_kind, PackageResolver};
+
+/// The packages depending on a package, recursively up to the workspace
+/// members. Like `cargo tree -i`, packages that were already expanded are only
+/// expanded once and marked as repeated.
+#[derive(Debug, Clone)]
+pub struct InverseTree {
+    pub root: InverseNode,
+}
+
+#[derive(Debug, Clone)]
+pub struct InverseNode {
+    pub id: PackageId,
+    pub name: String,
+    pub version: String,
+    /// How this package depends on its parent in the inverse tree. `None` for
+    /// the root.
+    pub edge: Option<DependencyEdge>,
+    pub is_workspace_member: bool,
+    pub repeated: bool,
+    pub children: Vec<InverseNode>,
+}
+
+/// A dependency edge `dependent -> dependency` in the resolved graph together
+/// with the reasons for it.
+#[derive(Debug, Clone)]
+pub struct DependencyEdge {
+    pub kind: DependencyKind,
+    /// Features of the dependency requested by the dependent, including
+    /// `dep/feature` entries of active features of the dependent.
+    pub features: BTreeSet<String>,
+    pub uses_default_features: bool,
+    /// Active features of the dependent that enable this (optional) dependency.
+    pub enabled_by: BTreeSet<String>,
+}
+
+impl DependencyEdge {
+    pub fn new(
+        dependent: &Package,
+        active_features: &[String],
+        node_dep: &NodeDep,
+        dependency: &Package,
+    ) -> Self {
+        let kind = node_dep_kind(node_dep);
+        let declarations = dependent
+            .dependencies
+            .iter()
+            .filter(|d| {
+                d.name == dependency.name && node_dep.dep_kinds.iter().any(|k| k.kind == d.kind)
+            })
+            .collect::<Vec<_>>();
+
+        let mut features = declarations
+            .iter()
+            .flat_map(|d| d.features.iter().cloned())
+            .collect::<BTreeSet<_>>();
+        let uses_default_features = declarations.iter().any(|d| d.uses_default_features);
+
+        let mut enabled_by = BTreeSet::new();
+        let names = declarations
+            .iter()
+            .map(|d| d.rename.as_deref().unwrap_or(&d.name))
+            .collect::<HashSet<_>>();
+        for feature in active_features {
+            let Some(entries) = dependent.features.get(feature) else {
+                continue;
+            };
+            for entry in entries {
+                let entry = entry.strip_prefix("dep:").unwrap_or(entry);
+                let (dep, dep_feature) = match entry.split_once('/') {
+                    Some((dep, dep_feature)) => (dep.trim_end_matches('?'), Some(dep_feature)),
+                    None => (entry, None),
+                };
+                if !names.contains(dep) {
+                    continue;
+                }
+                match dep_feature {
+                    Some(dep_feature) => {
+                        features.insert(dep_feature.to_string());
+                    }
+                    None => {
+                        enabled_by.insert(feature.clone());
+                    }
+                }
+            }
+        }
+
+        Self {
+            kind,
+            features,
+            uses_default_features,
+            enabled_by,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if !matches!(self.kind, DependencyKind::Normal) {
+            parts.push(format!("({})", self.kind));
+        }
+        let mut features = self.features.iter().cloned().collect::<Vec<_>>();
+        if self.uses_default_features {
+            features.insert(0, "default".to_string());
+        }
+        if !features.is_empty() {
+            parts.push(format!("[features: {}]", features.join(", ")));
+        }
+        if !self.enabled_by.is_empty() {
+            let enabled_by = self.enabled_by.iter().cloned().collect::<Vec<_>>();
+            parts.push(format!("[enabled by: {}]", enabled_by.join(", ")));
+        }
+        parts.join(" ")
+    }
+}
+
+impl InverseTree {
+    pub fn build(metadata: &Metadata, package: &PackageId) -> Option<Self> {
+        let resolver = PackageResolver::new(metadata);
+        let workspace_members = metadata.workspace_members.iter().collect::<HashSet<_>>();
+
+        let root = resolver.package(package)?;
+        let mut expanded = HashSet::new();
+        let root = Self::node(root, None, &resolver, &workspace_members, &mut expanded);
+
+        Some(Self { root })
+    }
+
+    fn node(
+        package: &Package,
+        edge: Option<DependencyEdge>,
+        resolver: &PackageResolver,
+        workspace_members: &HashSet<&PackageId>,
+        expanded: &mut HashSet<PackageId>,
+    ) -> InverseNode {
+        let mut node = InverseNode {
+            id: package.id.clone(),
+            name: package.name.clone(),
+            version: package.version.to_string(),
+            edge,
+            is_workspace_member: workspace_members.contains(&package.id),
+            repeated: false,
+            children: Vec::new(),
+        };
+
+        let dependents = resolver.dependents(&package.id);
+        if !expanded.insert(package.id.clone()) {
+            node.repeated = !dependents.is_empty();
+            return node;
+        }
+
+        for (dependent_id, node_dep) in dependents {
+            let Some(dependent) = resolver.package(dependent_id) else {
+                continue;
+            };
+            let active_features = resolver
+                .node(dependent_id)
+                .map(|n| n.features.as_slice())
+                .unwrap_or_default();
+            let edge = DependencyEdge::new(dependent, active_features, node_dep, package);
+            node.children.push(Self::node(
+                dependent,
+                Some(edge),
+                resolver,
+                workspace_members,
+                expanded,
+            ));
+        }
+
+        node
+    }
+
+    /// Renders the tree in the style of `cargo tree`.
+    pub fn render_text(&self) -> String {
+        let mut out = String::new();
+        Self::render_node(&self.root, "", "", &mut out);
+        out
+    }
+
+    fn render_node(node: &InverseNode, prefix: &str, child_prefix: &str, out: &mut String) {
+        out.push_str(prefix);
+        out.push_str(&node.label());
+        out.push('\n');
+
+        let count = node.children.len();
+        for (i, child) in node.children.iter().enumerate() {
+            let last = i + 1 == count;
+            let (branch, indent) = if last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            Self::render_node(
+                child,
+                &format!("{child_prefix}{branch}"),
+                &format!("{child_prefix}{indent}"),
+                out,
+            );
+        }
+    }
+}
+
+impl InverseNode {
+    pub fn label(&self) -> String {
+        let mut label = format!("{} v{}", self.name, self.version);
+        if self.is_workspace_member {
+            label.push_str(" (workspace)");
+        }
+        if let Some(edge) = &self.edge {
+            let edge = edge.describe();
+            if !edge.is_empty() {
+                label.push(' ');
+                label.push_str(&edge);
+            }
+        }
+        if self.repeated {
+            label.push_str(" (*)");
+        }
+        label
+    }
+}
+
+/// Finds packages by `name` or `name@version`.
+pub fn find_packages<'a>(metadata: &'a Metadata, spec: &str) -> Vec<&'a Package> {
+    let (name, version) = match spec.split_once('@') {
+        Some((name, version)) => (name, Some(version)),
+        None => (spec, None),
+    };
+    metadata
+        .packages
+        .iter()
+        .filter(|p| p.name == name)
+        .filter(|p| version.is_none_or(|v| p.version.to_string() == v))
+        .collect()
+}
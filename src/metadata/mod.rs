@@ -1,8 +1,18 @@
+pub mod crate_references;
 pub(crate) mod dep_tree;
+pub mod dependency_size;
+pub mod duplicates;
+pub mod feature_docs;
+pub mod feature_resolver;
 pub(crate) mod features;
+pub mod inverse_tree;
 pub(crate) mod package_resolver;
-pub mod workspace_info;
+pub(crate) mod policy;
+pub mod timings;
 pub mod toml;
+pub mod unused_features;
+pub mod upgrades;
+pub mod workspace_info;
 
 pub use features::Features;
 pub use package_resolver::PackageResolver;
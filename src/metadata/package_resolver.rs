@@ -1,4 +1,4 @@
-use cargo_metadata::{Metadata, Node, Package, PackageId};
+use cargo_metadata::{DependencyKind, Metadata, Node, NodeDep, Package, PackageId};
 use std::collections::BTreeMap;
 
 /// Given a dependent package and a package name which is a dependency of the
@@ -6,30 +6,49 @@ use std::collections::BTreeMap;
 pub struct PackageResolver<'a> {
     resolved: BTreeMap<PackageId, &'a Node>,
     packages: BTreeMap<PackageId, &'a Package>,
+    /// dependency -> [(dependent, node dep)]
+    dependents: BTreeMap<&'a PackageId, Vec<(&'a PackageId, &'a NodeDep)>>,
 }
 
 impl<'a> PackageResolver<'a> {
     pub fn new(metadata: &'a Metadata) -> Self {
-        let resolved = metadata
+        let nodes = &metadata
             .resolve
             .as_ref()
             .unwrap() // TODO
-            .nodes
-            .iter()
-            .map(|n| (n.id.clone(), n))
-            .collect();
+            .nodes;
+        let resolved = nodes.iter().map(|n| (n.id.clone(), n)).collect();
         let packages = metadata
             .packages
             .iter()
             .map(|p| (p.id.clone(), p))
             .collect();
-        Self { resolved, packages }
+        let mut dependents = BTreeMap::<_, Vec<_>>::new();
+        for node in nodes {
+            for dep in &node.deps {
+                dependents
+                    .entry(&dep.pkg)
+                    .or_default()
+                    .push((&node.id, dep));
+            }
+        }
+        Self {
+            resolved,
+            packages,
+            dependents,
+        }
+    }
+
+    pub fn package(&self, id: &PackageId) -> Option<&'a Package> {
+        self.packages.get(id).copied()
+    }
+
+    pub fn node(&self, id: &PackageId) -> Option<&'a Node> {
+        self.resolved.get(id).copied()
     }
 
     pub fn resolve_dependency(&self, dependent: &PackageId, dep_name: &str) -> Option<&Package> {
-        let Some(resolver) = self.resolved.get(dependent) else {
-            return None;
-        };
+        let resolver = self.resolved.get(dependent)?;
         resolver
             .deps
             .iter()
@@ -41,4 +60,40 @@ impl<'a> PackageResolver<'a> {
             .and_then(|dep| self.packages.get(&dep.pkg))
             .copied()
     }
+
+    /// The resolved dependencies of `dependent` (`resolve.nodes[].deps`)
+    /// together with their packages.
+    pub fn dependencies(&self, dependent: &PackageId) -> Vec<(&'a NodeDep, &'a Package)> {
+        let Some(node) = self.resolved.get(dependent) else {
+            return Vec::new();
+        };
+        node.deps
+            .iter()
+            .filter_map(|dep| self.packages.get(&dep.pkg).map(|p| (dep, *p)))
+            .collect()
+    }
+
+    /// The packages that depend on `dependency` in the resolved graph.
+    pub fn dependents(&self, dependency: &PackageId) -> &[(&'a PackageId, &'a NodeDep)] {
+        self.dependents
+            .get(dependency)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+/// The kind a resolved dependency is shown as. Dependencies that are used as
+/// normal dependencies and in other ways count as normal.
+pub fn node_dep_kind(dep: &NodeDep) -> DependencyKind {
+    if dep
+        .dep_kinds
+        .iter()
+        .any(|k| matches!(k.kind, DependencyKind::Normal))
+    {
+        return DependencyKind::Normal;
+    }
+    dep.dep_kinds
+        .first()
+        .map(|k| k.kind)
+        .unwrap_or(DependencyKind::Normal)
 }
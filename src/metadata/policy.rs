@@ -0,0 +1,269 @@
+//! Checks of how the workspace members declare their dependencies, for the
+//! policy tab.
+
+use cargo_metadata::{semver::VersionReq, Metadata, Package};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use super::toml::table_name;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rule {
+    /// A registry dependency that accepts any version.
+    WildcardVersion,
+    /// A dependency of `[workspace.dependencies]` that a member declares
+    /// itself instead of with `workspace = true`.
+    NotInherited,
+    /// Members require different versions of the same registry dependency.
+    DivergingVersions,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 3] = [
+        Rule::WildcardVersion,
+        Rule::NotInherited,
+        Rule::DivergingVersions,
+    ];
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Rule::WildcardVersion => "wildcard version requirements",
+            Rule::NotInherited => "not inherited from [workspace.dependencies]",
+            Rule::DivergingVersions => "different version requirements across members",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Violation {
+    pub rule: Rule,
+    pub dep_name: String,
+    /// Names of the members that declare the dependency this way.
+    pub members: Vec<String>,
+    pub message: String,
+}
+
+/// Checks the dependencies of every workspace member against all [`Rule`]s.
+pub fn find_violations(metadata: &Metadata) -> Vec<Violation> {
+    let members = metadata.workspace_packages();
+    let mut violations = Vec::new();
+
+    for member in &members {
+        for dep in member.dependencies.iter().filter(|dep| is_registry(dep)) {
+            if dep.req == VersionReq::STAR {
+                violations.push(Violation {
+                    rule: Rule::WildcardVersion,
+                    dep_name: dep.name.clone(),
+                    members: vec![member.name.clone()],
+                    message: format!("{} = \"*\"", dep.name),
+                });
+            }
+        }
+    }
+
+    let root_manifest = metadata.workspace_root.join("Cargo.toml");
+    if let Some(workspace_deps) = read_manifest(root_manifest.as_std_path()).and_then(|doc| {
+        doc.get("workspace")?
+            .get("dependencies")?
+            .as_table_like()
+            .map(keys)
+    }) {
+        for member in &members {
+            let Some(doc) = read_manifest(member.manifest_path.as_std_path()) else {
+                continue;
+            };
+            for dep in &member.dependencies {
+                let key = dep.rename.as_deref().unwrap_or(&dep.name);
+                if dep.target.is_some() || !workspace_deps.iter().any(|name| name == key) {
+                    continue;
+                }
+                let inherited = doc
+                    .get(table_name(dep.kind))
+                    .and_then(|deps| deps.get(key))
+                    .and_then(|dep| dep.get("workspace"))
+                    .and_then(|workspace| workspace.as_bool())
+                    .unwrap_or(false);
+                if !inherited {
+                    violations.push(Violation {
+                        rule: Rule::NotInherited,
+                        dep_name: key.to_string(),
+                        members: vec![member.name.clone()],
+                        message: format!("use {key} = {{ workspace = true }}"),
+                    });
+                }
+            }
+        }
+    }
+
+    let mut requirements = BTreeMap::<&str, BTreeMap<String, Vec<&Package>>>::new();
+    for member in &members {
+        for dep in member.dependencies.iter().filter(|dep| is_registry(dep)) {
+            let members = requirements
+                .entry(&dep.name)
+                .or_default()
+                .entry(dep.req.to_string())
+                .or_default();
+            if !members.iter().any(|p| p.id == member.id) {
+                members.push(member);
+            }
+        }
+    }
+    for (dep_name, by_req) in requirements {
+        // a member may require another version for its tests or build script
+        let involved = by_req
+            .values()
+            .flatten()
+            .map(|p| &p.id)
+            .collect::<BTreeSet<_>>();
+        if by_req.len() < 2 || involved.len() < 2 {
+            continue;
+        }
+        let message = by_req
+            .iter()
+            .map(|(req, members)| {
+                let names = members.iter().map(|p| p.name.as_str()).collect::<Vec<_>>();
+                format!("{req} in {}", names.join(", "))
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        violations.push(Violation {
+            rule: Rule::DivergingVersions,
+            dep_name: dep_name.to_string(),
+            members: by_req.values().flatten().map(|p| p.name.clone()).collect(),
+            message,
+        });
+    }
+
+    violations
+}
+
+fn is_registry(dep: &cargo_metadata::Dependency) -> bool {
+    dep.source
+        .as_deref()
+        .is_some_and(|source| source.starts_with("registry+") || source.starts_with("sparse+"))
+}
+
+fn keys(table: &dyn toml_edit::TableLike) -> Vec<String> {
+    table.iter().map(|(key, _)| key.to_string()).collect()
+}
+
+fn read_manifest(path: &Path) -> Option<toml_edit::DocumentMut> {
+    let content = std::fs::read_to_string(path)
+        .inspect_err(|err| warn!("could not read {path:?}: {err}"))
+        .ok()?;
+    content
+        .parse()
+        .inspect_err(|err| warn!("could not parse {path:?}: {err}"))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes a workspace with a library member per `(name, dependencies)`
+    /// and reads its metadata.
+    fn workspace(workspace_deps: &str, members: &[(&str, &str)]) -> (tempfile::TempDir, Metadata) {
+        let dir = tempfile::Builder::new()
+            .prefix("cargo-oh-my-dependencies")
+            .tempdir()
+            .unwrap();
+        let names = members
+            .iter()
+            .map(|(name, _)| format!("{name:?}"))
+            .collect::<Vec<_>>();
+        std::fs::write(
+            dir.path().join("Cargo.toml"),
+            format!(
+                "[workspace]\nmembers = [{}]\n\n[workspace.dependencies]\n{workspace_deps}",
+                names.join(", ")
+            ),
+        )
+        .unwrap();
+        for (name, deps) in members {
+            let member = dir.path().join(name);
+            std::fs::create_dir_all(member.join("src")).unwrap();
+            std::fs::write(member.join("src/lib.rs"), "").unwrap();
+            std::fs::write(
+                member.join("Cargo.toml"),
+                format!(
+                    "[package]\nname = {name:?}\nversion = \"0.1.0\"\nedition = \"2021\"\n\n{deps}"
+                ),
+            )
+            .unwrap();
+        }
+        let metadata = cargo_metadata::MetadataCommand::new()
+            .manifest_path(dir.path().join("Cargo.toml"))
+            .no_deps()
+            .exec()
+            .unwrap();
+        (dir, metadata)
+    }
+
+    fn rules(violations: &[Violation], rule: Rule) -> Vec<(&str, Vec<&str>)> {
+        violations
+            .iter()
+            .filter(|v| v.rule == rule)
+            .map(|v| {
+                let members = v.members.iter().map(String::as_str).collect();
+                (v.dep_name.as_str(), members)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn wildcard_version() {
+        let (_dir, metadata) = workspace(
+            "",
+            &[
+                ("a", "[dependencies]\nserde = \"*\"\nlog = \"0.4\"\n"),
+                ("b", "[build-dependencies]\ncc = \"*\"\n"),
+            ],
+        );
+        let violations = find_violations(&metadata);
+        assert_eq!(
+            rules(&violations, Rule::WildcardVersion),
+            vec![("serde", vec!["a"]), ("cc", vec!["b"])]
+        );
+    }
+
+    #[test]
+    fn not_inherited() {
+        let (_dir, metadata) = workspace(
+            "serde = \"1\"\nlog = \"0.4\"\n",
+            &[
+                (
+                    "a",
+                    "[dependencies]\nserde = { workspace = true }\nlog = \"0.4\"\n",
+                ),
+                ("b", "[dev-dependencies]\nserde = \"1\"\n"),
+            ],
+        );
+        let violations = find_violations(&metadata);
+        assert_eq!(
+            rules(&violations, Rule::NotInherited),
+            vec![("log", vec!["a"]), ("serde", vec!["b"])]
+        );
+    }
+
+    #[test]
+    fn diverging_versions() {
+        let (_dir, metadata) = workspace(
+            "",
+            &[
+                (
+                    "a",
+                    "[dependencies]\nserde = \"1.0.100\"\nlog = \"0.4\"\n\n[dev-dependencies]\nlog = \"0.4.20\"\n",
+                ),
+                ("b", "[dependencies]\nserde = \"1.0.200\"\n"),
+                ("c", "[dependencies]\nserde = \"1.0.200\"\n"),
+            ],
+        );
+        let violations = find_violations(&metadata);
+        // log only differs between the dependencies and dev-dependencies of `a`
+        assert_eq!(
+            rules(&violations, Rule::DivergingVersions),
+            vec![("serde", vec!["a", "b", "c"])]
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use cargo_metadata::{Metadata, Package};
+use eyre::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::{
+    feature_resolver::{FeatureResolver, RemovedFeature},
+    Features, PackageResolver,
+};
+
+/// Compile times per package, read from the output of `cargo build --timings`.
+///
+/// Two formats are supported:
+/// - the HTML report cargo writes to `target/cargo-timings/cargo-timing.html`
+/// - the JSON messages of
+///   `cargo build --timings=json -Zunstable-options --message-format=json`
+///
+/// Packages with multiple units (build script, lib, bins) get the sum of their
+/// durations.
+#[derive(Debug, Clone, Default)]
+pub struct Timings {
+    /// Seconds by package id, available with the JSON format.
+    by_id: HashMap<String, f64>,
+    /// Seconds by (name, version), available with the HTML report.
+    by_name: HashMap<(String, String), f64>,
+}
+
+/// An entry of `UNIT_DATA` in the HTML report.
+#[derive(Debug, Deserialize)]
+struct HtmlUnit {
+    name: String,
+    version: String,
+    duration: f64,
+}
+
+/// A `timing-info` JSON message.
+#[derive(Debug, Deserialize)]
+struct TimingInfo {
+    reason: String,
+    package_id: Option<String>,
+    duration: Option<f64>,
+}
+
+/// What enabling a feature costs: the packages that are only built because of
+/// it and their compile time.
+#[derive(Debug, Clone)]
+pub struct FeatureCost<'a> {
+    pub member: &'a Package,
+    pub dep_name: String,
+    pub feature: String,
+    pub packages: Vec<&'a Package>,
+    pub seconds: f64,
+}
+
+impl Timings {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("reading timings from {path:?}"))?;
+        let timings = if content.contains("const UNIT_DATA") {
+            Self::from_html(&content)?
+        } else {
+            Self::from_json_messages(&content)?
+        };
+        if timings.by_id.is_empty() && timings.by_name.is_empty() {
+            eyre::bail!("{path:?} contains no timing information");
+        }
+        Ok(timings)
+    }
+
+    fn from_html(html: &str) -> Result<Self> {
+        let Some((_, data)) = html.split_once("const UNIT_DATA = ") else {
+            eyre::bail!("UNIT_DATA not found in timing report");
+        };
+        // the array is followed by `;` and more javascript, only parse the
+        // first value
+        let units = serde_json::Deserializer::from_str(data)
+            .into_iter::<Vec<HtmlUnit>>()
+            .next()
+            .ok_or_else(|| eyre::eyre!("UNIT_DATA is empty"))?
+            .context("parsing UNIT_DATA")?;
+
+        let mut by_name = HashMap::new();
+        for unit in units {
+            *by_name.entry((unit.name, unit.version)).or_default() += unit.duration;
+        }
+        Ok(Self {
+            by_name,
+            ..Default::default()
+        })
+    }
+
+    fn from_json_messages(messages: &str) -> Result<Self> {
+        let mut by_id = HashMap::new();
+        for line in messages.lines() {
+            let line = line.trim();
+            if !line.starts_with('{') {
+                continue;
+            }
+            let Ok(message) = serde_json::from_str::<TimingInfo>(line) else {
+                continue;
+            };
+            if message.reason != "timing-info" {
+                continue;
+            }
+            if let (Some(id), Some(duration)) = (message.package_id, message.duration) {
+                *by_id.entry(id).or_default() += duration;
+            }
+        }
+        Ok(Self {
+            by_id,
+            ..Default::default()
+        })
+    }
+
+    /// Compile seconds of a package, `None` if it wasn't part of the build.
+    pub fn seconds(&self, package: &Package) -> Option<f64> {
+        self.by_id.get(&package.id.repr).copied().or_else(|| {
+            self.by_name
+                .get(&(package.name.clone(), package.version.to_string()))
+                .copied()
+        })
+    }
+
+    /// Summed compile seconds of `packages`.
+    pub fn total<'a>(&self, packages: impl IntoIterator<Item = &'a Package>) -> f64 {
+        packages.into_iter().filter_map(|p| self.seconds(p)).sum()
+    }
+
+    /// The cost of every feature the workspace members enable directly,
+    /// attributed via the packages that would drop out of the build without
+    /// it. Sorted by cost, most expensive first.
+    pub fn feature_costs<'a>(&self, metadata: &'a Metadata) -> Vec<FeatureCost<'a>> {
+        let resolver = PackageResolver::new(metadata);
+        let feature_resolver = FeatureResolver::new(metadata);
+        let baseline = feature_resolver.resolve(None);
+        let mut costs = Vec::new();
+
+        for member in metadata.workspace_packages() {
+            for dep in &member.dependencies {
+                let Some(dep_package) = resolver.resolve_dependency(&member.id, &dep.name) else {
+                    continue;
+                };
+                let dep_name = dep.rename.as_deref().unwrap_or(&dep.name);
+                for feature in Features::new(dep, dep_package).active_features() {
+                    let packages = feature_resolver.removal_impact_on(
+                        &baseline,
+                        &RemovedFeature {
+                            member: &member.id,
+                            dep_name,
+                            dep_kind: dep.kind,
+                            feature,
+                        },
+                    );
+                    let seconds = self.total(packages.iter().copied());
+                    costs.push(FeatureCost {
+                        member,
+                        dep_name: dep_name.to_string(),
+                        feature: feature.clone(),
+                        packages,
+                        seconds,
+                    });
+                }
+            }
+        }
+
+        costs.sort_by(|a, b| b.seconds.total_cmp(&a.seconds));
+        costs
+    }
+}
@@ -1,8 +1,18 @@
+use cargo_metadata::DependencyKind;
 use std::collections::BTreeSet;
 use toml_edit::visit::*;
 use toml_edit::visit_mut::*;
 use toml_edit::{Array, InlineTable, Item, KeyMut, Table, Value};
 
+/// The manifest table that lists dependencies of `kind`.
+pub fn table_name(kind: DependencyKind) -> &'static str {
+    match kind {
+        DependencyKind::Development => "dev-dependencies",
+        DependencyKind::Build => "build-dependencies",
+        _ => "dependencies",
+    }
+}
+
 // -=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-=-
 
 #[derive(Debug, Default)]
@@ -0,0 +1,602 @@
+use cargo_metadata::{DependencyKind, Metadata, Package, PackageId};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::{Features, PackageResolver};
+
+/// What the source of a dependency tells about a feature we enable.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum FeatureUsage {
+    /// The feature was not checked or gates nothing we can find by name, e.g.
+    /// trait impls or items declared by macros.
+    #[default]
+    Unknown,
+    /// Some of the items the feature gates are referenced by the workspace
+    /// package.
+    Used { items: Vec<String> },
+    /// None of the items the feature gates are referenced by the workspace
+    /// package.
+    PossiblyUnused { items: Vec<String> },
+}
+
+impl FeatureUsage {
+    pub fn is_possibly_unused(&self) -> bool {
+        matches!(self, FeatureUsage::PossiblyUnused { .. })
+    }
+}
+
+/// A feature a workspace member enables but apparently doesn't use.
+#[derive(Debug, Clone)]
+pub struct UnusedFeature {
+    pub member: PackageId,
+    pub member_name: String,
+    pub dep_name: String,
+    pub dep_kind: DependencyKind,
+    pub feature: String,
+    /// The public items the feature gates, none of them is referenced.
+    pub items: Vec<String>,
+}
+
+/// Public items of a package that are only compiled with a feature.
+#[derive(Debug, Clone, Default)]
+struct GatedItems {
+    names: BTreeSet<String>,
+    /// Gated items without a name we could look for, like `impl` blocks or
+    /// glob re-exports. If a feature gates any of them we can't tell if it is
+    /// used.
+    opaque: usize,
+}
+
+/// Finds enabled features of dependencies that are possibly unused by looking
+/// at the source code: a feature is flagged if it gates public items (via
+/// `#[cfg(feature = "...")]`) and the workspace package refers to none of them
+/// by name.
+///
+/// This is a heuristic. Items declared by macros, trait impls and method
+/// calls are invisible to it, in these cases the usage is
+/// [`FeatureUsage::Unknown`]. Sources are scanned once and cached.
+#[derive(Debug, Default)]
+pub struct SourceScanner {
+    gated_items: HashMap<PackageId, HashMap<String, GatedItems>>,
+    identifiers: HashMap<PackageId, HashSet<String>>,
+}
+
+impl SourceScanner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forgets the identifiers of the workspace packages, their sources may
+    /// have changed. Dependencies are only scanned once per package id.
+    pub fn forget_members(&mut self) {
+        self.identifiers.clear();
+    }
+
+    /// Usage of `feature` of the dependency `dependency` by the workspace
+    /// package `member`.
+    pub fn usage(&mut self, member: &Package, dependency: &Package, feature: &str) -> FeatureUsage {
+        let items = self.feature_items(dependency, feature);
+        if items.opaque > 0 || items.names.is_empty() {
+            return FeatureUsage::Unknown;
+        }
+
+        let identifiers = self
+            .identifiers
+            .entry(member.id.clone())
+            .or_insert_with(|| member_identifiers(member));
+        let (used, unused): (Vec<_>, Vec<_>) = items
+            .names
+            .into_iter()
+            .partition(|name| identifiers.contains(name));
+
+        if used.is_empty() {
+            FeatureUsage::PossiblyUnused { items: unused }
+        } else {
+            FeatureUsage::Used { items: used }
+        }
+    }
+
+    /// Items gated by `feature` and the features it enables in the same
+    /// package.
+    fn feature_items(&mut self, package: &Package, feature: &str) -> GatedItems {
+        let gated = self
+            .gated_items
+            .entry(package.id.clone())
+            .or_insert_with(|| package_gated_items(package));
+
+        let mut result = GatedItems::default();
+        let mut seen = HashSet::new();
+        let mut todo = vec![feature.to_string()];
+        while let Some(feature) = todo.pop() {
+            if !seen.insert(feature.clone()) {
+                continue;
+            }
+            if let Some(items) = gated.get(&feature) {
+                result.names.extend(items.names.iter().cloned());
+                result.opaque += items.opaque;
+            }
+            for entry in package.features.get(&feature).into_iter().flatten() {
+                if !entry.contains('/') {
+                    todo.push(entry.trim_start_matches("dep:").to_string());
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Enabled features that are possibly unused, for all workspace members.
+pub fn find_unused_features(metadata: &Metadata) -> Vec<UnusedFeature> {
+    let resolver = PackageResolver::new(metadata);
+    let mut scanner = SourceScanner::new();
+    let mut unused = Vec::new();
+
+    for member in metadata.workspace_packages() {
+        for dep in &member.dependencies {
+            let Some(dep_package) = resolver.resolve_dependency(&member.id, &dep.name) else {
+                continue;
+            };
+            let mut features = Features::new(dep, dep_package)
+                .active_features()
+                .into_iter()
+                .collect::<Vec<_>>();
+            features.sort();
+
+            for feature in features {
+                if let FeatureUsage::PossiblyUnused { items } =
+                    scanner.usage(member, dep_package, feature)
+                {
+                    unused.push(UnusedFeature {
+                        member: member.id.clone(),
+                        member_name: member.name.clone(),
+                        dep_name: dep.name.clone(),
+                        dep_kind: dep.kind,
+                        feature: feature.clone(),
+                        items,
+                    });
+                }
+            }
+        }
+    }
+
+    unused
+}
+
+/// Scans the library source of `package` for items behind
+/// `#[cfg(feature = "...")]`.
+fn package_gated_items(package: &Package) -> HashMap<String, GatedItems> {
+    let mut gated = HashMap::<String, GatedItems>::new();
+    let Some(lib) = package.targets.iter().find(|t| {
+        t.kind
+            .iter()
+            .any(|k| k == "lib" || k == "proc-macro" || k == "rlib")
+    }) else {
+        return gated;
+    };
+    let Some(src_dir) = lib.src_path.parent() else {
+        return gated;
+    };
+
+    for file in rust_files(src_dir.as_std_path()) {
+        let Ok(source) = std::fs::read_to_string(&file) else {
+            continue;
+        };
+        scan_file(&file, &source, &mut gated);
+    }
+
+    gated
+}
+
+fn scan_file(file: &Path, source: &str, gated: &mut HashMap<String, GatedItems>) {
+    let lines = source.lines().collect::<Vec<_>>();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim_start();
+        let inner = line.starts_with("#![cfg(");
+        if !inner && !line.starts_with("#[cfg(") {
+            i += 1;
+            continue;
+        }
+
+        let (attribute, end) = attribute_text(&lines, i);
+        i = end + 1;
+        let features = gating_features(&attribute);
+        if features.is_empty() {
+            continue;
+        }
+
+        let items = if inner {
+            // the whole file is gated
+            let mut items = GatedItems::default();
+            if let Some(name) = module_name(file) {
+                items.names.insert(name);
+            }
+            let top_level = lines.iter().filter(|l| !l.starts_with(char::is_whitespace));
+            for line in top_level {
+                if let Some(item) = declared_item(line) {
+                    add_item(&mut items, item);
+                }
+            }
+            items
+        } else {
+            let mut items = GatedItems::default();
+            // skip other attributes and doc comments of the item
+            while i < lines.len() {
+                let line = lines[i].trim_start();
+                if line.starts_with("#[") {
+                    i = attribute_text(&lines, i).1 + 1;
+                } else if line.starts_with("//") || line.is_empty() {
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+            if let Some(line) = lines.get(i) {
+                let line = if line.trim_start().starts_with("pub use") {
+                    use_statement(&lines, i)
+                } else {
+                    line.to_string()
+                };
+                if let Some(item) = declared_item(&line) {
+                    add_item(&mut items, item);
+                }
+            }
+            items
+        };
+
+        for feature in features {
+            let entry = gated.entry(feature).or_default();
+            entry.names.extend(items.names.iter().cloned());
+            entry.opaque += items.opaque;
+        }
+    }
+}
+
+/// The attribute starting at line `start`, possibly spanning multiple lines,
+/// and the index of its last line.
+fn attribute_text(lines: &[&str], start: usize) -> (String, usize) {
+    let mut text = String::new();
+    let mut depth = 0i32;
+    for (i, line) in lines.iter().enumerate().skip(start) {
+        for c in line.chars() {
+            text.push(c);
+            match c {
+                '[' => depth += 1,
+                ']' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return (text, i);
+                    }
+                }
+                _ => {}
+            }
+        }
+        text.push(' ');
+    }
+    (text, lines.len().saturating_sub(1))
+}
+
+/// A `pub use` statement that might span multiple lines.
+fn use_statement(lines: &[&str], start: usize) -> String {
+    let mut text = String::new();
+    for line in &lines[start..] {
+        text.push_str(line.trim());
+        text.push(' ');
+        if line.contains(';') {
+            break;
+        }
+    }
+    text
+}
+
+/// Features in a `cfg` attribute that are required, i.e. not negated.
+fn gating_features(attribute: &str) -> Vec<String> {
+    let mut features = Vec::new();
+    let mut rest = attribute;
+    while let Some(pos) = rest.find("feature") {
+        let before = rest[..pos].trim_end();
+        let after = rest[pos + "feature".len()..].trim_start();
+        // e.g. `target_feature`
+        let in_word = rest[..pos].ends_with(|c: char| c.is_alphanumeric() || c == '_');
+        rest = &rest[pos + "feature".len()..];
+        if in_word {
+            continue;
+        }
+
+        let Some(value) = after.strip_prefix('=') else {
+            continue;
+        };
+        let Some(value) = value.trim_start().strip_prefix('"') else {
+            continue;
+        };
+        let Some(end) = value.find('"') else {
+            continue;
+        };
+        if before.ends_with("not(") {
+            continue;
+        }
+        features.push(value[..end].to_string());
+    }
+    features
+}
+
+enum Item {
+    Named(Vec<String>),
+    Opaque,
+}
+
+fn add_item(items: &mut GatedItems, item: Item) {
+    match item {
+        Item::Named(names) => items.names.extend(names),
+        Item::Opaque => items.opaque += 1,
+    }
+}
+
+/// The public item declared by `line`, if any. Private items can't be used by
+/// dependents and are ignored.
+fn declared_item(line: &str) -> Option<Item> {
+    let line = line.trim_start();
+    if line.starts_with("impl") || line.starts_with("unsafe impl") {
+        return Some(Item::Opaque);
+    }
+    if let Some(rest) = line.strip_prefix("macro_rules!") {
+        return identifier(rest).map(|name| Item::Named(vec![name]));
+    }
+
+    let rest = line.strip_prefix("pub")?;
+    let rest = if rest.starts_with('(') {
+        // pub(crate) and friends are not visible to dependents
+        return None;
+    } else {
+        rest.trim_start()
+    };
+
+    if let Some(rest) = rest.strip_prefix("use ") {
+        return Some(use_names(rest));
+    }
+
+    let mut words = rest.split_whitespace();
+    loop {
+        let word = words.next()?;
+        match word {
+            "unsafe" | "async" | "const" | "extern" | "mut" | "\"C\"" => {
+                if word == "const" {
+                    // `pub const NAME` vs `pub const fn name`
+                    let next = words.clone().next()?;
+                    if !matches!(next, "fn" | "unsafe" | "async" | "extern") {
+                        return identifier(next).map(|name| Item::Named(vec![name]));
+                    }
+                }
+                continue;
+            }
+            "fn" | "struct" | "enum" | "trait" | "type" | "union" | "mod" | "static" => {
+                let mut next = words.next()?;
+                if next == "mut" {
+                    next = words.next()?;
+                }
+                return identifier(next).map(|name| Item::Named(vec![name]));
+            }
+            _ => return None,
+        }
+    }
+}
+
+/// Names brought into scope by a `use` tree, e.g. `a::{b, c as d};`.
+fn use_names(tree: &str) -> Item {
+    let tree = tree.split(';').next().unwrap_or(tree);
+    let mut names = Vec::new();
+    for leaf in tree.split([',', '{', '}']) {
+        let leaf = leaf.trim();
+        if leaf.is_empty() {
+            continue;
+        }
+        let name = match leaf.split_once(" as ") {
+            Some((_, alias)) => alias.trim(),
+            None => leaf.rsplit("::").next().unwrap_or(leaf),
+        };
+        match name {
+            "*" => return Item::Opaque,
+            "self" | "_" | "" => {}
+            name => names.extend(identifier(name)),
+        }
+    }
+    if names.is_empty() {
+        Item::Opaque
+    } else {
+        Item::Named(names)
+    }
+}
+
+fn identifier(text: &str) -> Option<String> {
+    let name = text
+        .trim_start()
+        .trim_start_matches("r#")
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .next()?;
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
+
+fn module_name(file: &Path) -> Option<String> {
+    let stem = file.file_stem()?.to_str()?;
+    if stem == "mod" || stem == "lib" {
+        file.parent()?
+            .file_name()?
+            .to_str()
+            .map(ToString::to_string)
+    } else {
+        Some(stem.to_string())
+    }
+}
+
+/// All identifiers appearing in the sources of the targets of a workspace
+/// package.
+fn member_identifiers(member: &Package) -> HashSet<String> {
+    let mut identifiers = HashSet::new();
+    for file in package_rust_files(member) {
+        let Ok(source) = std::fs::read_to_string(&file) else {
+            continue;
+        };
+        identifiers.extend(
+            source
+                .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .filter(|word| !word.is_empty())
+                .map(ToString::to_string),
+        );
+    }
+    identifiers
+}
+
+/// Rust files in the directories of the targets of a package.
+pub(super) fn package_rust_files(package: &Package) -> Vec<PathBuf> {
+    let mut dirs = package
+        .targets
+        .iter()
+        .filter_map(|t| t.src_path.parent())
+        .map(|dir| dir.as_std_path().to_path_buf())
+        .collect::<Vec<_>>();
+    dirs.sort();
+    dirs.dedup();
+
+    let mut files = dirs
+        .iter()
+        .flat_map(|dir| rust_files(dir))
+        .collect::<Vec<_>>();
+    files.sort();
+    files.dedup();
+    files
+}
+
+/// Rust files below `dir`. Build output and nested packages are skipped.
+pub(super) fn rust_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut todo = vec![dir.to_path_buf()];
+    while let Some(dir) = todo.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                if entry.file_name() == "target" || path.join("Cargo.toml").exists() {
+                    continue;
+                }
+                todo.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "rs") {
+                files.push(path);
+            }
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(item: Option<Item>) -> Option<Vec<String>> {
+        match item? {
+            Item::Named(names) => Some(names),
+            Item::Opaque => Some(vec!["<opaque>".to_string()]),
+        }
+    }
+
+    #[test]
+    fn gating() {
+        assert_eq!(gating_features("#[cfg(feature = \"derive\")]"), ["derive"]);
+        assert_eq!(
+            gating_features("#[cfg(all(feature=\"a\", not(feature = \"b\"), feature = \"c\"))]"),
+            ["a", "c"]
+        );
+        assert_eq!(
+            gating_features("#[cfg(any(feature = \"std\", test))]"),
+            ["std"]
+        );
+        assert!(gating_features("#[cfg(target_feature = \"sse2\")]").is_empty());
+        assert!(gating_features("#[cfg(unix)]").is_empty());
+    }
+
+    #[test]
+    fn declared_items() {
+        let item = |line| names(declared_item(line));
+        assert_eq!(item("pub fn parse(s: &str)").unwrap(), ["parse"]);
+        assert_eq!(item("pub struct Value<'a> {").unwrap(), ["Value"]);
+        assert_eq!(item("pub const fn new() -> Self {").unwrap(), ["new"]);
+        assert_eq!(item("pub const MAX: usize = 3;").unwrap(), ["MAX"]);
+        assert_eq!(item("pub static mut COUNT: u8 = 0;").unwrap(), ["COUNT"]);
+        assert_eq!(item("pub unsafe extern \"C\" fn raw()").unwrap(), ["raw"]);
+        assert_eq!(item("pub mod r#async;").unwrap(), ["async"]);
+        assert_eq!(item("macro_rules! json {").unwrap(), ["json"]);
+        assert_eq!(item("impl Serialize for Value {").unwrap(), ["<opaque>"]);
+        // not visible to dependents
+        assert!(item("pub(crate) fn helper()").is_none());
+        assert!(item("fn private()").is_none());
+        assert!(item("let x = 1;").is_none());
+    }
+
+    #[test]
+    fn use_tree_names() {
+        let item = |line| names(declared_item(line));
+        assert_eq!(item("pub use de::Deserialize;").unwrap(), ["Deserialize"]);
+        assert_eq!(
+            item("pub use crate::{ser::Serializer, de::{self, Error as DeError}};").unwrap(),
+            ["Serializer", "DeError"]
+        );
+        assert_eq!(item("pub use self::value::*;").unwrap(), ["<opaque>"]);
+    }
+
+    #[test]
+    fn scan() {
+        let source = r#"
+#[cfg(feature = "json")]
+/// Parses JSON.
+#[inline]
+pub fn from_json() {}
+
+#[cfg(feature = "json")]
+pub use crate::json::{
+    Map,
+    Number as JsonNumber,
+};
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+#[cfg(not(feature = "std"))]
+pub fn no_std() {}
+
+#[cfg(
+    feature = "yaml"
+)]
+pub struct Yaml;
+"#;
+        let mut gated = HashMap::new();
+        scan_file(Path::new("src/lib.rs"), source, &mut gated);
+
+        let json = &gated["json"];
+        let names = json.names.iter().map(String::as_str).collect::<Vec<_>>();
+        assert_eq!(names, ["JsonNumber", "Map", "from_json"]);
+        assert_eq!(json.opaque, 0);
+        assert!(gated["std"].names.is_empty());
+        assert_eq!(gated["std"].opaque, 1);
+        assert_eq!(gated["yaml"].names.iter().collect::<Vec<_>>(), ["Yaml"]);
+        assert_eq!(gated.len(), 3);
+
+        // an inner attribute gates the module and everything in the file
+        let mut gated = HashMap::new();
+        let source = "#![cfg(feature = \"rt\")]\n\npub struct Runtime;\n\nfn private() {}\n";
+        scan_file(Path::new("src/runtime/mod.rs"), source, &mut gated);
+        let names = gated["rt"]
+            .names
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["Runtime", "runtime"]);
+    }
+}
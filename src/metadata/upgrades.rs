@@ -0,0 +1,86 @@
+use cargo_metadata::semver::{Version, VersionReq};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::cargo::RegistryIndex;
+
+/// Newer versions of a dependency than the resolved one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Upgrade {
+    /// The newest version that is semver-compatible with the resolved one.
+    pub compatible: Option<Version>,
+    /// The newest version if it has breaking changes.
+    pub incompatible: Option<Version>,
+}
+
+impl Upgrade {
+    /// The newest version of both.
+    pub fn newest(&self) -> Option<&Version> {
+        self.incompatible.as_ref().or(self.compatible.as_ref())
+    }
+}
+
+/// Looks up upgrades in the local registry index, see [`RegistryIndex`].
+#[derive(Debug, Default)]
+pub struct Upgrades {
+    index: Option<RegistryIndex>,
+    /// Stable, non-yanked versions by crate, newest first.
+    versions: Mutex<HashMap<String, Arc<[Version]>>>,
+}
+
+impl Upgrades {
+    /// Without an index no upgrades are found.
+    pub fn open() -> Self {
+        let index = match RegistryIndex::open() {
+            Ok(index) => Some(index),
+            Err(err) => {
+                warn!("no registry index to look up upgrades: {err}");
+                None
+            }
+        };
+        Self {
+            index,
+            versions: Default::default(),
+        }
+    }
+
+    /// Forgets the versions looked up so far, e.g. after cargo updated the
+    /// index cache. The index itself is not read again.
+    pub fn clear(&self) {
+        self.versions.lock().unwrap().clear();
+    }
+
+    fn versions(&self, name: &str) -> Arc<[Version]> {
+        if let Some(versions) = self.versions.lock().unwrap().get(name) {
+            return versions.clone();
+        }
+        let versions = self
+            .index
+            .as_ref()
+            .and_then(|index| index.versions(name).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|v| !v.yanked && v.version.pre.is_empty())
+            .map(|v| v.version)
+            .collect::<Arc<[_]>>();
+        self.versions
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), versions.clone());
+        versions
+    }
+
+    pub fn upgrade(&self, name: &str, resolved: &Version) -> Upgrade {
+        let compatible_req = VersionReq::parse(&format!("^{resolved}")).ok();
+        let is_compatible = |v: &Version| compatible_req.as_ref().is_some_and(|req| req.matches(v));
+
+        let versions = self.versions(name);
+        let newer = versions.iter().filter(|v| *v > resolved);
+        let compatible = newer.clone().find(|v| is_compatible(v)).cloned();
+        let incompatible = newer.take(1).find(|v| !is_compatible(v)).cloned();
+        Upgrade {
+            compatible,
+            incompatible,
+        }
+    }
+}
@@ -1,21 +1,44 @@
-use eyre::Result;
-use std::{path::PathBuf, rc::Rc};
+use eyre::{Context, Result};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 
 use cargo_metadata::{DependencyKind, Metadata, Package, PackageId};
 use cargo_toml::Manifest;
+use toml_edit::{DocumentMut, Item};
 
-use crate::cargo;
+use crate::{
+    cargo,
+    config::{config, Filter},
+};
 
 use super::{
     dep_tree::{DepTree, FeatureStatus},
+    timings::Timings,
+    toml::table_name,
+    unused_features::SourceScanner,
+    upgrades::Upgrades,
     PackageResolver,
 };
 
+/// A feature of a dependency of a workspace member to turn on or off.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeatureToggle {
+    pub package: PackageId,
+    pub dep_name: String,
+    pub dep_kind: DependencyKind,
+    pub feature: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct WorkspaceInfo {
     pub manifest_path: PathBuf,
-    pub metadata: Rc<Metadata>,
+    pub metadata: Arc<Metadata>,
     pub manifest: Manifest,
+    /// Compile times, if a timings report was loaded.
+    pub timings: Option<Timings>,
+    pub upgrades: Arc<Upgrades>,
+    /// Shared by the clones, so sources are not scanned on every rebuild.
+    pub sources: Arc<Mutex<SourceScanner>>,
 }
 
 impl WorkspaceInfo {
@@ -28,7 +51,7 @@ impl WorkspaceInfo {
             eyre::bail!("{manifest_path:?} not found");
         }
 
-        let metadata = Rc::new(
+        let metadata = Arc::new(
             cargo_metadata::MetadataCommand::new()
                 .manifest_path(&manifest_path)
                 .exec()?,
@@ -40,16 +63,27 @@ impl WorkspaceInfo {
             manifest_path,
             metadata,
             manifest,
+            timings: None,
+            upgrades: Arc::new(Upgrades::open()),
+            sources: Default::default(),
         })
     }
 
+    pub fn load_timings(&mut self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        self.timings = Some(Timings::load(path)?);
+        Ok(())
+    }
+
     pub fn update(&mut self) -> Result<()> {
-        self.metadata = Rc::new(
+        self.metadata = Arc::new(
             cargo_metadata::MetadataCommand::new()
                 .manifest_path(&self.manifest_path)
                 .exec()?,
         );
         self.manifest = Manifest::from_path(&self.manifest_path)?;
+        // cargo might have updated the index cache
+        self.upgrades.clear();
+        self.sources.lock().unwrap().forget_members();
         Ok(())
     }
 
@@ -61,32 +95,126 @@ impl WorkspaceInfo {
         feature_name: String,
         _feature_status: FeatureStatus,
     ) -> Result<()> {
-        let Some(package) = self.metadata.packages.iter().find(|p| p.id == pkg) else {
+        self.edit_dependency(&pkg, &dep_name, dep_kind, |edit| {
+            edit.toggle_feature(feature_name)
+        })
+    }
+
+    /// Toggles all `features` with one edit per dependency.
+    pub fn toggle_features(&mut self, features: &[FeatureToggle]) -> Result<()> {
+        let mut by_dependency: Vec<(&FeatureToggle, Vec<&str>)> = Vec::new();
+        for toggle in features {
+            let group = by_dependency.iter_mut().find(|(first, _)| {
+                first.package == toggle.package
+                    && first.dep_name == toggle.dep_name
+                    && first.dep_kind == toggle.dep_kind
+            });
+            match group {
+                Some((_, features)) => features.push(&toggle.feature),
+                None => by_dependency.push((toggle, vec![&toggle.feature])),
+            }
+        }
+
+        for (dep, features) in by_dependency {
+            self.edit_dependency(&dep.package, &dep.dep_name, dep.dep_kind, |edit| {
+                features
+                    .into_iter()
+                    .fold(edit, |edit, feature| edit.toggle_feature(feature))
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Turns the default features of a dependency on or off and adds
+    /// `features`, e.g. the ones of `default` that are still needed.
+    pub fn set_default_features(
+        &mut self,
+        pkg: PackageId,
+        dep_name: String,
+        dep_kind: DependencyKind,
+        enabled: bool,
+        features: Vec<String>,
+    ) -> Result<()> {
+        self.edit_dependency(&pkg, &dep_name, dep_kind, |mut edit| {
+            edit = edit.default_features(enabled);
+            if !enabled {
+                // `features = ["default"]` enables them as well
+                edit = edit.remove_feature("default");
+            }
+            features
+                .into_iter()
+                .fold(edit, |edit, feature| edit.add_feature(feature))
+        })
+    }
+
+    /// Removes `features` of a dependency, and its default features unless
+    /// `default_features`, e.g. the ones `minimize` found to be unneeded.
+    pub fn remove_features(
+        &mut self,
+        pkg: PackageId,
+        dep_name: String,
+        dep_kind: DependencyKind,
+        default_features: bool,
+        features: Vec<String>,
+    ) -> Result<()> {
+        self.edit_dependency(&pkg, &dep_name, dep_kind, |mut edit| {
+            if !default_features {
+                edit = edit.default_features(false);
+            }
+            features
+                .into_iter()
+                .fold(edit, |edit, feature| edit.remove_feature(feature))
+        })
+    }
+
+    fn edit_dependency<'a>(
+        &'a self,
+        pkg: &PackageId,
+        dep_name: &'a str,
+        dep_kind: DependencyKind,
+        edit: impl FnOnce(cargo::EditDependency<'a>) -> cargo::EditDependency<'a>,
+    ) -> Result<()> {
+        let Some(package) = self.metadata.packages.iter().find(|p| &p.id == pkg) else {
             eyre::bail!("Package not found");
         };
 
-        let manifest_deps = match dep_kind {
-            DependencyKind::Normal | DependencyKind::Unknown => &self.manifest.dependencies,
-            DependencyKind::Development => &self.manifest.dev_dependencies,
-            DependencyKind::Build => &self.manifest.build_dependencies,
-        };
-        let dep = manifest_deps.get(&dep_name).ok_or_else(|| {
-            eyre::eyre!(
-                "Dependency {dep_name:?} not found in manifest {:?}",
-                self.manifest_path
-            )
-        })?;
-
-        let inherited = dep.detail().and_then(|d| {
-            if d.inherited {
-                Some(&self.metadata.workspace_root)
-            } else {
-                None
-            }
-        });
+        // the dependency is declared in the manifest of the member, not the
+        // one of the workspace
+        let manifest_path = package.manifest_path.as_std_path();
+        let doc = std::fs::read_to_string(manifest_path)
+            .with_context(|| format!("reading {manifest_path:?}"))?
+            .parse::<DocumentMut>()?;
+        let key = package
+            .dependencies
+            .iter()
+            .find(|dep| dep.name == dep_name && dep.kind == dep_kind)
+            .and_then(|dep| dep.rename.as_deref())
+            .unwrap_or(dep_name);
+        let table_name = table_name(dep_kind);
+        let targets = doc
+            .get("target")
+            .and_then(Item::as_table_like)
+            .into_iter()
+            .flat_map(|targets| targets.iter().map(|(_, target)| target));
+        let entries = std::iter::once(doc.as_item())
+            .chain(targets)
+            .filter_map(|table| table.get(table_name)?.get(key))
+            .collect::<Vec<_>>();
+        if entries.is_empty() {
+            eyre::bail!("Dependency {key:?} not found in manifest {manifest_path:?}");
+        }
 
-        cargo::EditDependency::new(package, &dep_name, dep_kind)
-            .toggle_feature(feature_name)
+        let inherited = entries
+            .iter()
+            .any(|entry| {
+                entry
+                    .get("workspace")
+                    .and_then(Item::as_bool)
+                    .unwrap_or(false)
+            })
+            .then_some(&self.metadata.workspace_root);
+
+        edit(cargo::EditDependency::new(package, dep_name, dep_kind).verify(config().verify_edits))
             .set_workspace_dependency_at(inherited)
             .apply()?;
 
@@ -101,7 +229,131 @@ impl WorkspaceInfo {
         PackageResolver::new(&self.metadata)
     }
 
-    pub fn tree(&self) -> DepTree {
-        DepTree::build(self)
+    pub fn tree(&self, filter: &Filter) -> DepTree {
+        DepTree::build(self, filter)
+    }
+
+    pub fn tree_by_dependency(&self, filter: &Filter) -> DepTree {
+        DepTree::build_by_dependency(self, filter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A virtual workspace whose members `a`, `b` and `c` depend on the
+    /// member `util`, which has the features `x` and `y`. `d` inherits it
+    /// from `[workspace.dependencies]`.
+    fn workspace() -> (tempfile::TempDir, WorkspaceInfo) {
+        let dir = tempfile::Builder::new()
+            .prefix("cargo-oh-my-dependencies")
+            .tempdir()
+            .unwrap();
+        let files = [
+            ("Cargo.toml", "[workspace]\nmembers = [\"a\", \"b\", \"c\", \"d\", \"util\"]\nresolver = \"2\"\n\n[workspace.dependencies]\nutil = { path = \"util\" }\n"),
+            ("util/Cargo.toml", "[package]\nname = \"util\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[features]\nx = []\ny = []\n"),
+            ("a/Cargo.toml", "[package]\nname = \"a\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\nutil = { path = \"../util\" }\n"),
+            ("b/Cargo.toml", "[package]\nname = \"b\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dev-dependencies]\nutil = { path = \"../util\", features = [\"x\"] }\n"),
+            ("c/Cargo.toml", "[package]\nname = \"c\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\nutil = { path = \"../util\", features = [\"x\"] }\n"),
+            ("d/Cargo.toml", "[package]\nname = \"d\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\nutil = { workspace = true, features = [\"x\", \"y\"] }\n"),
+        ];
+        for (path, content) in files {
+            let path = dir.path().join(path);
+            let src = path.parent().unwrap().join("src");
+            std::fs::create_dir_all(&src).unwrap();
+            std::fs::write(src.join("lib.rs"), "").unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        let info = WorkspaceInfo::load(dir.path()).unwrap();
+        (dir, info)
+    }
+
+    fn member(info: &WorkspaceInfo, name: &str) -> PackageId {
+        let package = info.metadata.packages.iter().find(|p| p.name == name);
+        package.unwrap().id.clone()
+    }
+
+    fn util_features(info: &WorkspaceInfo, name: &str) -> Vec<String> {
+        let package = info.metadata.packages.iter().find(|p| p.name == name);
+        let dep = package
+            .unwrap()
+            .dependencies
+            .iter()
+            .find(|d| d.name == "util");
+        let mut features = dep.unwrap().features.clone();
+        features.sort();
+        features
+    }
+
+    #[test]
+    fn toggle_member_feature() {
+        let (_dir, mut info) = workspace();
+        let a = member(&info, "a");
+        info.toggle_feature(
+            a,
+            "util".to_string(),
+            DependencyKind::Normal,
+            "y".to_string(),
+            FeatureStatus::Disabled,
+        )
+        .unwrap();
+        let b = member(&info, "b");
+        info.toggle_feature(
+            b,
+            "util".to_string(),
+            DependencyKind::Development,
+            "x".to_string(),
+            FeatureStatus::Enabled,
+        )
+        .unwrap();
+
+        info.update().unwrap();
+        assert_eq!(util_features(&info, "a"), vec!["y"]);
+        assert!(util_features(&info, "b").is_empty());
+    }
+
+    #[test]
+    fn toggle_feature_everywhere() {
+        let (_dir, mut info) = workspace();
+        let features = ["a", "c"]
+            .into_iter()
+            .map(|name| FeatureToggle {
+                package: member(&info, name),
+                dep_name: "util".to_string(),
+                dep_kind: DependencyKind::Normal,
+                feature: "y".to_string(),
+            })
+            .collect::<Vec<_>>();
+        info.toggle_features(&features).unwrap();
+
+        info.update().unwrap();
+        assert_eq!(util_features(&info, "a"), vec!["y"]);
+        assert_eq!(util_features(&info, "b"), vec!["x"]);
+        assert_eq!(util_features(&info, "c"), vec!["x", "y"]);
+    }
+
+    #[test]
+    fn remove_inherited_features() {
+        let (dir, mut info) = workspace();
+        let d = member(&info, "d");
+        info.remove_features(
+            d,
+            "util".to_string(),
+            DependencyKind::Normal,
+            true,
+            vec!["y".to_string()],
+        )
+        .unwrap();
+
+        info.update().unwrap();
+        assert_eq!(util_features(&info, "d"), vec!["x"]);
+        let manifest = std::fs::read_to_string(dir.path().join("d/Cargo.toml")).unwrap();
+        assert!(
+            manifest.contains(r#"util = { workspace = true, features = ["x"] }"#),
+            "{manifest}"
+        );
+        let root = std::fs::read_to_string(dir.path().join("Cargo.toml")).unwrap();
+        assert!(root.contains("util = { path = \"util\" }"), "{root}");
     }
 }
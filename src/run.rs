@@ -4,34 +4,37 @@ use crossterm::event;
 
 use crate::{action::Action, component::Component, components::app, tui, Args};
 
+/// How long to wait for an event before sending [`Action::Tick`].
+const TICK_RATE: Duration = Duration::from_millis(100);
+
 /// runs the application's main loop until the user quits
 pub fn run_loop(args: Args, terminal: &mut tui::Tui) -> eyre::Result<()> {
     let mut app = app::App::new(args)?;
+    let mut render = true;
 
     loop {
-        let bounds = terminal.size()?;
-        terminal.draw(|frame| {
-            app.render(frame, bounds);
-        })?;
+        if render {
+            let bounds = terminal.size()?;
+            terminal.draw(|frame| {
+                app.render(frame, bounds);
+            })?;
+            render = false;
+        }
 
-        loop {
-            if event::poll(Duration::from_millis(1000))? {
-                let event = event::read()?;
-                info!("event: {:?}", event);
-                let mut action = Component::handle_events(&mut app, event)?;
-                let mut render = false;
-                while let Some(this_action) = action.take() {
-                    let quit = matches!(this_action, Action::Quit);
-                    render = matches!(this_action, Action::Render);
-                    action = app.update(this_action)?;
-                    if quit {
-                        return Ok(());
-                    }
-                }
-                if render {
-                    break;
-                }
+        let mut action = if event::poll(TICK_RATE)? {
+            let event = event::read()?;
+            info!("event: {:?}", event);
+            Component::handle_events(&mut app, event)?
+        } else {
+            Some(Action::Tick)
+        };
+        while let Some(this_action) = action.take() {
+            match this_action {
+                Action::Quit => return Ok(()),
+                Action::Render => render = true,
+                _ => {}
             }
+            action = app.update(this_action)?;
         }
     }
 }
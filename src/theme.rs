@@ -0,0 +1,227 @@
+//! Colors and icons of the UI, chosen with the `theme` and `icons` settings
+//! of the [config](crate::config).
+
+use ratatui::prelude::*;
+use std::sync::OnceLock;
+
+use crate::config::{config, IconSet, ThemeName};
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// The theme of the active config.
+pub fn theme() -> &'static Theme {
+    THEME.get_or_init(|| Theme::new(config().theme, config().icons))
+}
+
+#[derive(Debug)]
+pub struct Icons {
+    pub enabled: &'static str,
+    pub indirectly_enabled: &'static str,
+    pub disabled: &'static str,
+    pub unknown: &'static str,
+    pub upgrade: &'static str,
+    pub marked: &'static str,
+    /// Placeholder of children that are not loaded yet.
+    pub pending: &'static str,
+    pub node_closed: &'static str,
+    pub node_open: &'static str,
+    /// Frames of the busy indicator.
+    pub spinner: &'static [&'static str],
+}
+
+impl Icons {
+    const UNICODE: Icons = Icons {
+        enabled: "✓",
+        indirectly_enabled: "—",
+        disabled: " ",
+        unknown: "?",
+        upgrade: "⇡",
+        marked: "●",
+        pending: "…",
+        node_closed: "▶ ",
+        node_open: "▼ ",
+        spinner: &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"],
+    };
+
+    const ASCII: Icons = Icons {
+        enabled: "x",
+        indirectly_enabled: "~",
+        disabled: " ",
+        unknown: "?",
+        upgrade: "^",
+        marked: "*",
+        pending: "...",
+        node_closed: "> ",
+        node_open: "v ",
+        spinner: &["|", "/", "-", "\\"],
+    };
+}
+
+#[derive(Debug)]
+pub struct Theme {
+    pub icons: Icons,
+    /// Whether colors are used at all. Colored text from elsewhere, like
+    /// errors, should be stripped if not.
+    pub colored: bool,
+    /// Names in breadcrumbs and dialog texts.
+    pub emphasis: Style,
+    /// Secondary text like versions and help labels.
+    pub dim: Style,
+    /// Keys in the help.
+    pub key: Style,
+    /// The selected row.
+    pub highlight: Style,
+    pub warning: Style,
+    pub error: Style,
+    pub package: Style,
+    pub dependency: Style,
+    pub transitive_dependency: Style,
+    /// Features of workspace packages and feature entries that refer to
+    /// another feature.
+    pub package_feature: Style,
+    /// `dep:name` entries.
+    pub dep_entry: Style,
+    /// `name/feature` entries.
+    pub dep_feature_entry: Style,
+    pub feature_enabled: Style,
+    pub feature_indirectly_enabled: Style,
+    pub feature_disabled: Style,
+    pub default_features: Style,
+    pub upgrade_compatible: Style,
+    pub upgrade_incompatible: Style,
+    /// Treemap rectangles of workspace members, their direct dependencies and
+    /// the transitive ones. Selected rectangles are reversed.
+    pub map_member: Style,
+    pub map_dependency: Style,
+    pub map_transitive: Style,
+    pub map_dev_dependency: Style,
+    pub map_build_dependency: Style,
+    pub map_feature: Style,
+}
+
+impl Theme {
+    pub fn new(name: ThemeName, icons: IconSet) -> Self {
+        let icons = match icons {
+            IconSet::Unicode => Icons::UNICODE,
+            IconSet::Ascii => Icons::ASCII,
+        };
+        let plain = Style::default();
+        match name {
+            ThemeName::Dark => Self {
+                icons,
+                colored: true,
+                emphasis: plain.bold(),
+                dim: plain.dim(),
+                key: plain.blue(),
+                highlight: plain.on_dark_gray(),
+                warning: plain.yellow(),
+                error: plain.red(),
+                package: plain.white().bold(),
+                dependency: plain.white(),
+                transitive_dependency: plain.gray(),
+                package_feature: plain.magenta(),
+                dep_entry: plain.blue(),
+                dep_feature_entry: plain.green(),
+                feature_enabled: plain.green().bold(),
+                feature_indirectly_enabled: plain.green(),
+                feature_disabled: plain.white(),
+                default_features: plain.cyan(),
+                upgrade_compatible: plain.green(),
+                upgrade_incompatible: plain.red(),
+                map_member: plain.yellow(),
+                map_dependency: plain.green(),
+                map_transitive: plain.blue(),
+                map_dev_dependency: plain.blue(),
+                map_build_dependency: plain.yellow(),
+                map_feature: plain.magenta(),
+            },
+            // the terminal's own foreground instead of white, darker grays
+            ThemeName::Light => Self {
+                icons,
+                colored: true,
+                emphasis: plain.bold(),
+                dim: plain.dark_gray(),
+                key: plain.blue(),
+                highlight: plain.on_gray(),
+                warning: plain.fg(Color::Indexed(130)),
+                error: plain.red(),
+                package: plain.bold(),
+                dependency: plain,
+                transitive_dependency: plain.dark_gray(),
+                package_feature: plain.magenta(),
+                dep_entry: plain.blue(),
+                dep_feature_entry: plain.green(),
+                feature_enabled: plain.green().bold(),
+                feature_indirectly_enabled: plain.green(),
+                feature_disabled: plain,
+                default_features: plain.fg(Color::Indexed(30)),
+                upgrade_compatible: plain.green(),
+                upgrade_incompatible: plain.red(),
+                map_member: plain.fg(Color::Indexed(130)),
+                map_dependency: plain.green(),
+                map_transitive: plain.blue(),
+                map_dev_dependency: plain.blue(),
+                map_build_dependency: plain.fg(Color::Indexed(130)),
+                map_feature: plain.magenta(),
+            },
+            // bright colors and no dimmed text
+            ThemeName::HighContrast => Self {
+                icons,
+                colored: true,
+                emphasis: plain.bold(),
+                dim: plain,
+                key: plain.light_cyan().bold(),
+                highlight: plain.reversed(),
+                warning: plain.light_yellow(),
+                error: plain.light_red(),
+                package: plain.white().bold(),
+                dependency: plain.white(),
+                transitive_dependency: plain,
+                package_feature: plain.light_magenta(),
+                dep_entry: plain.light_blue(),
+                dep_feature_entry: plain.light_green(),
+                feature_enabled: plain.light_green().bold(),
+                feature_indirectly_enabled: plain.light_green(),
+                feature_disabled: plain.white(),
+                default_features: plain.light_cyan(),
+                upgrade_compatible: plain.light_green(),
+                upgrade_incompatible: plain.light_red().bold(),
+                map_member: plain.light_yellow(),
+                map_dependency: plain.light_green(),
+                map_transitive: plain.light_blue(),
+                map_dev_dependency: plain.light_blue(),
+                map_build_dependency: plain.light_yellow(),
+                map_feature: plain.light_magenta(),
+            },
+            // modifiers only, the icons tell the states apart
+            ThemeName::Monochrome => Self {
+                icons,
+                colored: false,
+                emphasis: plain.bold(),
+                dim: plain.dim(),
+                key: plain.bold(),
+                highlight: plain.reversed(),
+                warning: plain.italic(),
+                error: plain.bold(),
+                package: plain.bold(),
+                dependency: plain,
+                transitive_dependency: plain.dim(),
+                package_feature: plain.italic(),
+                dep_entry: plain,
+                dep_feature_entry: plain,
+                feature_enabled: plain.bold(),
+                feature_indirectly_enabled: plain,
+                feature_disabled: plain,
+                default_features: plain.italic(),
+                upgrade_compatible: plain,
+                upgrade_incompatible: plain.bold(),
+                map_member: plain.bold(),
+                map_dependency: plain,
+                map_transitive: plain.dim(),
+                map_dev_dependency: plain.italic(),
+                map_build_dependency: plain.dim(),
+                map_feature: plain,
+            },
+        }
+    }
+}
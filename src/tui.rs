@@ -1,4 +1,8 @@
-use crossterm::{execute, terminal::*};
+use crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::*,
+};
 use ratatui::prelude::*;
 use std::io::{self, stdout, Stdout};
 
@@ -8,7 +12,7 @@ impl Tui {
     /// Initialize the terminal
     pub fn new() -> io::Result<Self> {
         debug!("initializing tui");
-        execute!(stdout(), EnterAlternateScreen)?;
+        execute!(stdout(), EnterAlternateScreen, EnableMouseCapture)?;
         enable_raw_mode()?;
         Ok(Self(Terminal::new(CrosstermBackend::new(stdout()))?))
     }
@@ -20,7 +24,9 @@ impl Tui {
 }
 
 pub fn restore() {
-    if let Err(err) = execute!(stdout(), LeaveAlternateScreen).and_then(|_| disable_raw_mode()) {
+    if let Err(err) = execute!(stdout(), DisableMouseCapture, LeaveAlternateScreen)
+        .and_then(|_| disable_raw_mode())
+    {
         error!("failed to restore tui: {err}");
     }
 }